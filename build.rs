@@ -8,8 +8,14 @@ fn main() -> Result<(), std::io::Error> {
 
     let mut string_to_write = "".to_string();
 
-    for path in fs::read_dir(out_dir).unwrap() {
-        let plugin = path.unwrap().file_name().into_string().unwrap();
+    // sorted, so that the order the interpreters are tried in (on a tie) is the same on every
+    // machine
+    let mut plugins: Vec<String> = fs::read_dir(out_dir)?
+        .map(|path| path.unwrap().file_name().into_string().unwrap())
+        .collect();
+    plugins.sort();
+
+    for plugin in &plugins {
         if plugin == "mod.rs" {
             continue;
         }
@@ -28,14 +34,13 @@ fn main() -> Result<(), std::io::Error> {
 ",
     );
 
-    for path in fs::read_dir(out_dir).unwrap() {
-        let mut plugin = path.unwrap().file_name().into_string().unwrap();
+    for plugin in &plugins {
         if plugin == "mod.rs" || plugin == "import.rs" {
             continue;
         }
-        plugin = plugin[..plugin.len() - 3].to_string();
+        let plugin = &plugin[..plugin.len() - 3];
 
-        string_to_write.push_str("{");
+        string_to_write.push('{');
         string_to_write.push_str(&format!(
             "
             type Current = interpreters::{};
@@ -56,9 +61,6 @@ fn main() -> Result<(), std::io::Error> {
 
     // cargo stuff for rebuild
 
-    for path in fs::read_dir(out_dir).unwrap() {
-        let _plugin_path = path.unwrap().path().display();
-    }
     println!(
         "cargo:rerun-if-changed=build.rs
                              "
@@ -5,12 +5,33 @@ endif
 
 
 let s:SnipRun = 'run'
+let s:SnipRunFile = 'run_file'
+let s:SnipRunToEnd = 'run_to_eof'
 let s:SnipTerminate = 'terminate'
 let s:SnipClean = "clean"
+let s:SnipSetLogLevel = "set_log_level"
+let s:SnipShowLog = "show_log"
+let s:SnipShowCode = "show_code"
+let s:SnipShowHistory = "show_history"
+let s:SnipInfo = "info"
+let s:SnipEnableLive = "enable_live"
+let s:SnipDisableLive = "disable_live"
+let s:SnipBufferChanged = "buffer_changed"
+let s:SnipStop = "stop"
+let s:SnipReplay = "replay"
+let s:SnipCapabilities = "capabilities"
 
 let s:scriptdir = resolve(expand('<sfile>:p:h') . '/..')
+
+" User configuration, sent to sniprun on every run
+if !exists('g:sniprun_config')
+  let g:sniprun_config = {}
+endif
 let s:bin= s:scriptdir.'/target/release/sniprun'
 
+" Lines errors come from are highlighted with this group
+highlight default link SniprunErrorLine SpellBad
+
 " Entry point. Initialize RPC. If it succeeds, then attach commands to the `rpcnotify` invocations.
 function! s:connect()
   let id = s:initRpc()
@@ -23,6 +44,9 @@ function! s:connect()
     let s:sniprunJobId = id
 
     call s:configureCommands()
+    if has_key(g:sniprun_config, 'log_level')
+      call s:setloglevel(g:sniprun_config['log_level'])
+    endif
   endif
 endfunction
 
@@ -31,15 +55,77 @@ endfunction
 
 function! s:configureCommands()
   command! -range SnipRun <line1>,<line2>call s:run()
+  command! SnipRunFile :call s:runfile()
+  command! SnipRunToEnd :call s:runtoend()
   command! SnipTerminate :call s:terminate()
-  command! SnipReset :call s:clean()| :call s:terminate()
+  command! -nargs=? SnipStop :call s:stop(<q-args>)
+  command! SnipReplay :call s:replay()
+  command! -nargs=? SnipReset :call s:clean(<q-args>)| :call s:terminate()
+  command! SnipLog :call s:showlog()
+  command! SnipShowCode :call s:showcode()
+  command! -nargs=? SnipHistory :call s:showhistory(<q-args>)
+  command! -range SnipCheck <line1>,<line2>call s:check()
+  command! -range SnipInfo <line1>,<line2>call s:info()
+  command! -range SnipLive <line1>,<line2>call s:enablelive()
+  command! SnipLiveOff :call s:disablelive()
+  command! -nargs=1 SnipLogLevel :call s:setloglevel(<q-args>)
 endfunction
 
 
 function! s:run() range
   let s:fl=a:firstline
   let s:ll=a:lastline
-  call rpcnotify(s:sniprunJobId, s:SnipRun, str2nr(s:fl), str2nr(s:ll), s:scriptdir)
+  call rpcnotify(s:sniprunJobId, s:SnipRun, str2nr(s:fl), str2nr(s:ll), s:scriptdir, g:sniprun_config)
+endfunction
+
+function! s:check() range
+  call rpcnotify(s:sniprunJobId, s:SnipRun, str2nr(a:firstline), str2nr(a:lastline), s:scriptdir, extend(copy(g:sniprun_config), {'compile_only': v:true}))
+endfunction
+
+function! s:info() range
+  call rpcnotify(s:sniprunJobId, s:SnipInfo, str2nr(a:firstline), str2nr(a:lastline), s:scriptdir, g:sniprun_config)
+endfunction
+
+" Run several ranges of lines of the current buffer as one snippet, eg: [[1, 3], [10, 12]]
+function! SnipRunRanges(ranges)
+  call rpcnotify(s:sniprunJobId, s:SnipRun, a:ranges, 0, s:scriptdir, g:sniprun_config)
+endfunction
+
+" Ask what sniprun can do, the answer is in g:sniprun_capabilities when the
+" User SniprunCapabilities autocommand is triggered
+function! SnipCapabilities()
+  call rpcnotify(s:sniprunJobId, s:SnipCapabilities)
+endfunction
+
+function! s:runfile()
+  call rpcnotify(s:sniprunJobId, s:SnipRunFile, 1, line('$'), s:scriptdir, g:sniprun_config)
+endfunction
+
+function! s:runtoend()
+  call rpcnotify(s:sniprunJobId, s:SnipRunToEnd, line('.'), line('.'), s:scriptdir, g:sniprun_config)
+endfunction
+
+function! s:enablelive() range
+  call rpcnotify(s:sniprunJobId, s:SnipEnableLive, str2nr(a:firstline), str2nr(a:lastline), s:scriptdir, g:sniprun_config)
+  augroup sniprun_live
+    autocmd!
+    autocmd TextChanged,TextChangedI <buffer> call rpcnotify(s:sniprunJobId, s:SnipBufferChanged)
+  augroup END
+endfunction
+
+function! s:disablelive()
+  augroup sniprun_live
+    autocmd!
+  augroup END
+  call rpcnotify(s:sniprunJobId, s:SnipDisableLive)
+endfunction
+
+function! s:stop(id)
+  call rpcnotify(s:sniprunJobId, s:SnipStop, a:id)
+endfunction
+
+function! s:replay()
+  call rpcnotify(s:sniprunJobId, s:SnipReplay, g:sniprun_config)
 endfunction
 
 function! s:terminate()
@@ -49,8 +135,24 @@ function! s:terminate()
 endfunction
 
 
-function! s:clean()
-  call rpcnotify(s:sniprunJobId, s:SnipClean)
+function! s:showlog()
+  call rpcnotify(s:sniprunJobId, s:SnipShowLog)
+endfunction
+
+function! s:showcode()
+  call rpcnotify(s:sniprunJobId, s:SnipShowCode)
+endfunction
+
+function! s:showhistory(count)
+  call rpcnotify(s:sniprunJobId, s:SnipShowHistory, a:count)
+endfunction
+
+function! s:setloglevel(level)
+  call rpcnotify(s:sniprunJobId, s:SnipSetLogLevel, a:level)
+endfunction
+
+function! s:clean(target)
+  call rpcnotify(s:sniprunJobId, s:SnipClean, a:target, g:sniprun_config)
   sleep 200m
   " necessary to give enough time to clean the sniprun work directory
 endfunction
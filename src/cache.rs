@@ -0,0 +1,180 @@
+use crate::interpreter::{ExecutionResult, SupportLevel};
+use lazy_static::lazy_static;
+use log::info;
+use serde_json::json;
+use std::collections::HashMap;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// how many results are kept, the oldest ones are forgotten first
+const CAPACITY: usize = 100;
+
+/// a cached result, by key, with the support level the code ran at
+type Entry = (String, ExecutionResult, Option<SupportLevel>);
+
+lazy_static! {
+    /// the cached results of each work directory, oldest first; loaded from the directory when
+    /// first needed
+    static ref RESULTS: Mutex<HashMap<String, Vec<Entry>>> = Mutex::new(HashMap::new());
+}
+
+/// the key of the result of some code: a hash of everything that can change what it prints.
+/// The keys are saved in the work directory (in the cache, in the names of the compiled files):
+/// the hash must not change from one run of sniprun to the next, unlike the one of DefaultHasher
+pub fn key<T: Hash>(inputs: &T) -> String {
+    let mut hasher = Fnv1a::default();
+    inputs.hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+/// the 64-bit FNV-1a hash, that has no random seed
+struct Fnv1a(u64);
+
+impl Default for Fnv1a {
+    fn default() -> Self {
+        Fnv1a(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for Fnv1a {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 = (self.0 ^ *byte as u64).wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+fn cache_file(work_dir: &str) -> String {
+    format!("{}/result_cache.json", work_dir)
+}
+
+fn load(work_dir: &str) -> Vec<Entry> {
+    let content = fs::read_to_string(cache_file(work_dir)).unwrap_or_default();
+    let entries: Vec<serde_json::Value> = serde_json::from_str(&content).unwrap_or_default();
+    entries
+        .iter()
+        .filter_map(|entry| {
+            let result = ExecutionResult {
+                stdout: entry["stdout"].as_str()?.to_string(),
+                stderr: entry["stderr"].as_str()?.to_string(),
+                exit_code: entry["exit_code"].as_i64().map(|code| code as i32),
+                duration: Duration::from_secs_f64(entry["duration"].as_f64().unwrap_or(0.0)),
+                artifacts: entry["artifacts"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|artifact| artifact.as_str().map(String::from))
+                    .collect(),
+            };
+            let level = entry["level"].as_str().and_then(|level| level.parse().ok());
+            Some((entry["key"].as_str()?.to_string(), result, level))
+        })
+        .collect()
+}
+
+fn save(work_dir: &str, results: &[Entry]) {
+    let entries: Vec<serde_json::Value> = results
+        .iter()
+        .map(|(key, result, level)| {
+            json!({
+                "key": key,
+                "stdout": result.stdout,
+                "stderr": result.stderr,
+                "exit_code": result.exit_code,
+                "duration": result.duration.as_secs_f64(),
+                "artifacts": result.artifacts,
+                "level": level.map(|level| level.to_string()),
+            })
+        })
+        .collect();
+    let file = cache_file(work_dir);
+    if let Err(e) = fs::write(&file, serde_json::Value::from(entries).to_string()) {
+        info!("[CACHE] Could not save the result cache to {}: {}", file, e);
+    }
+}
+
+/// the result stored for that key in the work directory, if any, and the level the code ran at
+pub fn get(work_dir: &str, key: &str) -> Option<(ExecutionResult, Option<SupportLevel>)> {
+    let mut results = crate::lock(&RESULTS);
+    results
+        .entry(work_dir.to_string())
+        .or_insert_with(|| load(work_dir))
+        .iter()
+        .find(|(k, _, _)| k == key)
+        .map(|(_, result, level)| (result.clone(), *level))
+}
+
+/// store the result for that key in the work directory, with the level the code ran at
+pub fn insert(work_dir: &str, key: String, result: &ExecutionResult, level: Option<SupportLevel>) {
+    let mut results = crate::lock(&RESULTS);
+    let results = results
+        .entry(work_dir.to_string())
+        .or_insert_with(|| load(work_dir));
+    results.retain(|(k, _, _)| *k != key);
+    results.push((key, result.clone(), level));
+    if results.len() > CAPACITY {
+        results.remove(0);
+    }
+    save(work_dir, results);
+}
+
+/// forget every result of the work directory
+pub fn clear(work_dir: &str) {
+    crate::lock(&RESULTS).insert(work_dir.to_string(), vec![]);
+    let _ = fs::remove_file(cache_file(work_dir));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_are_stable() {
+        // the reference values of FNV-1a
+        let hash = |bytes: &[u8]| {
+            let mut hasher = Fnv1a::default();
+            hasher.write(bytes);
+            hasher.finish()
+        };
+        assert_eq!(hash(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(hash(b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(key(&("a", 1)), key(&("a", 1)));
+        assert_ne!(key(&("a", 1)), key(&("a", 2)));
+    }
+
+    #[test]
+    fn each_work_dir_has_its_own_results() {
+        let (first_data, second_data) = (
+            crate::DataHolder::with_code_in_temp_dir("sh", ""),
+            crate::DataHolder::with_code_in_temp_dir("sh", ""),
+        );
+        let (first, second) = (&first_data.work_dir, &second_data.work_dir);
+        let result = ExecutionResult {
+            stdout: String::from("1\n"),
+            exit_code: Some(0),
+            duration: Duration::from_millis(1500),
+            artifacts: vec![String::from("out.txt")],
+            ..ExecutionResult::default()
+        };
+        insert(first, String::from("k"), &result, Some(SupportLevel::Bloc));
+        assert!(get(second, "k").is_none());
+
+        // what was saved is what is loaded, level included
+        crate::lock(&RESULTS).remove(first);
+        assert_eq!(
+            get(first, "k"),
+            Some((result.clone(), Some(SupportLevel::Bloc)))
+        );
+
+        insert(second, String::from("k"), &result, None);
+        clear(first);
+        assert!(get(first, "k").is_none());
+        assert_eq!(get(second, "k"), Some((result, None)));
+    }
+}
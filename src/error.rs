@@ -1,3 +1,4 @@
+use std::fmt;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -5,35 +6,259 @@ use thiserror::Error;
 pub enum SniprunError {
     ///this error should only be raised when something goes very wrong, and you can't figure out
     ///what
-    #[error("Unknown error: {0}")]
     UnknownError(String),
     ///internal error, should only be raised by Sniprun-specifi code; *not* in languages
     ///interpreters
-    #[error("Internal error: {0}")]
     InternalError(String),
     /// raised if code cannot be fetched from files for whatever reason
-    #[error("Cannot fetch code from files")]
     FetchCodeError,
+    /// raised when there is no code to run: the selection, or the line, is empty or only
+    /// comments
+    NoCodeFound,
 
     ///when the user's code run into problems because of an interpreter's implementation
-    #[error("Interpreter limitations error: {0}")]
+    ///(eg: "Rust_original cannot resolve external crates")
     InterpreterLimitationError(String),
 
     /// raised when code couldn't be run because of either incorrect code or
     /// UnsufficientSupportLevel but the language interpreter cannot determine which one
-    #[error("Code contains errors or unsufficent support level")]
     InterpreterError,
     /// should be raised when users code fail to run but code is correct
-    #[error("Support level not high enough for this language")]
     UnsufficientSupportLevel,
     /// errors raised if the user code is incorrect and fail a compile-time (and not because the language interpreter
     /// failed to the needed code/imports
-    #[error("Compile-time error: {0}")]
     CompilationError(String),
     /// errors raised if the user code is incorrect and fail a run-time (and not because the language interpreter failed to fetch the needed code/imports
-    #[error("RuntimeError: {0}")]
     RuntimeError(String),
+    /// raised when something the interpreter needs is not available on the system, eg: a binary
+    /// (compiler, interpreter...) that is not in the PATH, or a kernel that is not running
+    PrerequisiteError(String),
+    /// raised when the code runs for longer than the timeout the user set
+    TimeoutError(String),
     ///custom error for advanced interpreters, the error will be displayed as-is
-    #[error("{0}")]
     CustomError(String),
+    /// raised when several attempts to run the code (eg: with different interpreters) all
+    /// failed, holds the error of every attempt in order
+    AllAttemptsFailed(Vec<SniprunError>),
+
+    /// wraps another error with the name of the interpreter that raised it and,
+    /// when known, the line (in the original buffer) the error originates from and
+    /// the copy of the generated source file kept for debugging
+    WithContext {
+        interpreter: String,
+        line: Option<i64>,
+        kept_file: Option<String>,
+        #[source]
+        source: Box<SniprunError>,
+    },
+}
+
+impl SniprunError {
+    /// attach the (original buffer) line number the error originates from
+    pub fn at_line(self, line: i64) -> SniprunError {
+        match self {
+            SniprunError::WithContext {
+                interpreter,
+                kept_file,
+                source,
+                ..
+            } => SniprunError::WithContext {
+                interpreter,
+                line: Some(line),
+                kept_file,
+                source,
+            },
+            other => SniprunError::WithContext {
+                interpreter: String::new(),
+                line: Some(line),
+                kept_file: None,
+                source: Box::new(other),
+            },
+        }
+    }
+
+    /// attach the name of the interpreter that raised the error, keeping the line if any
+    pub fn in_interpreter(self, name: &str) -> SniprunError {
+        match self {
+            SniprunError::WithContext {
+                line,
+                kept_file,
+                source,
+                ..
+            } => SniprunError::WithContext {
+                interpreter: name.to_string(),
+                line,
+                kept_file,
+                source,
+            },
+            other => SniprunError::WithContext {
+                interpreter: name.to_string(),
+                line: None,
+                kept_file: None,
+                source: Box::new(other),
+            },
+        }
+    }
+
+    /// attach the path of the generated source file kept to debug the error
+    pub fn with_kept_file(self, path: String) -> SniprunError {
+        match self {
+            SniprunError::WithContext {
+                interpreter,
+                line,
+                source,
+                ..
+            } => SniprunError::WithContext {
+                interpreter,
+                line,
+                kept_file: Some(path),
+                source,
+            },
+            other => SniprunError::WithContext {
+                interpreter: String::new(),
+                line: None,
+                kept_file: Some(path),
+                source: Box::new(other),
+            },
+        }
+    }
+
+    /// the underlying error, without the context (interpreter name, line) attached to it
+    pub fn inner(&self) -> &SniprunError {
+        match self {
+            SniprunError::WithContext { source, .. } => source.inner(),
+            other => other,
+        }
+    }
+
+    /// the line (in the original buffer) the error originates from, if known; for several
+    /// failed attempts, the line of the first one that has one
+    pub fn line(&self) -> Option<i64> {
+        match self {
+            SniprunError::WithContext { line, source, .. } => line.or_else(|| source.line()),
+            SniprunError::AllAttemptsFailed(errors) => errors.iter().find_map(|e| e.line()),
+            _ => None,
+        }
+    }
+
+    /// true if the error may be solved by running the code another way
+    /// (another interpreter, another support level...)
+    pub fn may_fallback(&self) -> bool {
+        matches!(
+            self.inner(),
+            SniprunError::CompilationError(_)
+                | SniprunError::InterpreterLimitationError(_)
+                | SniprunError::UnsufficientSupportLevel
+                | SniprunError::InterpreterError
+                | SniprunError::PrerequisiteError(_)
+        )
+    }
+
+    /// name of the variant of the underlying error, eg: "CompilationError"
+    pub fn variant_name(&self) -> &'static str {
+        match self.inner() {
+            SniprunError::UnknownError(_) => "UnknownError",
+            SniprunError::InternalError(_) => "InternalError",
+            SniprunError::FetchCodeError => "FetchCodeError",
+            SniprunError::NoCodeFound => "NoCodeFound",
+            SniprunError::InterpreterLimitationError(_) => "InterpreterLimitationError",
+            SniprunError::InterpreterError => "InterpreterError",
+            SniprunError::UnsufficientSupportLevel => "UnsufficientSupportLevel",
+            SniprunError::CompilationError(_) => "CompilationError",
+            SniprunError::RuntimeError(_) => "RuntimeError",
+            SniprunError::PrerequisiteError(_) => "PrerequisiteError",
+            SniprunError::TimeoutError(_) => "TimeoutError",
+            SniprunError::CustomError(_) => "CustomError",
+            SniprunError::AllAttemptsFailed(_) => "AllAttemptsFailed",
+            SniprunError::WithContext { .. } => "WithContext",
+        }
+    }
+
+    /// short description of the kind of error, and the associated message if any
+    fn describe(&self) -> (String, Option<String>) {
+        match self {
+            SniprunError::UnknownError(msg) => ("unknown error".to_string(), Some(msg.clone())),
+            SniprunError::InternalError(msg) => ("internal error".to_string(), Some(msg.clone())),
+            SniprunError::FetchCodeError => ("cannot fetch code from files".to_string(), None),
+            SniprunError::NoCodeFound => (
+                "nothing to run: the selection (or the line) is empty, or only comments"
+                    .to_string(),
+                None,
+            ),
+            SniprunError::InterpreterLimitationError(msg) => {
+                ("interpreter limitation".to_string(), Some(msg.clone()))
+            }
+            SniprunError::InterpreterError => (
+                "code contains errors or unsufficient support level".to_string(),
+                None,
+            ),
+            SniprunError::UnsufficientSupportLevel => (
+                "support level not high enough for this language".to_string(),
+                None,
+            ),
+            SniprunError::CompilationError(msg) => {
+                ("compilation failed".to_string(), Some(msg.clone()))
+            }
+            SniprunError::RuntimeError(msg) => ("runtime error".to_string(), Some(msg.clone())),
+            SniprunError::PrerequisiteError(msg) => {
+                ("missing prerequisite".to_string(), Some(msg.clone()))
+            }
+            SniprunError::TimeoutError(msg) => ("timeout".to_string(), Some(msg.clone())),
+            SniprunError::CustomError(msg) => (msg.clone(), None),
+            SniprunError::AllAttemptsFailed(errors) => (
+                format!("all {} attempts failed", errors.len()),
+                Some(
+                    errors
+                        .iter()
+                        .map(|e| format!("\n{}", e))
+                        .collect::<String>(),
+                ),
+            ),
+            SniprunError::WithContext { source, .. } => source.describe(),
+        }
+    }
+}
+
+impl fmt::Display for SniprunError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (line, kept_file) = match self {
+            SniprunError::WithContext {
+                interpreter,
+                line,
+                kept_file,
+                ..
+            } => {
+                if !interpreter.is_empty() {
+                    write!(f, "sniprun({}): ", interpreter)?;
+                }
+                (*line, kept_file.as_ref())
+            }
+            _ => (None, None),
+        };
+        let (kind, message) = self.describe();
+        write!(f, "{}", kind)?;
+        if let Some(line) = line {
+            write!(f, " at line {}", line)?;
+        }
+        match message {
+            Some(message) if !message.trim().is_empty() => write!(f, ": {}", message.trim_end())?,
+            _ => (),
+        }
+        match kept_file {
+            Some(kept_file) => write!(f, "\nsee {}", kept_file),
+            None => Ok(()),
+        }
+    }
+}
+
+impl From<std::io::Error> for SniprunError {
+    fn from(error: std::io::Error) -> Self {
+        match error.kind() {
+            std::io::ErrorKind::TimedOut => SniprunError::TimeoutError(error.to_string()),
+            std::io::ErrorKind::OutOfMemory => SniprunError::RuntimeError(error.to_string()),
+            // the code printed more than max_output_bytes
+            std::io::ErrorKind::FileTooLarge => SniprunError::RuntimeError(error.to_string()),
+            _ => SniprunError::InternalError(error.to_string()),
+        }
+    }
 }
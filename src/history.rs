@@ -0,0 +1,241 @@
+use crate::launcher::RunResult;
+use log::info;
+use serde_json::json;
+use std::collections::VecDeque;
+use std::fs;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// how many lines of output of each run are remembered
+const OUTPUT_LINES: usize = 5;
+
+/// a completed run, as remembered by the history
+#[derive(Debug, Clone)]
+pub struct HistoryEntry {
+    /// when the run ended, in seconds since the epoch
+    pub timestamp: u64,
+    pub interpreter: String,
+    pub filetype: String,
+    /// same as the status of a RunResult: ok, compile_error, runtime_error, timeout or error
+    pub status: String,
+    /// how long the whole run took
+    pub duration: Duration,
+    /// how long the code took to compile, for the interpreters that build it
+    pub build_duration: Option<Duration>,
+    /// the first lines of the output (stderr for failed runs)
+    pub output: String,
+}
+
+impl HistoryEntry {
+    pub fn from_run(run: &RunResult, filetype: &str) -> Self {
+        let output = if run.status == "ok" {
+            &run.stdout
+        } else {
+            &run.stderr
+        };
+        HistoryEntry {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+            interpreter: run.interpreter.clone(),
+            filetype: filetype.to_string(),
+            status: run.status.clone(),
+            duration: run.duration,
+            build_duration: run
+                .steps
+                .iter()
+                .filter(|(step, _)| step.ends_with(": build"))
+                .map(|(_, duration)| *duration)
+                .reduce(|total, duration| total + duration),
+            output: output
+                .lines()
+                .take(OUTPUT_LINES)
+                .collect::<Vec<_>>()
+                .join("\n"),
+        }
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        json!({
+            "timestamp": self.timestamp,
+            "interpreter": self.interpreter,
+            "filetype": self.filetype,
+            "status": self.status,
+            "duration_ms": self.duration.as_millis() as u64,
+            "build_duration_ms": self.build_duration.map(|d| d.as_millis() as u64),
+            "output": self.output,
+        })
+    }
+
+    /// the entries saved before the filetype and durations were, have none
+    fn from_json(value: &serde_json::Value) -> Option<Self> {
+        Some(HistoryEntry {
+            timestamp: value["timestamp"].as_u64()?,
+            interpreter: value["interpreter"].as_str()?.to_string(),
+            filetype: value["filetype"].as_str().unwrap_or("").to_string(),
+            status: value["status"].as_str()?.to_string(),
+            duration: Duration::from_millis(value["duration_ms"].as_u64().unwrap_or(0)),
+            build_duration: value["build_duration_ms"]
+                .as_u64()
+                .map(Duration::from_millis),
+            output: value["output"].as_str()?.to_string(),
+        })
+    }
+}
+
+/// the last completed runs, oldest first, saved to a file (one JSON entry per line) so that
+/// they survive sniprun restarts
+#[derive(Debug)]
+pub struct History {
+    entries: VecDeque<HistoryEntry>,
+    file: String,
+}
+
+impl History {
+    /// the history saved in `file`, or an empty one; unreadable entries are skipped
+    pub fn load(file: &str) -> Self {
+        let entries = fs::read_to_string(file)
+            .unwrap_or_default()
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .filter_map(|value| HistoryEntry::from_json(&value))
+            .collect();
+        History {
+            entries,
+            file: file.to_string(),
+        }
+    }
+
+    /// the file the history is saved in
+    pub fn file(&self) -> &str {
+        &self.file
+    }
+
+    /// remember a run, forgetting the oldest ones beyond `capacity`; saved to the file only if
+    /// `persist`
+    pub fn push(&mut self, entry: HistoryEntry, capacity: usize, persist: bool) {
+        self.entries.push_back(entry);
+        while self.entries.len() > capacity {
+            self.entries.pop_front();
+        }
+        if persist {
+            self.save();
+        }
+    }
+
+    /// forget every run
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.save();
+    }
+
+    fn save(&self) {
+        let content: String = self
+            .entries
+            .iter()
+            .map(|entry| entry.to_json().to_string() + "\n")
+            .collect();
+        if let Err(e) = fs::write(&self.file, content) {
+            info!("[HISTORY] Could not save history to {}: {}", self.file, e);
+        }
+    }
+
+    /// the history as lines of text: the last `count` runs (all of them if None), newest
+    /// first, then statistics by interpreter
+    pub fn render(&self, count: Option<usize>) -> Vec<String> {
+        if self.entries.is_empty() {
+            return vec![String::from("no run yet")];
+        }
+        let mut lines = vec![];
+        let count = count.unwrap_or(self.entries.len());
+        for entry in self.entries.iter().rev().take(count) {
+            let mark = if entry.status == "ok" {
+                ""
+            } else {
+                " [FAILED]"
+            };
+            let filetype = if entry.filetype.is_empty() {
+                String::new()
+            } else {
+                format!(" ({})", entry.filetype)
+            };
+            let build = entry
+                .build_duration
+                .map(|d| format!(", built in {:.2}s", d.as_secs_f64()))
+                .unwrap_or_default();
+            lines.push(format!(
+                "{} {}{} {}{} in {:.2}s{}",
+                format_timestamp(entry.timestamp),
+                entry.interpreter,
+                filetype,
+                entry.status,
+                mark,
+                entry.duration.as_secs_f64(),
+                build
+            ));
+            lines.extend(entry.output.lines().map(|line| format!("    {}", line)));
+        }
+        lines.push(String::new());
+        lines.extend(self.statistics());
+        lines
+    }
+
+    /// by interpreter: the number of runs, how many failed, and their average duration
+    fn statistics(&self) -> Vec<String> {
+        let mut interpreters: Vec<(&str, usize, usize, Duration)> = vec![];
+        for entry in &self.entries {
+            let index = match interpreters
+                .iter()
+                .position(|(name, ..)| *name == entry.interpreter)
+            {
+                Some(index) => index,
+                None => {
+                    interpreters.push((&entry.interpreter, 0, 0, Duration::ZERO));
+                    interpreters.len() - 1
+                }
+            };
+            let (_, runs, failures, total) = &mut interpreters[index];
+            *runs += 1;
+            if entry.status != "ok" {
+                *failures += 1;
+            }
+            *total += entry.duration;
+        }
+        let mut lines = vec![String::from("statistics:")];
+        for (name, runs, failures, total) in interpreters {
+            lines.push(format!(
+                "    {}: {} run(s), {} failed, {:.2}s on average",
+                if name.is_empty() { "(none)" } else { name },
+                runs,
+                failures,
+                total.as_secs_f64() / runs as f64
+            ));
+        }
+        lines
+    }
+}
+
+/// 'YYYY-MM-DD HH:MM:SS' (UTC) from seconds since the epoch
+fn format_timestamp(timestamp: u64) -> String {
+    let days = (timestamp / 86400) as i64;
+    let seconds = timestamp % 86400;
+    // days to civil date, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        year,
+        month,
+        day,
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
@@ -1,5 +1,20 @@
 use crate::error::SniprunError;
 use crate::DataHolder;
+use log::{debug, info};
+use neovim_lib::Neovim;
+use std::cell::RefCell;
+use std::env;
+use std::fmt;
+use std::fs::{self, DirBuilder};
+use std::io::{self, BufRead, BufReader, Read};
+use std::ops::BitOr;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, ExitStatus, Output, Stdio};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
 
 #[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
 #[allow(dead_code)]
@@ -22,17 +37,341 @@ pub enum SupportLevel {
     Selected = 255,
 }
 
+impl fmt::Display for SupportLevel {
+    ///the name the user gives the level with, eg: "bloc"
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            SupportLevel::Unsupported => "unsupported",
+            SupportLevel::Line => "line",
+            SupportLevel::Bloc => "bloc",
+            SupportLevel::Import => "import",
+            SupportLevel::File => "file",
+            SupportLevel::Project => "project",
+            SupportLevel::System => "system",
+            SupportLevel::Selected => "selected",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for SupportLevel {
+    type Err = SniprunError;
+
+    ///the level the user asks for (config: `support_level`), case insensitive; "block" is
+    ///"bloc" too
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.trim().to_lowercase().as_str() {
+            "line" => Ok(SupportLevel::Line),
+            "bloc" | "block" => Ok(SupportLevel::Bloc),
+            "import" => Ok(SupportLevel::Import),
+            "file" => Ok(SupportLevel::File),
+            "project" => Ok(SupportLevel::Project),
+            "system" => Ok(SupportLevel::System),
+            _ => Err(SniprunError::CustomError(format!(
+                "unknown support level '{}', expected line, bloc, import, file, project or system",
+                name
+            ))),
+        }
+    }
+}
+
+impl SupportLevel {
+    ///this level, unless it is higher than the given maximum
+    pub fn capped(self, max: SupportLevel) -> SupportLevel {
+        if self > max {
+            max
+        } else {
+            self
+        }
+    }
+
+    ///the next level below this one, None for Line (and Unsupported)
+    pub fn lower(self) -> Option<SupportLevel> {
+        match self {
+            SupportLevel::Selected => Some(SupportLevel::System),
+            SupportLevel::System => Some(SupportLevel::Project),
+            SupportLevel::Project => Some(SupportLevel::File),
+            SupportLevel::File => Some(SupportLevel::Import),
+            SupportLevel::Import => Some(SupportLevel::Bloc),
+            SupportLevel::Bloc => Some(SupportLevel::Line),
+            SupportLevel::Line | SupportLevel::Unsupported => None,
+        }
+    }
+}
+
+///Set of flags describing what an interpreter can do, besides running code.
+///Combine them with `|`, eg: `Capabilities::STREAMING | Capabilities::SEPARATE_BUILD`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Capabilities(u32);
+
+impl Capabilities {
+    ///can do nothing more than run code and return its output
+    pub const NONE: Capabilities = Capabilities(0);
+    ///can return output progressively, while the code is running
+    pub const STREAMING: Capabilities = Capabilities(1);
+    ///keeps state (variables, functions...) between runs
+    pub const REPL: Capabilities = Capabilities(1 << 1);
+    ///has a build step distinct from the execution (compiled languages)
+    pub const SEPARATE_BUILD: Capabilities = Capabilities(1 << 2);
+    ///holds resources that outlive a run (a process, a container...), released by cleanup()
+    pub const CLEANUP: Capabilities = Capabilities(1 << 3);
+    ///reads files around the code besides the edited one (eg: a go.mod, a Gemfile, the modules
+    ///it imports, the files of its directory): its results aren't cached
+    pub const PROJECT_FILES: Capabilities = Capabilities(1 << 4);
+
+    ///true if all the flags of `other` are also set in self
+    pub fn contains(self, other: Capabilities) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    ///the flags of self, but the ones of `other`
+    pub fn without(self, other: Capabilities) -> Capabilities {
+        Capabilities(self.0 & !other.0)
+    }
+
+    ///number of flags of `wanted` that are set in self
+    pub fn matching(self, wanted: Capabilities) -> u32 {
+        (self.0 & wanted.0).count_ones()
+    }
+
+    ///names of the flags set, eg: ["streaming", "separate_build"]
+    pub fn names(self) -> Vec<&'static str> {
+        [
+            (Capabilities::STREAMING, "streaming"),
+            (Capabilities::REPL, "repl"),
+            (Capabilities::SEPARATE_BUILD, "separate_build"),
+            (Capabilities::CLEANUP, "cleanup"),
+            (Capabilities::PROJECT_FILES, "project_files"),
+        ]
+        .iter()
+        .filter(|(flag, _)| self.contains(*flag))
+        .map(|(_, name)| *name)
+        .collect()
+    }
+}
+
+impl BitOr for Capabilities {
+    type Output = Capabilities;
+    fn bitor(self, other: Capabilities) -> Capabilities {
+        Capabilities(self.0 | other.0)
+    }
+}
+
+///The comments of a language: the markers of the comments that run to the end of the line,
+///and the (start, end) markers of the block comments
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CommentSyntax {
+    pub line: &'static [&'static str],
+    pub block: &'static [(&'static str, &'static str)],
+}
+
+#[allow(dead_code)]
+impl CommentSyntax {
+    ///unknown comments: nothing is taken for one
+    pub const NONE: CommentSyntax = CommentSyntax {
+        line: &[],
+        block: &[],
+    };
+    ///'// comment' and '/* comment */'
+    pub const C_LIKE: CommentSyntax = CommentSyntax {
+        line: &["//"],
+        block: &[("/*", "*/")],
+    };
+    ///'# comment'
+    pub const HASH: CommentSyntax = CommentSyntax {
+        line: &["#"],
+        block: &[],
+    };
+
+    ///the comments of a filetype, as far as the common languages go: the default of the
+    ///interpreters that don't tell theirs
+    pub fn guess(filetype: &str) -> CommentSyntax {
+        match filetype {
+            "c" | "cpp" | "rust" | "go" | "java" | "javascript" | "typescript" | "cs"
+            | "kotlin" | "scala" | "swift" | "d" | "dart" | "groovy" | "php" | "zig" | "v" => {
+                CommentSyntax::C_LIKE
+            }
+            "python" | "ruby" | "sh" | "bash" | "zsh" | "perl" | "r" | "elixir" | "coffee"
+            | "make" | "cmake" | "awk" => CommentSyntax::HASH,
+            "julia" => CommentSyntax {
+                line: &["#"],
+                block: &[("#=", "=#")],
+            },
+            "nim" => CommentSyntax {
+                line: &["#"],
+                block: &[("#[", "]#")],
+            },
+            "lua" => CommentSyntax {
+                line: &["--"],
+                block: &[("--[[", "]]")],
+            },
+            "haskell" | "elm" | "idris" => CommentSyntax {
+                line: &["--"],
+                block: &[("{-", "-}")],
+            },
+            "sql" => CommentSyntax {
+                line: &["--"],
+                block: &[("/*", "*/")],
+            },
+            "ada" => CommentSyntax {
+                line: &["--"],
+                block: &[],
+            },
+            "lisp" | "scheme" | "racket" | "clojure" | "asm" | "nasm" => CommentSyntax {
+                line: &[";"],
+                block: &[],
+            },
+            "erlang" | "matlab" | "tex" => CommentSyntax {
+                line: &["%"],
+                block: &[],
+            },
+            "ocaml" => CommentSyntax {
+                line: &[],
+                block: &[("(*", "*)")],
+            },
+            "fsharp" => CommentSyntax {
+                line: &["//"],
+                block: &[("(*", "*)")],
+            },
+            _ => CommentSyntax::NONE,
+        }
+    }
+
+    ///the code without its comments (a marker in a string is taken for a comment too, which
+    ///only matters to code that is nothing but that string)
+    pub fn strip(&self, code: &str) -> String {
+        let mut stripped = String::new();
+        let mut rest = code;
+        while let Some(c) = rest.chars().next() {
+            if let Some((start, end)) = self.block.iter().find(|(start, _)| rest.starts_with(start))
+            {
+                rest = match rest[start.len()..].find(end) {
+                    Some(i) => &rest[start.len() + i + end.len()..],
+                    None => "",
+                };
+            } else if self.line.iter().any(|marker| rest.starts_with(marker)) {
+                rest = &rest[rest.find('\n').unwrap_or(rest.len())..];
+            } else {
+                stripped.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+        stripped
+    }
+
+    ///true if the code is nothing but comments and blanks
+    pub fn only_comments(&self, code: &str) -> bool {
+        self.strip(code).trim().is_empty()
+    }
+}
+
+///What a successful run of the code produced
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ExecutionResult {
+    pub stdout: String,
+    pub stderr: String,
+    ///exit code of the process that ran the code, when there is one
+    pub exit_code: Option<i32>,
+    ///how long the execution (not the build) took
+    pub duration: Duration,
+    ///the files the run created or modified in the directory the code ran in, filled by the
+    ///launcher (config: `capture_artifacts`)
+    pub artifacts: Vec<String>,
+}
+
+impl ExecutionResult {
+    ///result of code that was run without a process of its own (eg: inside sniprun)
+    pub fn new(stdout: String) -> Self {
+        ExecutionResult {
+            stdout,
+            ..ExecutionResult::default()
+        }
+    }
+
+    ///result of the process that ran the code
+    pub fn from_output(output: &Output) -> Self {
+        ExecutionResult {
+            stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+            stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+            exit_code: output.status.code(),
+            duration: Duration::default(),
+            artifacts: vec![],
+        }
+    }
+
+    ///what is shown to the user when the result is simply echoed: the output, then the files
+    ///the run created
+    pub fn to_display_string(&self) -> String {
+        let mut display = self.stdout.clone();
+        for artifact in &self.artifacts {
+            if !display.is_empty() && !display.ends_with('\n') {
+                display.push('\n');
+            }
+            display.push_str(&format!("file: {}", artifact));
+        }
+        display
+    }
+}
+
+///Where the lines of the generated file come from: add_boilerplate() records the lines it adds,
+///so that errors located in the generated file can be located in the user's buffer
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LineOffsets {
+    ///the lines (1-based) of the generated file that are not the user's code, sorted
+    added: Vec<i64>,
+}
+
+impl LineOffsets {
+    ///`count` lines were added before the code
+    pub fn prepend(&mut self, count: i64) {
+        self.insert(1, count);
+    }
+
+    ///`count` lines were added before the given line (1-based) of the generated file, as it
+    ///was before the insertion
+    pub fn insert(&mut self, line: i64, count: i64) {
+        for added in self.added.iter_mut().filter(|added| **added >= line) {
+            *added += count;
+        }
+        self.added.extend(line..line + count);
+        self.added.sort_unstable();
+    }
+
+    ///the line of the user's code a line of the generated file is, None if it was added
+    pub fn code_line(&self, generated_line: i64) -> Option<i64> {
+        if self.added.binary_search(&generated_line).is_ok() {
+            return None;
+        }
+        let before = self
+            .added
+            .iter()
+            .filter(|added| **added < generated_line)
+            .count() as i64;
+        Some(generated_line - before)
+    }
+}
+
 ///This is the trait all interpreters must implement.
 ///The launcher run fucntions new() and run() from this trait.
 pub trait Interpreter {
     //create
     fn new(data: DataHolder) -> Box<Self> {
-        Self::new_with_level(data, Self::get_max_support_level())
+        // the user may ask for a lower level than the maximum (config: `support_level`)
+        let level = data.support_level_for(Self::get_max_support_level());
+        let level = Self::negotiate_level(&data, level);
+        Self::new_with_level(data, level)
     }
     /// This implies your interpreter struct should have a 'data' and a 'support_level' field.
     /// I suggest you also add a 'code' String field to hold the code you want to modify and run
     fn new_with_level(data: DataHolder, level: SupportLevel) -> Box<Self>;
 
+    /// The level your interpreter can run the code at, when `level` is asked for: override it
+    /// if that depends on the data (eg: the code needs a project around to run at Import level).
+    /// It must not create anything, the launcher calls it to tell the user what would run
+    fn negotiate_level(_data: &DataHolder, level: SupportLevel) -> SupportLevel {
+        level
+    }
+
     ///Return the (unique) name of your interpreter.
     fn get_name() -> String;
 
@@ -42,12 +381,86 @@ pub trait Interpreter {
 
     fn get_current_level(&self) -> SupportLevel;
     fn set_current_level(&mut self, level: SupportLevel);
+    #[allow(dead_code)]
     fn get_data(&self) -> DataHolder;
 
+    /// Path of the source file your interpreter generates (the code with its boilerplate), if
+    /// any. Users can open it (`:SnipShowCode`) to see what was actually compiled/run.
+    fn get_generated_file(&self) -> Option<String> {
+        None
+    }
+
     /// You should override this method as soon as you wish to test your interpreter.
     fn get_max_support_level() -> SupportLevel {
         //to overwrite in trait impls
-        return SupportLevel::Unsupported;
+        SupportLevel::Unsupported
+    }
+
+    /// Name of the subfolder of sniprun's work directory your interpreter writes its files in.
+    /// This is the only folder cleaned when the user resets only your interpreter.
+    fn get_work_dir_name() -> String {
+        Self::get_name().to_lowercase()
+    }
+
+    /// The extension of the source files of your language (eg: "rs"), without the dot. Empty
+    /// if your interpreter doesn't write the code to a file.
+    fn get_file_extension() -> String {
+        String::new()
+    }
+
+    /// Path of the file to write the code to: 'main.<extension>' in your interpreter's work
+    /// directory, so that the helpers working on the generated file name it the same way for
+    /// every language.
+    fn temp_source_path(data: &DataHolder) -> String {
+        let path = format!("{}/{}/main", data.work_dir, Self::get_work_dir_name());
+        match Self::get_file_extension().as_str() {
+            "" => path,
+            extension => path + "." + extension,
+        }
+    }
+
+    /// The comments of your language, so that a selection of comments only isn't run. Guessed
+    /// from the filetype by default.
+    fn get_comment_syntax(filetype: &str) -> CommentSyntax {
+        CommentSyntax::guess(filetype)
+    }
+
+    /// What your interpreter can do, besides running code. Be conservative, the launcher
+    /// and some features rely on this to choose an interpreter.
+    fn get_capabilities() -> Capabilities {
+        Capabilities::NONE
+    }
+
+    /// What your interpreter can do with these data: override it if some of its capabilities
+    /// depend on its options (eg: no streaming in some mode). Like negotiate_level(), it must
+    /// not create anything
+    fn negotiate_capabilities(_data: &DataHolder) -> Capabilities {
+        Self::get_capabilities()
+    }
+
+    /// Forget the state kept between runs, if your interpreter keeps one (eg: restart the REPL
+    /// the code runs in). Called when the user resets sniprun or your interpreter.
+    fn clean_repl_memory(_data: &DataHolder) -> Result<(), SniprunError> {
+        Ok(())
+    }
+
+    /// Release the resources your interpreter keeps after a run (kill a persistent process,
+    /// stop a container, remove a socket...). If your interpreter has the CLEANUP capability,
+    /// the launcher keeps its last instance and calls this on `:SnipReset` and when neovim exits
+    fn cleanup(&self) {}
+
+    /// The lines your boilerplate added to the code, keep a LineOffsets field up to date in
+    /// add_boilerplate() if you add lines before or inside the user's code
+    fn get_line_offsets(&self) -> LineOffsets {
+        LineOffsets::default()
+    }
+
+    /// The line of the user's buffer a line (1-based) of the generated file comes from, None if
+    /// the boilerplate added it. Use it to locate errors, see SniprunError::at_line()
+    fn to_buffer_line(&self, generated_line: i64) -> Option<i64> {
+        self.get_line_offsets()
+            .code_line(generated_line)
+            .and_then(|line| self.get_data().buffer_line(line))
     }
 
     /// This method should get the needed code from the data struct and eventually the files
@@ -63,19 +476,1054 @@ pub trait Interpreter {
     fn build(&mut self) -> Result<(), SniprunError>; //return path to executable
 
     ///This should be used to execute a binary or execute the script
-    ///In case it's successfull, returns Ok( standart_output );
-    fn execute(&mut self) -> Result<String, SniprunError>;
+    ///In case it's successfull, returns Ok( what the code printed ), see ExecutionResult::new()
+    ///and ExecutionResult::from_output(). The duration is filled in for you.
+    fn execute(&mut self) -> Result<ExecutionResult, SniprunError>;
 
-    /// set the current support level to the one provided, run fetch(), add_boilerplate(), build() and execute() in order if each step is successfull
-    fn run_at_level(&mut self, level: SupportLevel) -> Result<String, SniprunError> {
+    /// Check that the code compiles, without running it (for `compile_only` runs). By default
+    /// the code is built; if your interpreter has no build step, override this with a syntax
+    /// check when your language has one (see `syntax_check()`)
+    fn check(&mut self) -> Result<ExecutionResult, SniprunError> {
+        self.build()?;
+        if Self::get_capabilities().contains(Capabilities::SEPARATE_BUILD) {
+            Ok(ExecutionResult::new(String::from("compiled OK\n")))
+        } else {
+            Ok(ExecutionResult::new(format!(
+                "no compile step for {}, nothing was checked\n",
+                Self::get_name()
+            )))
+        }
+    }
+
+    /// set the current support level to the one provided, then run fetch_code() and
+    /// add_boilerplate(): what comes before the build, that the launcher may do in the background
+    fn prepare_at_level(&mut self, level: SupportLevel) -> Result<(), SniprunError> {
         self.set_current_level(level);
-        self.fetch_code()
-            .and_then(|_| self.add_boilerplate())
-            .and_then(|_| self.build())
-            .and_then(|_| self.execute())
+        timed("fetch_code", || self.fetch_code())?;
+        timed("add_boilerplate", || self.add_boilerplate())
+    }
+
+    /// run check() on the code prepare_at_level() prepared: the code is not run
+    fn build_and_check(&mut self) -> Result<ExecutionResult, SniprunError> {
+        if Self::get_capabilities().contains(Capabilities::SEPARATE_BUILD) {
+            self.get_data().progress.report("compiling");
+        }
+        timed("check", || self.check())
+    }
+
+    /// run build() then execute() on the code prepare_at_level() prepared
+    fn build_and_execute(&mut self) -> Result<ExecutionResult, SniprunError> {
+        // the user is told when a compilation, that may be long, starts and ends
+        let progress = Self::get_capabilities()
+            .contains(Capabilities::SEPARATE_BUILD)
+            .then(|| self.get_data().progress);
+        if let Some(progress) = &progress {
+            progress.report("compiling");
+        }
+        timed("build", || self.build())?;
+        if let Some(progress) = &progress {
+            progress.report("running");
+        }
+        let start = Instant::now();
+        let mut result = timed("execute", || self.execute())?;
+        result.duration = start.elapsed();
+        Ok(result)
+    }
+
+    /// like run_at_level(), but stops after check(): the code is not run
+    fn check_at_level(&mut self, level: SupportLevel) -> Result<ExecutionResult, SniprunError> {
+        self.prepare_at_level(level)?;
+        self.build_and_check()
     }
-    /// default run function ran from the launcher (run_at_level(max_level))
-    fn run(&mut self) -> Result<String, SniprunError> {
+
+    /// set the current support level to the one provided, run fetch(), add_boilerplate(), build() and execute() in order if each step is successfull
+    fn run_at_level(&mut self, level: SupportLevel) -> Result<ExecutionResult, SniprunError> {
+        self.prepare_at_level(level)?;
+        self.build_and_execute()
+    }
+    /// run_at_level() at the current level, what the launcher does in two steps: see
+    /// prepare_at_level() and build_and_execute()
+    #[cfg(test)]
+    fn run(&mut self) -> Result<ExecutionResult, SniprunError> {
         self.run_at_level(self.get_current_level())
     }
 }
+
+/// Run a command checking the syntax of the code without running it (eg: `ruby -c main.rb`),
+/// for Interpreter::check(). What it reports is made an error by `error` (eg: a located
+/// CompilationError)
+pub fn syntax_check(
+    command: &mut Command,
+    error: impl FnOnce(&str) -> SniprunError,
+) -> Result<ExecutionResult, SniprunError> {
+    let output = command.output()?;
+    if output.status.success() {
+        return Ok(ExecutionResult::new(String::from("syntax OK\n")));
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if stderr.trim().is_empty() {
+        Err(error(&String::from_utf8_lossy(&output.stdout)))
+    } else {
+        Err(error(&stderr))
+    }
+}
+
+/// Implements the methods every interpreter implements the same way: `get_name` (the name of
+/// the struct), `get_current_level`, `set_current_level` and `get_data`. Use it inside your
+/// `impl Interpreter for YourStruct` block, your struct needs 'data' and 'support_level' fields:
+/// `declare_interpreter!(YourStruct);`
+macro_rules! declare_interpreter {
+    ($name:ident) => {
+        fn get_name() -> String {
+            String::from(stringify!($name))
+        }
+
+        fn get_current_level(&self) -> SupportLevel {
+            self.support_level
+        }
+        fn set_current_level(&mut self, level: SupportLevel) {
+            self.support_level = level;
+        }
+
+        fn get_data(&self) -> DataHolder {
+            self.data.clone()
+        }
+    };
+}
+
+/// The path of the subfolder of sniprun's work directory an interpreter writes its files in.
+/// Call it in new_with_level() with `&Self::get_work_dir_name()`: the launcher creates the
+/// folder before it creates the interpreter.
+pub fn work_dir_path(data: &DataHolder, dir_name: &str) -> String {
+    data.work_dir.clone() + "/" + dir_name
+}
+
+/// Create (if needed) the subfolder of sniprun's work directory an interpreter writes its
+/// files in, and return its path
+pub fn create_work_dir(data: &DataHolder, dir_name: &str) -> Result<String, SniprunError> {
+    let dir = work_dir_path(data, dir_name);
+    DirBuilder::new()
+        .recursive(true)
+        .create(&dir)
+        .map_err(|e| {
+            SniprunError::InternalError(format!("could not create directory {}: {}", dir, e))
+        })?;
+    Ok(dir)
+}
+
+/// The code selected by the user, for an interpreter running at the given support level: the
+/// bloc if there is one and the level allows it, else the current line, else nothing. Line
+/// endings are normalized, and the indentation common to all the lines is removed (so that a
+/// bloc selected inside a function runs as-is). Call it in fetch_code() if you need nothing more.
+pub fn selected_code(data: &DataHolder, level: SupportLevel) -> String {
+    let bloc = data.current_bloc.replace("\r\n", "\n");
+    if !bloc.trim().is_empty() && level >= SupportLevel::Bloc {
+        return dedent(&bloc);
+    }
+    let line = data.current_line.trim_end_matches('\r');
+    if !line.trim().is_empty() && level >= SupportLevel::Line {
+        return dedent(line);
+    }
+    String::new()
+}
+
+/// remove the leading whitespace (spaces and tabs, as-is) common to all the non-blank lines
+fn dedent(code: &str) -> String {
+    let indentation = |line: &str| line.len() - line.trim_start_matches(&[' ', '\t'][..]).len();
+    let mut common: Option<&str> = None;
+    for line in code.lines().filter(|l| !l.trim().is_empty()) {
+        let prefix = &line[..indentation(line)];
+        common = Some(match common {
+            None => prefix,
+            Some(common) => {
+                let length = common
+                    .bytes()
+                    .zip(prefix.bytes())
+                    .take_while(|(a, b)| a == b)
+                    .count();
+                &common[..length]
+            }
+        });
+    }
+    let common = common.unwrap_or("");
+    code.lines()
+        .map(|line| {
+            line.strip_prefix(common)
+                .unwrap_or_else(|| line.trim_start())
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Write the code to the given file, replacing it if it exists.
+pub fn write_code(path: &str, code: &str) -> Result<(), SniprunError> {
+    fs::write(path, code)
+        .map_err(|e| SniprunError::InternalError(format!("could not write {}: {}", path, e)))
+}
+
+/// Remove the oldest files of the directory that have the given extension, so that only `kept`
+/// of them remain: for interpreters that keep a compiled file per code, so that their work
+/// directory doesn't grow forever.
+pub fn remove_oldest_files(dir: &str, extension: &str, kept: usize) {
+    let mut files: Vec<(std::time::SystemTime, PathBuf)> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|e| e == extension))
+        .filter_map(|path| Some((path.metadata().ok()?.modified().ok()?, path)))
+        .collect();
+    if files.len() <= kept {
+        return;
+    }
+    files.sort();
+    for (_, file) in &files[..files.len() - kept] {
+        let _ = fs::remove_file(file);
+    }
+}
+
+/// Check that every binary given is available, either as a path to an existing file or
+/// as an executable found in the PATH. Interpreters should call this at the start of
+/// build() / execute() instead of letting Command::new(..) fail.
+pub fn check_toolchain(binaries: &[&str]) -> Result<(), SniprunError> {
+    for binary in binaries {
+        let found = if binary.contains('/') {
+            Path::new(binary).is_file()
+        } else {
+            env::var_os("PATH")
+                .map(|paths| env::split_paths(&paths).any(|dir| dir.join(binary).is_file()))
+                .unwrap_or(false)
+        };
+        if !found {
+            return Err(SniprunError::PrerequisiteError(format!(
+                "{} not found in PATH, please install it or check your $PATH",
+                binary
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// The closest directory, starting from `start` (or its directory if it is a file) and going up
+/// to the filesystem root, that contains `marker` (a file or a directory, eg: "go.mod",
+/// "node_modules"). Symlinks in `start` are resolved first, so the lookup happens where the
+/// file really is. An empty `start` (eg: an unnamed buffer's filepath) finds nothing.
+pub fn find_ancestor(start: &Path, marker: &str) -> Option<PathBuf> {
+    if start.as_os_str().is_empty() {
+        return None;
+    }
+    let start = start.canonicalize().unwrap_or_else(|_| start.to_path_buf());
+    start
+        .ancestors()
+        .find(|dir| dir.join(marker).exists())
+        .map(Path::to_path_buf)
+}
+
+/// The python binary of the environment the edited file belongs to, if any: a `.venv` or
+/// `venv` directory above the file, the pipenv environment of a Pipfile above the file, or
+/// the activated virtualenv / conda environment ($VIRTUAL_ENV, $CONDA_PREFIX), in that order.
+/// None means the python found in the PATH should be used.
+pub fn python_environment(filepath: &str) -> Option<PathBuf> {
+    let start = Path::new(filepath);
+    for venv in &[".venv", "venv"] {
+        let marker = format!("{}/bin/python", venv);
+        if let Some(dir) = find_ancestor(start, &marker) {
+            return Some(dir.join(marker));
+        }
+    }
+    if let Some(dir) = find_ancestor(start, "Pipfile") {
+        let pipenv = Command::new("pipenv")
+            .arg("--venv")
+            .current_dir(&dir)
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string());
+        match pipenv {
+            Some(venv) if !venv.is_empty() => return Some(Path::new(&venv).join("bin/python")),
+            _ => info!("[ENV] No pipenv environment found for {:?}", dir),
+        }
+    }
+    ["VIRTUAL_ENV", "CONDA_PREFIX"]
+        .iter()
+        .filter_map(env::var_os)
+        .map(|prefix| Path::new(&prefix).join("bin/python"))
+        .find(|python| python.is_file())
+}
+
+/// run one step of an interpreter's lifecycle, logging how long it took (debug level)
+fn timed<T>(step: &str, f: impl FnOnce() -> Result<T, SniprunError>) -> Result<T, SniprunError> {
+    let start = Instant::now();
+    let result = f();
+    debug!(
+        "[INTERPRETER] {} {} in {:?}",
+        step,
+        if result.is_ok() { "done" } else { "failed" },
+        start.elapsed()
+    );
+    STEP_DURATIONS.with(|steps| steps.borrow_mut().push((step.to_string(), start.elapsed())));
+    result
+}
+
+thread_local! {
+    /// the steps of the lifecycle of the interpreters run on this thread, with their duration
+    static STEP_DURATIONS: RefCell<Vec<(String, Duration)>> = const { RefCell::new(vec![]) };
+}
+
+/// the steps run on this thread (see timed()) since the last call, with their duration
+pub fn take_step_durations() -> Vec<(String, Duration)> {
+    STEP_DURATIONS.with(|steps| steps.borrow_mut().drain(..).collect())
+}
+
+///Sending half of the channel the output of the code is streamed through, line by line,
+///while it runs. Holds nothing when streaming is disabled.
+#[derive(Debug, Clone, Default)]
+pub struct OutputStream(Option<mpsc::Sender<String>>);
+
+impl OutputStream {
+    pub fn new(sender: mpsc::Sender<String>) -> Self {
+        OutputStream(Some(sender))
+    }
+    pub fn is_enabled(&self) -> bool {
+        self.0.is_some()
+    }
+}
+
+impl PartialEq for OutputStream {
+    fn eq(&self, other: &Self) -> bool {
+        self.is_enabled() == other.is_enabled()
+    }
+}
+
+///Tells the user what a run is doing (eg: compiling), with the name of the phase that starts.
+///Holds nothing when the progress isn't shown.
+#[derive(Clone, Default)]
+pub struct Progress(Option<Arc<NotifyProgress>>);
+
+/// tells the user that a phase of the run starts
+type NotifyProgress = dyn Fn(&str) + Send + Sync;
+
+impl Progress {
+    pub fn new(notify: impl Fn(&str) + Send + Sync + 'static) -> Self {
+        Progress(Some(Arc::new(notify)))
+    }
+    pub fn report(&self, phase: &str) {
+        if let Some(notify) = &self.0 {
+            notify(phase);
+        }
+    }
+}
+
+impl fmt::Debug for Progress {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Progress({})",
+            if self.0.is_some() { "shown" } else { "hidden" }
+        )
+    }
+}
+
+impl PartialEq for Progress {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.is_some() == other.0.is_some()
+    }
+}
+
+///Handle to the neovim instance sniprun is attached to, for interpreters that run code
+///inside neovim itself. Holds nothing outside of a run.
+#[derive(Clone, Default)]
+pub struct NeovimInstance(Option<Arc<Mutex<Neovim>>>);
+
+impl NeovimInstance {
+    pub fn new(nvim: Arc<Mutex<Neovim>>) -> Self {
+        NeovimInstance(Some(nvim))
+    }
+    pub fn get(&self) -> Option<&Arc<Mutex<Neovim>>> {
+        self.0.as_ref()
+    }
+}
+
+impl fmt::Debug for NeovimInstance {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "NeovimInstance({})",
+            if self.0.is_some() { "attached" } else { "none" }
+        )
+    }
+}
+
+impl PartialEq for NeovimInstance {
+    fn eq(&self, other: &Self) -> bool {
+        match (&self.0, &other.0) {
+            (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+}
+
+/// The command that runs `program` in the `dir` directory, inside the sandbox the user chose
+/// (config: `sandbox` and `sandbox_image`) if any, natively otherwise or if the sandbox is not
+/// available. Add the arguments to the returned command as usual.
+/// The work directory is mounted at the same path in the sandbox, so paths under it are valid
+/// in both cases. The code runs as the user (the files it creates are theirs), within the
+/// user's resource limits, in a container named after the run so that run_command can kill it.
+pub fn sandboxed_command(data: &DataHolder, dir: &str, program: &str) -> Command {
+    if data.sandbox == "docker" && !data.sandbox_image.is_empty() {
+        if check_toolchain(&["docker"]).is_ok() {
+            static COUNT: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+            let mut command = Command::new("docker");
+            command
+                .arg("run")
+                .arg("--rm")
+                .arg("--init")
+                .arg("--name")
+                .arg(format!(
+                    "{}{}-{}",
+                    CONTAINER_PREFIX,
+                    std::process::id(),
+                    COUNT.fetch_add(1, Ordering::SeqCst)
+                ))
+                .arg("-v")
+                .arg(format!("{0}:{0}", data.work_dir))
+                .arg("-w")
+                .arg(dir);
+            #[cfg(unix)]
+            {
+                // safety: getuid and getgid can't fail
+                let (uid, gid) = unsafe { (libc::getuid(), libc::getgid()) };
+                command.arg("--user").arg(format!("{}:{}", uid, gid));
+            }
+            // the rlimits set by run_command would apply to the docker client, not to the code
+            if let Some(mb) = data.max_memory_mb {
+                command
+                    .arg(format!("--memory={}m", mb))
+                    .arg(format!("--memory-swap={}m", mb));
+            }
+            if let Some(seconds) = data.max_cpu_seconds {
+                // on a single CPU, the code can't use its CPU time faster than the time passes
+                command
+                    .arg("--cpus=1")
+                    .arg(format!("--ulimit=cpu={}:{}", seconds, seconds + 1));
+            }
+            command.arg(&data.sandbox_image).arg(program);
+            return command;
+        }
+        info!(
+            "[INTERPRETER] docker not found, running {} natively",
+            program
+        );
+    }
+    let mut command = Command::new(program);
+    command.current_dir(dir);
+    command
+}
+
+/// the names of the containers of sandboxed_command start with it
+const CONTAINER_PREFIX: &str = "sniprun-";
+
+/// the name of the container the command runs the code in, if it is a sandboxed_command
+fn container_name(command: &Command) -> Option<String> {
+    if command.get_program() != "docker" {
+        return None;
+    }
+    let mut args = command.get_args().map(|arg| arg.to_string_lossy());
+    args.find(|arg| arg == "--name")?;
+    args.next()
+        .filter(|name| name.starts_with(CONTAINER_PREFIX))
+        .map(|name| name.to_string())
+}
+
+///Lets sniprun stop a run that's not wanted anymore (eg: the user started another one):
+///the command run by `run_command` is then killed.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+impl PartialEq for CancelToken {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// Run the command to completion and return its output, like `Command::output()` does.
+/// If the data holds an enabled output stream, every line the command prints on its standard
+/// output is also sent through it as soon as it's available.
+/// If the data holds a timeout, the command is killed when it runs for longer than that, and
+/// an error of kind `TimedOut` is returned (converted to a TimeoutError by `?`).
+/// It is killed as well if the run is cancelled (error of kind `Interrupted`).
+/// The user's resource limits (Unix only) are applied too: an error of kind `TimedOut` or
+/// `OutOfMemory` (converted to a RuntimeError) is returned when one stopped the command.
+/// It runs in the directory the user chose (config: `run_dir`) if any, whatever the directory
+/// set on the command: call it in execute() only, not to build the code (see
+/// run_build_command).
+pub fn run_command(command: &mut Command, data: &DataHolder) -> io::Result<Output> {
+    // a sandbox only sees the work directory
+    if let Some(dir) = data.execution_dir().filter(|_| data.sandbox.is_empty()) {
+        command.current_dir(dir);
+    }
+    run_limited(command, data, data.output_stream.0.clone())
+}
+
+/// run_command for a build step that may take long (eg: a cargo build fetching and compiling
+/// crates): it has the same timeout, cancellation and limits, but it runs in the directory set
+/// on the command, and what it prints isn't streamed as the output of the code
+pub fn run_build_command(command: &mut Command, data: &DataHolder) -> io::Result<Output> {
+    run_limited(command, data, None)
+}
+
+/// run_command, in the directory set on the command, streaming its output through the sender
+fn run_limited(
+    command: &mut Command,
+    data: &DataHolder,
+    sender: Option<mpsc::Sender<String>>,
+) -> io::Result<Output> {
+    let container = container_name(command);
+    if container.is_none() {
+        limit_resources(command, data);
+    }
+    let output = run_to_completion(command, data, sender, container.as_deref())?;
+    match limit_error(&output, data, container.is_some()) {
+        Some(e) => Err(e),
+        None => Ok(output),
+    }
+}
+
+/// run_command, without the resource limits; the container, if the code runs in one, is killed
+/// along with the command
+fn run_to_completion(
+    command: &mut Command,
+    data: &DataHolder,
+    sender: Option<mpsc::Sender<String>>,
+    container: Option<&str>,
+) -> io::Result<Output> {
+    // without streaming, the whole output is kept until the end: a code printing in a loop
+    // would fill the memory before any timeout, it is stopped once its output is over
+    // max_output_bytes
+    let cap = sender.is_none().then_some(data.max_output_bytes as usize);
+    let flooded = Arc::new(AtomicBool::new(false));
+
+    // in its own process group, so that what the code starts is killed along with it
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(command, 0);
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    //both outputs are read on the side so the child never blocks on a full pipe
+    let mut child_stderr = child.stderr.take().unwrap();
+    let stderr_reader = thread::spawn(move || {
+        let mut stderr = vec![];
+        let _ = child_stderr.read_to_end(&mut stderr);
+        stderr
+    });
+    let mut child_stdout = child.stdout.take().unwrap();
+    let stdout_flooded = flooded.clone();
+    let stdout_reader = thread::spawn(move || -> io::Result<Vec<u8>> {
+        let mut stdout = vec![];
+        if let Some(cap) = cap {
+            // read by chunks: a line may never end
+            let mut chunk = [0; 8192];
+            loop {
+                let read = child_stdout.read(&mut chunk)?;
+                if read == 0 {
+                    break;
+                }
+                stdout.extend_from_slice(&chunk[..read]);
+                if stdout.len() > cap {
+                    stdout.truncate(cap);
+                    stdout_flooded.store(true, Ordering::SeqCst);
+                    break;
+                }
+            }
+            return Ok(stdout);
+        }
+        let mut reader = BufReader::new(child_stdout);
+        let mut line = vec![];
+        while reader.read_until(b'\n', &mut line)? > 0 {
+            if let Some(sender) = &sender {
+                let _ = sender.send(
+                    String::from_utf8_lossy(&line)
+                        .trim_end_matches('\n')
+                        .to_string(),
+                );
+            }
+            stdout.append(&mut line);
+        }
+        Ok(stdout)
+    });
+
+    let status = wait_for(&mut child, data, &flooded, container);
+    let stdout = stdout_reader.join().unwrap_or_else(|_| Ok(vec![]))?;
+    let stderr = stderr_reader.join().unwrap_or_default();
+    if flooded.load(Ordering::SeqCst) {
+        return Err(io::Error::new(
+            io::ErrorKind::FileTooLarge,
+            format!(
+                "the code printed more than {} bytes (max_output_bytes), it was stopped. \
+                 The beginning of its output:\n{}",
+                data.max_output_bytes,
+                String::from_utf8_lossy(&stdout)
+            ),
+        ));
+    }
+    Ok(Output {
+        status: status?,
+        stdout,
+        stderr,
+    })
+}
+
+/// make the command's process unable to use more memory or CPU time than the user allows
+/// (config: `max_memory_mb` and `max_cpu_seconds`)
+#[cfg(unix)]
+fn limit_resources(command: &mut Command, data: &DataHolder) {
+    use std::os::unix::process::CommandExt;
+    let memory = data.max_memory_mb.map(|mb| mb * 1024 * 1024);
+    let cpu = data.max_cpu_seconds;
+    if memory.is_none() && cpu.is_none() {
+        return;
+    }
+    let limit = |soft: u64, hard: u64| libc::rlimit {
+        rlim_cur: soft as libc::rlim_t,
+        rlim_max: hard as libc::rlim_t,
+    };
+    // safety: setrlimit is async-signal-safe, and nothing is allocated in the closure
+    unsafe {
+        command.pre_exec(move || {
+            if let Some(bytes) = memory {
+                if libc::setrlimit(libc::RLIMIT_AS, &limit(bytes, bytes)) != 0 {
+                    return Err(io::Error::last_os_error());
+                }
+            }
+            // the process gets a SIGXCPU when it reaches the limit, and is killed a second later
+            if let Some(seconds) = cpu {
+                if libc::setrlimit(libc::RLIMIT_CPU, &limit(seconds, seconds + 1)) != 0 {
+                    return Err(io::Error::last_os_error());
+                }
+            }
+            Ok(())
+        });
+    }
+}
+
+#[cfg(not(unix))]
+fn limit_resources(_command: &mut Command, _data: &DataHolder) {}
+
+/// if the command was stopped by one of the user's resource limits, the error saying which.
+/// Only when there is evidence of it: the signal sent when the CPU time is over, or an error
+/// message about a failed memory allocation (RLIMIT_AS makes the allocations fail, the program
+/// then reports it its own way); any other failure is the code's.
+/// In a container, the signal is in the exit code of the docker client (128 + the signal), and
+/// the memory limit kills the code instead: that is a failure like any other
+#[cfg(unix)]
+fn limit_error(output: &Output, data: &DataHolder, sandboxed: bool) -> Option<io::Error> {
+    use std::os::unix::process::ExitStatusExt;
+    if output.status.success() {
+        return None;
+    }
+    let signal = if sandboxed {
+        output.status.code().map(|code| code - 128)
+    } else {
+        output.status.signal()
+    };
+    if let Some(seconds) = data.max_cpu_seconds {
+        if signal == Some(libc::SIGXCPU) {
+            return Some(io::Error::new(
+                io::ErrorKind::TimedOut,
+                format!(
+                    "the code used more than {}s of CPU time (max_cpu_seconds)",
+                    seconds
+                ),
+            ));
+        }
+    }
+    if let Some(mb) = data.max_memory_mb {
+        let stderr = String::from_utf8_lossy(&output.stderr).to_lowercase();
+        // ENOMEM, C's and python's messages, C++'s exception, rust's and go's messages
+        let out_of_memory = [
+            "cannot allocate memory",
+            "memory allocation failed",
+            "out of memory",
+            "memoryerror",
+            "std::bad_alloc",
+        ]
+        .iter()
+        .any(|message| stderr.contains(message))
+            || (stderr.contains("memory allocation of") && stderr.contains("failed"));
+        if out_of_memory {
+            return Some(io::Error::new(
+                io::ErrorKind::OutOfMemory,
+                format!(
+                    "the code used more than {} MB of memory (max_memory_mb)",
+                    mb
+                ),
+            ));
+        }
+    }
+    None
+}
+
+#[cfg(not(unix))]
+fn limit_error(_output: &Output, _data: &DataHolder, _sandboxed: bool) -> Option<io::Error> {
+    None
+}
+
+/// kill the child and the processes it started, that would otherwise keep its output open: it
+/// must lead its own process group (see run_to_completion)
+#[cfg(unix)]
+pub fn kill_process_group(child: &mut Child) {
+    // safety: killpg only sends a signal
+    unsafe {
+        libc::killpg(child.id() as libc::pid_t, libc::SIGKILL);
+    }
+    let _ = child.kill();
+}
+
+#[cfg(not(unix))]
+pub fn kill_process_group(child: &mut Child) {
+    let _ = child.kill();
+}
+
+/// wait for the child to exit, killing it (and the container it runs the code in, if any: the
+/// code would go on without its client) if it runs for longer than the timeout, if the run is
+/// cancelled or if it printed too much
+fn wait_for(
+    child: &mut Child,
+    data: &DataHolder,
+    flooded: &AtomicBool,
+    container: Option<&str>,
+) -> io::Result<ExitStatus> {
+    let start = Instant::now();
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(status);
+        }
+        let error = match data.timeout {
+            _ if data.cancel.is_cancelled() => Some(io::Error::new(
+                io::ErrorKind::Interrupted,
+                "the run was cancelled",
+            )),
+            _ if flooded.load(Ordering::SeqCst) => Some(io::Error::new(
+                io::ErrorKind::FileTooLarge,
+                "the code printed too much",
+            )),
+            Some(timeout) if start.elapsed() > timeout => Some(io::Error::new(
+                io::ErrorKind::TimedOut,
+                format!("the code did not finish within {}s", timeout.as_secs_f64()),
+            )),
+            _ => None,
+        };
+        if let Some(error) = error {
+            if let Some(container) = container {
+                let _ = Command::new("docker")
+                    .arg("kill")
+                    .arg(container)
+                    .stdout(Stdio::null())
+                    .stderr(Stdio::null())
+                    .status();
+            }
+            kill_process_group(child);
+            let _ = child.wait();
+            return Err(error);
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn levels_are_shown_the_way_they_are_asked_for() {
+        for level in [
+            SupportLevel::Line,
+            SupportLevel::Bloc,
+            SupportLevel::Import,
+            SupportLevel::File,
+            SupportLevel::Project,
+            SupportLevel::System,
+        ] {
+            assert_eq!(level.to_string().parse::<SupportLevel>().unwrap(), level);
+        }
+        assert_eq!(
+            " Block ".parse::<SupportLevel>().unwrap(),
+            SupportLevel::Bloc
+        );
+        assert!("everything".parse::<SupportLevel>().is_err());
+    }
+
+    #[test]
+    fn a_level_is_capped_by_the_maximum() {
+        assert_eq!(
+            SupportLevel::File.capped(SupportLevel::Bloc),
+            SupportLevel::Bloc
+        );
+        assert_eq!(
+            SupportLevel::Line.capped(SupportLevel::Bloc),
+            SupportLevel::Line
+        );
+    }
+    #[cfg(unix)]
+    use std::os::unix::process::ExitStatusExt;
+
+    /// the output of a process killed by that signal (exit code 1 if None), printing stderr
+    #[cfg(unix)]
+    fn failed(signal: Option<i32>, stderr: &str) -> Output {
+        Output {
+            status: ExitStatus::from_raw(signal.unwrap_or(1 << 8)),
+            stdout: vec![],
+            stderr: stderr.as_bytes().to_vec(),
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn limits_are_blamed_only_with_evidence() {
+        let mut data = DataHolder::with_code_in_temp_dir("c", "");
+        data.max_cpu_seconds = Some(2);
+        data.max_memory_mb = Some(100);
+        let kind = |output: &Output| limit_error(output, &data, false).map(|e| e.kind());
+
+        assert_eq!(
+            kind(&failed(Some(libc::SIGXCPU), "")),
+            Some(io::ErrorKind::TimedOut)
+        );
+        assert_eq!(
+            kind(&failed(None, "MemoryError: cannot allocate")),
+            Some(io::ErrorKind::OutOfMemory)
+        );
+        assert_eq!(
+            kind(&failed(
+                Some(libc::SIGABRT),
+                "memory allocation of 1024 bytes failed"
+            )),
+            Some(io::ErrorKind::OutOfMemory)
+        );
+        // killed by someone else, a failed assertion, an error of the code
+        assert_eq!(kind(&failed(Some(libc::SIGKILL), "")), None);
+        assert_eq!(kind(&failed(Some(libc::SIGABRT), "assertion failed")), None);
+        assert_eq!(kind(&failed(None, "error: no such memory")), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn limits_stop_the_code() {
+        let mut data = DataHolder::with_code_in_temp_dir("sh", "");
+        data.max_cpu_seconds = Some(1);
+        let mut command = Command::new("sh");
+        command.arg("-c").arg("while :; do :; done");
+        let error = run_command(&mut command, &data).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::TimedOut);
+
+        if check_toolchain(&["python3"]).is_err() {
+            return;
+        }
+        data.max_cpu_seconds = None;
+        data.max_memory_mb = Some(100);
+        let mut command = Command::new("python3");
+        command.arg("-c").arg("x = bytearray(1024 * 1024 * 1024)");
+        let error = run_command(&mut command, &data).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::OutOfMemory);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn the_signal_of_a_container_is_in_its_exit_code() {
+        let mut data = DataHolder::with_code_in_temp_dir("sh", "");
+        data.max_cpu_seconds = Some(2);
+        let output = failed(None, "");
+        let output = Output {
+            status: ExitStatus::from_raw((128 + libc::SIGXCPU) << 8),
+            ..output
+        };
+        let error = limit_error(&output, &data, true).unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::TimedOut);
+        assert!(limit_error(&output, &data, false).is_none());
+    }
+
+    #[test]
+    fn sandboxed_commands_are_named_and_limited() {
+        let mut data = DataHolder::with_code_in_temp_dir("sh", "");
+        data.sandbox = String::from("docker");
+        data.sandbox_image = String::from("alpine");
+        data.max_memory_mb = Some(100);
+        data.max_cpu_seconds = Some(2);
+        let command = sandboxed_command(&data, &data.work_dir, "sh");
+        if command.get_program() != "docker" {
+            // docker isn't installed: the code runs natively
+            assert!(container_name(&command).is_none());
+            return;
+        }
+        let args: Vec<String> = command
+            .get_args()
+            .map(|arg| arg.to_string_lossy().to_string())
+            .collect();
+        for arg in [
+            "--init",
+            "--user",
+            "--memory=100m",
+            "--cpus=1",
+            "--ulimit=cpu=2:3",
+        ] {
+            assert!(
+                args.contains(&arg.to_string()),
+                "{} missing in {:?}",
+                arg,
+                args
+            );
+        }
+        // the options go before the image
+        assert_eq!(args[args.len() - 2..], ["alpine", "sh"]);
+        let name = container_name(&command).unwrap();
+        assert!(name.starts_with(CONTAINER_PREFIX));
+        let other = container_name(&sandboxed_command(&data, &data.work_dir, "sh")).unwrap();
+        assert_ne!(name, other);
+        assert!(container_name(&Command::new("sh")).is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn no_limit_no_blame() {
+        let data = DataHolder::with_code_in_temp_dir("c", "");
+        assert!(limit_error(&failed(Some(libc::SIGXCPU), "out of memory"), &data, false).is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn timeout_kills_what_the_code_started() {
+        let mut data = DataHolder::with_code_in_temp_dir("sh", "");
+        data.timeout = Some(Duration::from_millis(200));
+        let start = Instant::now();
+        let mut command = Command::new("sh");
+        command.arg("-c").arg("sleep 30 & sleep 30");
+        let error = run_command(&mut command, &data).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::TimedOut);
+        assert!(start.elapsed() < Duration::from_secs(10));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn cancel_kills_what_the_code_started() {
+        let data = DataHolder::with_code_in_temp_dir("sh", "");
+        let cancel = data.cancel.clone();
+        let canceller = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(200));
+            cancel.cancel();
+        });
+        let start = Instant::now();
+        let mut command = Command::new("sh");
+        command.arg("-c").arg("sleep 30 & sleep 30");
+        let error = run_command(&mut command, &data).unwrap_err();
+        canceller.join().unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::Interrupted);
+        assert!(start.elapsed() < Duration::from_secs(10));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn a_build_runs_where_it_is_told_within_the_limits() {
+        let mut data = DataHolder::with_code_in_temp_dir("sh", "");
+        let (sender, receiver) = mpsc::channel();
+        data.output_stream = OutputStream::new(sender);
+        data.run_dir = String::from("/");
+        data.timeout = Some(Duration::from_millis(200));
+        let build_dir = std::env::temp_dir().canonicalize().unwrap();
+
+        let mut command = Command::new("pwd");
+        command.current_dir(&build_dir);
+        let output = run_build_command(&mut command, &data).unwrap();
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout).trim(),
+            build_dir.to_string_lossy()
+        );
+        // what the build prints isn't the output of the code
+        assert!(receiver.try_recv().is_err());
+
+        let start = Instant::now();
+        let mut command = Command::new("sh");
+        command.arg("-c").arg("sleep 30 & sleep 30");
+        let error = run_build_command(&mut command, &data).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::TimedOut);
+        assert!(start.elapsed() < Duration::from_secs(10));
+    }
+
+    #[test]
+    fn the_closest_ancestor_with_the_marker_is_found() {
+        let data = DataHolder::with_code_in_temp_dir("go", "");
+        let root = Path::new(&data.work_dir).canonicalize().unwrap();
+        let deep = root.join("project/src/pkg");
+        fs::create_dir_all(&deep).unwrap();
+        fs::write(root.join("project/go.mod"), "").unwrap();
+        fs::write(deep.join("main.go"), "").unwrap();
+
+        let project = Some(root.join("project"));
+        assert_eq!(find_ancestor(&deep.join("main.go"), "go.mod"), project);
+        assert_eq!(find_ancestor(&deep, "go.mod"), project);
+        assert_eq!(find_ancestor(&root.join("project"), "go.mod"), project);
+        // up to the root, where there is none
+        assert_eq!(find_ancestor(&deep, "no-such-marker-anywhere"), None);
+        assert_eq!(find_ancestor(Path::new(""), "go.mod"), None);
+
+        // the lookup happens where a symlinked file really is
+        let link = root.join("link.go");
+        std::os::unix::fs::symlink(deep.join("main.go"), &link).unwrap();
+        assert_eq!(find_ancestor(&link, "go.mod"), project);
+    }
+
+    #[test]
+    fn the_selection_is_dedented_and_normalized() {
+        let mut data = DataHolder::with_code_in_temp_dir("python", "");
+        data.current_bloc = String::from("\tif a:\r\n\t\tb()\r\n\r\n\tc()\r\n");
+        assert_eq!(
+            selected_code(&data, SupportLevel::Bloc),
+            "if a:\n\tb()\n\nc()"
+        );
+
+        data.current_bloc = String::from("    a = 1\n      b = 2\n");
+        assert_eq!(selected_code(&data, SupportLevel::Bloc), "a = 1\n  b = 2");
+
+        // a level below Bloc only runs the line
+        data.current_line = String::from("    print(1)\r");
+        assert_eq!(selected_code(&data, SupportLevel::Line), "print(1)");
+        assert_eq!(selected_code(&data, SupportLevel::Unsupported), "");
+
+        // nothing selected, the line is run
+        data.current_bloc = String::from(" \n\t\n");
+        assert_eq!(selected_code(&data, SupportLevel::Bloc), "print(1)");
+        data.current_line = String::new();
+        assert_eq!(selected_code(&data, SupportLevel::Bloc), "");
+    }
+
+    #[test]
+    fn comments_are_told_from_code() {
+        let rust = CommentSyntax::C_LIKE;
+        assert!(rust.only_comments("// a\n/// b\n  /* c\n d */\n"));
+        assert!(rust.only_comments(""));
+        assert!(!rust.only_comments("// a\nlet a = 1;"));
+        assert!(!rust.only_comments("/* a */ let a = 1; // b"));
+        assert_eq!(
+            rust.strip("let a = 1; // one\n/* two */let b = 2;").trim(),
+            "let a = 1; \nlet b = 2;"
+        );
+
+        // the generic fallback knows the usual ones, and takes nothing for a comment otherwise
+        assert!(CommentSyntax::guess("python").only_comments("# a\n  # b"));
+        assert!(!CommentSyntax::guess("python").only_comments("# a\nprint(1)"));
+        assert!(CommentSyntax::guess("lua").only_comments("-- a\n--[[ b\n c ]]"));
+        assert!(!CommentSyntax::guess("unknown").only_comments("# a"));
+    }
+}
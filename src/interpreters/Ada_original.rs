@@ -0,0 +1,198 @@
+#[derive(Debug, Clone)]
+#[allow(non_camel_case_types)]
+pub struct Ada_original {
+    support_level: SupportLevel,
+    data: DataHolder,
+    code: String,
+
+    ///specific to ada
+    ada_work_dir: String,
+    ///named after the main unit: GNAT wants a file per unit, named like it
+    main_file_path: String,
+    bin_path: String,
+    line_offsets: LineOffsets,
+}
+
+lazy_static! {
+    /// the code is a whole main procedure ('procedure Name is' ... 'end Name;')
+    static ref ADA_UNIT: Regex = Regex::new(r"(?im)^procedure\s+(\w+)").unwrap();
+    /// a context clause, that goes before the unit
+    static ref ADA_CONTEXT_CLAUSE: Regex = Regex::new(r"(?i)^\s*(with|use)\s+[\w.]+").unwrap();
+    /// a declaration, that goes in the declarative part of the procedure (before 'begin'),
+    /// eg: 'X : Integer := 1;' or 'type Color is (Red, Green);'
+    static ref ADA_DECLARATION: Regex =
+        Regex::new(r"(?i)^\s*(\w+(\s*,\s*\w+)*\s*:[^=]|(sub)?type\s|pragma\s)").unwrap();
+    /// the code writes with Ada.Text_IO
+    static ref ADA_TEXT_IO: Regex =
+        Regex::new(r"(?i)\b(Put_Line|Put|New_Line|Get_Line)\b").unwrap();
+    /// GNAT locates errors as 'main.adb:LINE:COLUMN: message', exceptions as
+    /// 'raised CONSTRAINT_ERROR : main.adb:LINE overflow check failed'
+    static ref ADA_LOCATION: Regex = Regex::new(r"(\w+\.adb:)(\d+)").unwrap();
+}
+
+impl Ada_original {
+    /// the name of the main procedure of the code, if it is a whole unit ('procedure Name is'
+    /// ... 'end Name;')
+    fn unit_name(code: &str) -> Option<String> {
+        let name = ADA_UNIT.captures(code)?[1].to_string();
+        let end = Regex::new(&format!(r"(?im)^end\s+{}\s*;", regex::escape(&name))).ok()?;
+        end.is_match(code).then_some(name)
+    }
+
+    /// the error, with the lines of the code rather than the ones of the generated file,
+    /// located in the buffer where the first error is
+    fn error(&self, error: fn(String) -> SniprunError, report: &str) -> SniprunError {
+        let mut first_line = None;
+        let message = ADA_LOCATION.replace_all(report, |captures: &regex::Captures| {
+            let code_line = captures[2]
+                .parse::<i64>()
+                .ok()
+                .and_then(|line| self.line_offsets.code_line(line));
+            if first_line.is_none() {
+                first_line = code_line.and_then(|line| self.data.buffer_line(line));
+            }
+            match code_line {
+                Some(line) => format!("{}{}", &captures[1], line),
+                None => captures[0].to_string(),
+            }
+        });
+        let error = error(message.to_string());
+        match first_line {
+            Some(line) => error.at_line(line),
+            None => error,
+        }
+    }
+}
+
+impl Interpreter for Ada_original {
+    declare_interpreter!(Ada_original);
+
+    fn new_with_level(data: DataHolder, support_level: SupportLevel) -> Box<Ada_original> {
+        let awd = work_dir_path(&data, &Self::get_work_dir_name());
+        Box::new(Ada_original {
+            data,
+            support_level,
+            code: String::from(""),
+            main_file_path: awd.clone() + "/main.adb",
+            bin_path: awd.clone() + "/main",
+            ada_work_dir: awd,
+            line_offsets: LineOffsets::default(),
+        })
+    }
+
+    fn get_supported_languages() -> Vec<String> {
+        vec![String::from("ada"), String::from("adb")]
+    }
+
+    fn get_generated_file(&self) -> Option<String> {
+        Some(self.main_file_path.clone())
+    }
+
+    fn get_max_support_level() -> SupportLevel {
+        SupportLevel::Bloc
+    }
+
+    fn get_capabilities() -> Capabilities {
+        Capabilities::SEPARATE_BUILD | Capabilities::STREAMING
+    }
+
+    fn get_line_offsets(&self) -> LineOffsets {
+        self.line_offsets.clone()
+    }
+
+    fn fetch_code(&mut self) -> Result<(), SniprunError> {
+        self.code = selected_code(&self.data, self.get_current_level());
+        Ok(())
+    }
+
+    fn add_boilerplate(&mut self) -> Result<(), SniprunError> {
+        self.line_offsets = LineOffsets::default();
+        let text_io = ADA_TEXT_IO.is_match(&self.code) && !self.code.contains("Ada.Text_IO");
+        if let Some(name) = Self::unit_name(&self.code) {
+            let file_name = name.to_lowercase();
+            self.main_file_path = format!("{}/{}.adb", self.ada_work_dir, file_name);
+            self.bin_path = format!("{}/{}", self.ada_work_dir, file_name);
+            if text_io {
+                self.code = format!("with Ada.Text_IO; use Ada.Text_IO;\n{}", self.code);
+                self.line_offsets.prepend(1);
+            }
+            return Ok(());
+        }
+
+        // the context clauses, then the declarations, then the statements: the boilerplate
+        // lines are added between them so that the lines of the code keep their order
+        let lines: Vec<&str> = self.code.lines().collect();
+        let is_blank = |l: &str| l.trim().is_empty() || l.trim_start().starts_with("--");
+        let context_end = lines
+            .iter()
+            .position(|l| !(ADA_CONTEXT_CLAUSE.is_match(l) || is_blank(l)))
+            .unwrap_or(lines.len());
+        let declarations_end = lines[context_end..]
+            .iter()
+            .position(|l| !(ADA_DECLARATION.is_match(l) || is_blank(l)))
+            .map_or(lines.len(), |i| context_end + i);
+
+        let mut generated: Vec<&str> = vec![];
+        let mut added = vec![];
+        let mut add = |generated: &mut Vec<&str>, line| {
+            generated.push(line);
+            added.push(generated.len() as i64);
+        };
+        if text_io {
+            add(&mut generated, "with Ada.Text_IO; use Ada.Text_IO;");
+        }
+        generated.extend(&lines[..context_end]);
+        add(&mut generated, "procedure Main is");
+        generated.extend(&lines[context_end..declarations_end]);
+        add(&mut generated, "begin");
+        generated.extend(&lines[declarations_end..]);
+        // a procedure can't be empty
+        if lines[declarations_end..].iter().all(|l| is_blank(l)) {
+            add(&mut generated, "null;");
+        }
+        add(&mut generated, "end Main;");
+        for line in added {
+            self.line_offsets.insert(line, 1);
+        }
+        self.code = generated.join("\n") + "\n";
+        self.main_file_path = self.ada_work_dir.clone() + "/main.adb";
+        self.bin_path = self.ada_work_dir.clone() + "/main";
+        Ok(())
+    }
+
+    fn build(&mut self) -> Result<(), SniprunError> {
+        write_code(&self.main_file_path, &self.code)?;
+        check_toolchain(&["gnatmake"])?;
+        // the objects and .ali files of GNAT stay in the work directory
+        let output = Command::new("gnatmake")
+            .arg("-q")
+            .arg("-g")
+            .arg(&self.main_file_path)
+            .arg("-o")
+            .arg(&self.bin_path)
+            .current_dir(&self.ada_work_dir)
+            .output()?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            let report = String::from_utf8_lossy(&output.stderr).to_string()
+                + &String::from_utf8_lossy(&output.stdout);
+            Err(self.error(SniprunError::CompilationError, &report))
+        }
+    }
+
+    fn execute(&mut self) -> Result<ExecutionResult, SniprunError> {
+        let output = run_command(
+            Command::new(&self.bin_path).current_dir(&self.ada_work_dir),
+            &self.data,
+        )?;
+        if output.status.success() {
+            Ok(ExecutionResult::from_output(&output))
+        } else {
+            Err(self.error(
+                SniprunError::RuntimeError,
+                &String::from_utf8_lossy(&output.stderr),
+            ))
+        }
+    }
+}
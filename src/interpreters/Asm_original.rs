@@ -0,0 +1,234 @@
+#[derive(Debug, Clone)]
+#[allow(non_camel_case_types)]
+pub struct Asm_original {
+    support_level: SupportLevel,
+    data: DataHolder,
+    code: String,
+
+    ///specific to assembly
+    asm_work_dir: String,
+    main_file_path: String,
+    ///the binary of the code, named by a hash of the code: the same code is only assembled once
+    bin_path: String,
+    ///"gas" (AT&T syntax, assembled by as) or "nasm" (Intel syntax, assembled by nasm)
+    syntax: String,
+    line_offsets: LineOffsets,
+}
+
+/// how many assembled binaries are kept, the oldest ones are removed first
+const KEPT_ASM_BINARIES: usize = 20;
+
+lazy_static! {
+    /// gas directives (eg: '.text', '.globl') or AT&T registers (eg: '%rax') mean gas syntax
+    static ref ASM_GAS_SYNTAX: Regex =
+        Regex::new(r"(?m)^\s*\.[a-z]+\b|%[re]?(ax|bx|cx|dx|si|di|sp|bp|ip)\b|%r\d+").unwrap();
+    /// the code has its own entry point, rather than a main called by the C runtime
+    static ref ASM_START: Regex = Regex::new(r"(?m)^\s*_start\s*:").unwrap();
+    /// the code has an entry point: its own, or a main called by the C runtime
+    static ref ASM_ENTRY_POINT: Regex = Regex::new(r"(?m)^\s*(_start|main)\s*:").unwrap();
+    /// the assemblers locate errors as 'main.s:LINE: Error: message' (gas) or
+    /// 'main.asm:LINE: error: message' (nasm)
+    static ref ASM_LOCATION: Regex = Regex::new(r"main\.(?:s|asm):(\d+):").unwrap();
+}
+
+impl Asm_original {
+    /// the syntax the user chose (interpreter option `syntax`), else the one the code looks like
+    fn syntax(data: &DataHolder) -> String {
+        match data
+            .interpreter_option(&Self::get_name(), "syntax")
+            .and_then(|s| s.as_str())
+        {
+            Some(syntax) => syntax.to_string(),
+            None if data.filetype == "nasm" => String::from("nasm"),
+            None if ASM_GAS_SYNTAX.is_match(&data.current_bloc) => String::from("gas"),
+            None => String::from("nasm"),
+        }
+    }
+
+    /// the error of a failed assembly or link, located at the first line the assembler reports
+    fn compilation_error(&self, output: &std::process::Output) -> SniprunError {
+        let report = String::from_utf8_lossy(&output.stderr).to_string()
+            + &String::from_utf8_lossy(&output.stdout);
+        let error = SniprunError::CompilationError(report.clone());
+        let line = ASM_LOCATION
+            .captures(&report)
+            .and_then(|captures| captures[1].parse::<i64>().ok())
+            .and_then(|line| self.to_buffer_line(line));
+        match line {
+            Some(line) => error.at_line(line),
+            None => error,
+        }
+    }
+
+    /// the signal that killed the program (eg: a segfault)
+    fn signal_report(status: &std::process::ExitStatus) -> String {
+        use std::os::unix::process::ExitStatusExt;
+        let signal = status.signal().unwrap_or_default();
+        let name = match signal {
+            libc::SIGSEGV => "SIGSEGV, segmentation fault",
+            libc::SIGBUS => "SIGBUS, bus error",
+            libc::SIGILL => "SIGILL, illegal instruction",
+            libc::SIGFPE => "SIGFPE, arithmetic exception",
+            libc::SIGTRAP => "SIGTRAP, trace/breakpoint trap",
+            libc::SIGABRT => "SIGABRT, aborted",
+            libc::SIGKILL => "SIGKILL",
+            _ => "",
+        };
+        if name.is_empty() {
+            format!("the program was killed by signal {}", signal)
+        } else {
+            format!("the program was killed by signal {} ({})", signal, name)
+        }
+    }
+}
+
+impl Interpreter for Asm_original {
+    declare_interpreter!(Asm_original);
+
+    fn new_with_level(data: DataHolder, support_level: SupportLevel) -> Box<Asm_original> {
+        let awd = work_dir_path(&data, &Self::get_work_dir_name());
+        let syntax = Self::syntax(&data);
+        let mfp = if syntax == "gas" {
+            awd.clone() + "/main.s"
+        } else {
+            awd.clone() + "/main.asm"
+        };
+        Box::new(Asm_original {
+            data,
+            support_level,
+            code: String::from(""),
+            asm_work_dir: awd,
+            main_file_path: mfp,
+            bin_path: String::new(),
+            syntax,
+            line_offsets: LineOffsets::default(),
+        })
+    }
+
+    fn get_supported_languages() -> Vec<String> {
+        vec![
+            String::from("asm"),
+            String::from("nasm"),
+            String::from("assembly"),
+        ]
+    }
+
+    fn get_generated_file(&self) -> Option<String> {
+        Some(self.main_file_path.clone())
+    }
+
+    fn get_max_support_level() -> SupportLevel {
+        SupportLevel::Bloc
+    }
+
+    fn get_capabilities() -> Capabilities {
+        Capabilities::SEPARATE_BUILD | Capabilities::STREAMING
+    }
+
+    fn get_line_offsets(&self) -> LineOffsets {
+        self.line_offsets.clone()
+    }
+
+    fn fetch_code(&mut self) -> Result<(), SniprunError> {
+        self.code = selected_code(&self.data, self.get_current_level());
+        Ok(())
+    }
+
+    fn add_boilerplate(&mut self) -> Result<(), SniprunError> {
+        self.line_offsets = LineOffsets::default();
+        // gas warns about a last line without a newline
+        if !self.code.ends_with('\n') {
+            self.code.push('\n');
+        }
+        // instructions without an entry point run in a main, whose return value (eg: the
+        // last value of eax) is the exit code
+        if !ASM_ENTRY_POINT.is_match(&self.code) {
+            let (header, text) = if self.syntax == "gas" {
+                ("    .globl main\n    .text\nmain:\n", "    .text")
+            } else {
+                ("global main\nsection .text\nmain:\n", "section .text")
+            };
+            self.code = format!("{}{}{}\n    ret\n", header, self.code, text);
+            self.line_offsets.prepend(3);
+        }
+        self.bin_path = format!(
+            "{}/{}.bin",
+            self.asm_work_dir,
+            crate::cache::key(&(&self.code, &self.syntax))
+        );
+        Ok(())
+    }
+
+    fn build(&mut self) -> Result<(), SniprunError> {
+        write_code(&self.main_file_path, &self.code)?;
+        if Path::new(&self.bin_path).is_file() {
+            info!("[ASM] reusing {}", self.bin_path);
+            return Ok(());
+        }
+
+        let object = self.asm_work_dir.clone() + "/main.o";
+        let mut assembler = if self.syntax == "gas" {
+            check_toolchain(&["as"])?;
+            Command::new("as")
+        } else {
+            check_toolchain(&["nasm"])?;
+            let mut command = Command::new("nasm");
+            command.arg("-f").arg("elf64");
+            command
+        };
+        let output = assembler
+            .arg("-o")
+            .arg(&object)
+            .arg(&self.main_file_path)
+            .current_dir(&self.asm_work_dir)
+            .output()?;
+        if !output.status.success() {
+            return Err(self.compilation_error(&output));
+        }
+
+        // with its own _start, the code is linked without the C runtime
+        let mut linker = if ASM_START.is_match(&self.code) {
+            check_toolchain(&["ld"])?;
+            Command::new("ld")
+        } else {
+            check_toolchain(&["gcc"])?;
+            let mut command = Command::new("gcc");
+            command.arg("-no-pie");
+            command
+        };
+        let output = linker
+            .arg("-o")
+            .arg(&self.bin_path)
+            .arg(&object)
+            .current_dir(&self.asm_work_dir)
+            .output()?;
+        if !output.status.success() {
+            return Err(self.compilation_error(&output));
+        }
+        remove_oldest_files(&self.asm_work_dir, "bin", KEPT_ASM_BINARIES);
+        Ok(())
+    }
+
+    fn execute(&mut self) -> Result<ExecutionResult, SniprunError> {
+        let output = run_command(
+            Command::new(&self.bin_path).current_dir(&self.asm_work_dir),
+            &self.data,
+        )?;
+        // the exit code is often the result of the code, it is always shown
+        if let Some(code) = output.status.code() {
+            let mut result = ExecutionResult::from_output(&output);
+            if !result.stdout.is_empty() && !result.stdout.ends_with('\n') {
+                result.stdout.push('\n');
+            }
+            result.stdout.push_str(&format!("exit code: {}", code));
+            return Ok(result);
+        }
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let report = Self::signal_report(&output.status);
+        Err(SniprunError::RuntimeError(if stderr.trim().is_empty() {
+            report
+        } else {
+            format!("{}\n{}", stderr.trim_end(), report)
+        }))
+    }
+}
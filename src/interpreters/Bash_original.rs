@@ -6,98 +6,166 @@ pub struct Bash_original {
     code: String,
     bash_work_dir: String,
     main_file_path: String,
+
+    ///the shell the code runs with, named by the filetype: bash, sh or zsh
+    shell: String,
+    ///extra flags given to the shell, eg: ["-e", "-x"]
+    flags: Vec<String>,
+    line_offsets: LineOffsets,
+}
+
+lazy_static! {
+    /// shells locate errors as '/path/to/main.sh: line N: message' (bash) or
+    /// '/path/to/main.sh:N: message' (zsh, sh)
+    static ref SHELL_ERROR: Regex = Regex::new(r"main\.sh:(?: line )?(\d+):").unwrap();
+}
+
+impl Bash_original {
+    /// the shell for a filetype, bash unless it's a more specific one
+    fn shell(filetype: &str) -> String {
+        match filetype {
+            "sh" | "zsh" => filetype.to_string(),
+            _ => String::from("bash"),
+        }
+    }
+
+    /// the extra flags the user gave to the shell (interpreter option `flags`)
+    fn flags(data: &DataHolder) -> Vec<String> {
+        data.interpreter_option(&Self::get_name(), "flags")
+            .and_then(|f| f.as_array())
+            .map(|f| {
+                f.iter()
+                    .filter_map(|flag| flag.as_str().map(String::from))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// the directory the code runs in: the edited file's, so that relative paths work. Only
+    /// the work directory is available in a sandbox
+    fn run_dir(&self) -> String {
+        let file_dir = Path::new(&self.data.filepath).parent();
+        match file_dir {
+            Some(dir) if self.data.sandbox.is_empty() && dir.is_dir() => {
+                dir.to_string_lossy().to_string()
+            }
+            _ => self.bash_work_dir.clone(),
+        }
+    }
+
+    /// the error of a failed run, located in the buffer if the shell says where it happened
+    fn error(&self, stderr: &str) -> SniprunError {
+        let error = SniprunError::RuntimeError(stderr.to_string());
+        let line = SHELL_ERROR
+            .captures(stderr)
+            .and_then(|captures| captures[1].parse::<i64>().ok())
+            .and_then(|line| self.to_buffer_line(line));
+        match line {
+            Some(line) => error.at_line(line),
+            None => error,
+        }
+    }
 }
 
 impl Interpreter for Bash_original {
+    declare_interpreter!(Bash_original);
+
     fn new_with_level(data: DataHolder, level: SupportLevel) -> Box<Bash_original> {
-        let bwd = data.work_dir.clone() + "/bash-original";
-        let mut builder = DirBuilder::new();
-        builder.recursive(true);
-        builder
-            .create(&bwd)
-            .expect("Could not create directory for bash-original");
+        let bwd = work_dir_path(&data, &Self::get_work_dir_name());
         let mfp = bwd.clone() + "/main.sh";
+        let shell = Self::shell(&data.filetype);
+        let flags = Self::flags(&data);
         Box::new(Bash_original {
             data,
             support_level: level,
             code: String::from(""),
             bash_work_dir: bwd,
             main_file_path: mfp,
+            shell,
+            flags,
+            line_offsets: LineOffsets::default(),
         })
     }
 
-    fn get_name() -> String {
-        String::from("bash_original")
-    }
-
     fn get_supported_languages() -> Vec<String> {
         vec![
             String::from("bash"),
             String::from("shell"),
             String::from("sh"),
+            String::from("zsh"),
         ]
     }
 
-    fn get_current_level(&self) -> SupportLevel {
-        self.support_level
+    fn get_generated_file(&self) -> Option<String> {
+        Some(self.main_file_path.clone())
     }
-    fn set_current_level(&mut self, level: SupportLevel) {
-        self.support_level = level;
+
+    fn get_max_support_level() -> SupportLevel {
+        SupportLevel::Bloc
     }
 
-    fn get_data(&self) -> DataHolder {
-        self.data.clone()
+    fn get_capabilities() -> Capabilities {
+        Capabilities::STREAMING | Capabilities::PROJECT_FILES
     }
 
-    fn get_max_support_level() -> SupportLevel {
-        SupportLevel::Bloc
+    fn get_line_offsets(&self) -> LineOffsets {
+        self.line_offsets.clone()
     }
 
     fn fetch_code(&mut self) -> Result<(), SniprunError> {
-        if !self
-            .data
-            .current_bloc
-            .replace(&[' ', '\t', '\n', '\r'][..], "")
-            .is_empty()
-            && self.get_current_level() >= SupportLevel::Bloc
-        {
-            self.code = self.data.current_bloc.clone();
-        } else if !self.data.current_line.replace(" ", "").is_empty()
-            && self.get_current_level() >= SupportLevel::Line
-        {
-            self.code = self.data.current_line.clone();
-        } else {
-            self.code = String::from("");
-        }
+        self.code = selected_code(&self.data, self.get_current_level());
         Ok(())
     }
 
     fn add_boilerplate(&mut self) -> Result<(), SniprunError> {
         //add shebang just in case
-        self.code = String::from("#!/usr/bin/env bash \n") + &self.code;
+        self.code = format!("#!/usr/bin/env {}\n", self.shell) + &self.code + "\n";
+        self.line_offsets = LineOffsets::default();
+        self.line_offsets.prepend(1);
         Ok(())
     }
 
     fn build(&mut self) -> Result<(), SniprunError> {
-        let mut _file =
-            File::create(&self.main_file_path).expect("Failed to create file for bash-original");
-
-        write(&self.main_file_path, &self.code).expect("Unable to write to file for bash-original");
+        write_code(&self.main_file_path, &self.code)?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(
+                &self.main_file_path,
+                std::fs::Permissions::from_mode(0o755),
+            )?;
+        }
         Ok(())
     }
 
-    fn execute(&mut self) -> Result<String, SniprunError> {
-        let output = Command::new("bash")
-            .arg(&self.main_file_path)
-            .output()
-            .expect("Unable to start process");
-        info!("yay from bash interpreter");
+    fn check(&mut self) -> Result<ExecutionResult, SniprunError> {
+        self.build()?;
+        check_toolchain(&[&self.shell])?;
+        syntax_check(
+            Command::new(&self.shell).arg("-n").arg(&self.main_file_path),
+            // located like runtime errors are, but it's a syntax error
+            |stderr| {
+                let error = SniprunError::CompilationError(stderr.to_string());
+                match self.error(stderr).line() {
+                    Some(line) => error.at_line(line),
+                    None => error,
+                }
+            },
+        )
+    }
+
+    fn execute(&mut self) -> Result<ExecutionResult, SniprunError> {
+        check_toolchain(&[&self.shell])?;
+        let output = run_command(
+            sandboxed_command(&self.data, &self.run_dir(), &self.shell)
+                .args(&self.flags)
+                .arg(&self.main_file_path),
+            &self.data,
+        )?;
         if output.status.success() {
-            return Ok(String::from_utf8(output.stdout).unwrap());
+            Ok(ExecutionResult::from_output(&output))
         } else {
-            return Err(SniprunError::RuntimeError(
-                String::from_utf8(output.stderr).unwrap(),
-            ));
+            Err(self.error(&String::from_utf8_lossy(&output.stderr)))
         }
     }
 }
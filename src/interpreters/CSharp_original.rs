@@ -0,0 +1,379 @@
+#[derive(Debug, Clone)]
+#[allow(non_camel_case_types)]
+pub struct CSharp_original {
+    support_level: SupportLevel,
+    data: DataHolder,
+    code: String,
+
+    ///specific to c#
+    csharp_work_dir: String,
+    main_file_path: String,
+    ///"dotnet" to build the code in a console project, "dotnet-script" or "csi" to run it as
+    ///a script
+    runner: String,
+    ///the dotnet binary (of the SDK the project is built with)
+    dotnet: String,
+    line_offsets: LineOffsets,
+}
+
+lazy_static! {
+    /// the compiler locates errors as 'Program.cs(LINE,COLUMN): error CS0103: message', or
+    /// 'main.csx(LINE,COLUMN)' for a script
+    static ref CSHARP_COMPILATION_ERROR: Regex =
+        Regex::new(r"(?:Program\.cs|main\.csx)\((\d+),\d+\): error CS\d+").unwrap();
+    /// the frames of an unhandled exception are like 'at Program.<Main>$(String[] args) in
+    /// /path/to/Program.cs:line LINE'
+    static ref CSHARP_STACK_FRAME: Regex =
+        Regex::new(r"(?:Program\.cs|main\.csx):line (\d+)").unwrap();
+    /// a using directive (not a using statement), eg: 'using System.Linq;'
+    static ref CSHARP_USING: Regex = Regex::new(r"^\s*using\s+(static\s+)?[\w.]+\s*;").unwrap();
+    /// the code has its own entry point
+    static ref CSHARP_MAIN: Regex = Regex::new(r"\bstatic\s+(async\s+)?\w+\s+Main\s*\(").unwrap();
+}
+
+impl CSharp_original {
+    /// the runner the user chose (interpreter option `runner`), else the first installed of
+    /// dotnet, dotnet-script and csi
+    fn select_runner(data: &DataHolder, dotnet: &str) -> String {
+        if let Some(runner) = data
+            .interpreter_option(&Self::get_name(), "runner")
+            .and_then(|r| r.as_str())
+        {
+            return runner.to_string();
+        }
+        if check_toolchain(&[dotnet]).is_ok() {
+            return String::from("dotnet");
+        }
+        ["dotnet-script", "csi"]
+            .iter()
+            .find(|runner| check_toolchain(&[runner]).is_ok())
+            .map_or_else(|| String::from("dotnet"), |runner| runner.to_string())
+    }
+
+    fn project_dir(&self) -> String {
+        self.csharp_work_dir.clone() + "/project"
+    }
+
+    fn dll_path(&self) -> String {
+        self.project_dir() + "/bin/snippet.dll"
+    }
+
+    /// the dotnet command, quiet and without telemetry
+    fn dotnet_command(&self) -> Command {
+        let mut command = Command::new(&self.dotnet);
+        command
+            .env("DOTNET_CLI_TELEMETRY_OPTOUT", "1")
+            .env("DOTNET_NOLOGO", "1")
+            .current_dir(self.project_dir());
+        command
+    }
+
+    /// the error, located at the first line of the generated file the regex captures
+    fn located(&self, error: SniprunError, regex: &Regex, output: &str) -> SniprunError {
+        let line = regex
+            .captures(output)
+            .and_then(|captures| captures[1].parse::<i64>().ok())
+            .and_then(|line| self.to_buffer_line(line));
+        match line {
+            Some(line) => error.at_line(line),
+            None => error,
+        }
+    }
+
+    /// the console project the code is built in, created once: `dotnet new` takes a while
+    fn create_project(&self) -> Result<(), SniprunError> {
+        if Path::new(&(self.project_dir() + "/snippet.csproj")).is_file() {
+            return Ok(());
+        }
+        info!("[CSHARP] creating the console project");
+        let output = Command::new(&self.dotnet)
+            .arg("new")
+            .arg("console")
+            .arg("--force")
+            .arg("--name")
+            .arg("snippet")
+            .arg("--output")
+            .arg(self.project_dir())
+            .env("DOTNET_CLI_TELEMETRY_OPTOUT", "1")
+            .env("DOTNET_NOLOGO", "1")
+            .output()?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(SniprunError::PrerequisiteError(format!(
+                "could not create a console project: {}",
+                String::from_utf8_lossy(&output.stderr)
+            )))
+        }
+    }
+}
+
+impl Interpreter for CSharp_original {
+    declare_interpreter!(CSharp_original);
+
+    fn new_with_level(data: DataHolder, support_level: SupportLevel) -> Box<CSharp_original> {
+        let cwd = work_dir_path(&data, &Self::get_work_dir_name());
+        let option = |key: &str, default: &str| {
+            data.interpreter_option(&Self::get_name(), key)
+                .and_then(|v| v.as_str())
+                .unwrap_or(default)
+                .to_string()
+        };
+        let dotnet = option("dotnet", "dotnet");
+        let runner = Self::select_runner(&data, &dotnet);
+        let mfp = if runner != "dotnet" {
+            cwd.clone() + "/main.csx"
+        } else {
+            cwd.clone() + "/project/Program.cs"
+        };
+        Box::new(CSharp_original {
+            data,
+            support_level,
+            code: String::from(""),
+            csharp_work_dir: cwd,
+            main_file_path: mfp,
+            runner,
+            dotnet,
+            line_offsets: LineOffsets::default(),
+        })
+    }
+
+    fn get_supported_languages() -> Vec<String> {
+        vec![String::from("cs"), String::from("csharp")]
+    }
+
+    fn get_generated_file(&self) -> Option<String> {
+        Some(self.main_file_path.clone())
+    }
+
+    fn get_max_support_level() -> SupportLevel {
+        SupportLevel::Bloc
+    }
+
+    fn get_capabilities() -> Capabilities {
+        Capabilities::SEPARATE_BUILD | Capabilities::STREAMING
+    }
+
+    fn get_line_offsets(&self) -> LineOffsets {
+        self.line_offsets.clone()
+    }
+
+    fn fetch_code(&mut self) -> Result<(), SniprunError> {
+        self.code = selected_code(&self.data, self.get_current_level());
+        Ok(())
+    }
+
+    fn add_boilerplate(&mut self) -> Result<(), SniprunError> {
+        self.line_offsets = LineOffsets::default();
+        // top-level statements (and the classes after them) need no wrapping, unless the user
+        // targets an older C# (interpreter option `wrap_main`)
+        let wrap_main = self
+            .data
+            .interpreter_option(&Self::get_name(), "wrap_main")
+            .map(|v| config_bool(v, false))
+            .unwrap_or(false);
+        if !wrap_main || self.runner != "dotnet" || CSHARP_MAIN.is_match(&self.code) {
+            return Ok(());
+        }
+        // the using directives are moved before the class, on a single line, and blanked in
+        // place so that the lines of the code don't move
+        let usings: Vec<&str> = self
+            .code
+            .lines()
+            .filter(|l| CSHARP_USING.is_match(l))
+            .map(|l| l.trim())
+            .collect();
+        let body: Vec<&str> = self
+            .code
+            .lines()
+            .map(|l| if CSHARP_USING.is_match(l) { "" } else { l })
+            .collect();
+        self.code = usings.join(" ")
+            + "\nclass Program { static void Main() {\n"
+            + &body.join("\n")
+            + "\n} }\n";
+        self.line_offsets.prepend(2);
+        Ok(())
+    }
+
+    fn build(&mut self) -> Result<(), SniprunError> {
+        if self.runner != "dotnet" {
+            return write_code(&self.main_file_path, &self.code);
+        }
+
+        check_toolchain(&[&self.dotnet])?;
+        self.create_project()?;
+        // the same code isn't built again
+        let hash_path = self.project_dir() + "/built.hash";
+        let hash = crate::cache::key(&self.code);
+        if Path::new(&self.dll_path()).is_file()
+            && std::fs::read_to_string(&hash_path).is_ok_and(|built| built == hash)
+        {
+            info!("[CSHARP] reusing the previous build");
+            return Ok(());
+        }
+        write_code(&self.main_file_path, &self.code)?;
+        let _ = std::fs::remove_file(&hash_path);
+        let output = self
+            .dotnet_command()
+            .arg("build")
+            .arg("--nologo")
+            .arg("--verbosity")
+            .arg("quiet")
+            .arg("--output")
+            .arg(self.project_dir() + "/bin")
+            .output()?;
+        if !output.status.success() {
+            // the diagnostics are on stdout, each one twice: the summary repeats them
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let mut report: Vec<&str> = vec![];
+            for diagnostic in stdout
+                .lines()
+                .filter(|l| CSHARP_COMPILATION_ERROR.is_match(l))
+                .map(|l| l.split(" [").next().unwrap_or(l).trim())
+            {
+                if !report.contains(&diagnostic) {
+                    report.push(diagnostic);
+                }
+            }
+            let report = if report.is_empty() {
+                stdout.to_string()
+            } else {
+                report.join("\n")
+            };
+            return Err(self.located(
+                SniprunError::CompilationError(report.clone()),
+                &CSHARP_COMPILATION_ERROR,
+                &report,
+            ));
+        }
+        write_code(&hash_path, &hash)
+    }
+
+    fn execute(&mut self) -> Result<ExecutionResult, SniprunError> {
+        let mut command = if self.runner != "dotnet" {
+            check_toolchain(&[&self.runner])?;
+            let mut command = Command::new(&self.runner);
+            command
+                .arg(&self.main_file_path)
+                .current_dir(&self.csharp_work_dir);
+            command
+        } else {
+            let mut command = self.dotnet_command();
+            command.arg(self.dll_path());
+            command
+        };
+        let output = run_command(&mut command, &self.data)?;
+        if output.status.success() {
+            return Ok(ExecutionResult::from_output(&output));
+        }
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        // a script is compiled when it runs
+        if CSHARP_COMPILATION_ERROR.is_match(&stderr) {
+            Err(self.located(
+                SniprunError::CompilationError(stderr.clone()),
+                &CSHARP_COMPILATION_ERROR,
+                &stderr,
+            ))
+        } else {
+            Err(self.located(
+                SniprunError::RuntimeError(stderr.clone()),
+                &CSHARP_STACK_FRAME,
+                &stderr,
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_csharp_original {
+    use super::*;
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+
+    fn csharp(code: &str, options: &[(&str, neovim_lib::Value)]) -> Box<CSharp_original> {
+        DataHolder::prepared("cs", code, options)
+    }
+
+    #[test]
+    fn the_code_is_wrapped_in_main_on_demand() {
+        let code = "var a = 1;\nusing System.Linq;\nConsole.WriteLine(a);";
+        let dotnet = || ("runner", neovim_lib::Value::from("dotnet"));
+        assert_eq!(csharp(code, &[dotnet()]).code, code);
+
+        let interpreter = csharp(code, &[dotnet(), ("wrap_main", true.into())]);
+        assert_eq!(
+            interpreter.code,
+            "using System.Linq;\nclass Program { static void Main() {\nvar a = 1;\n\nConsole.WriteLine(a);\n} }\n"
+        );
+        assert_eq!(interpreter.to_buffer_line(5), Some(3));
+
+        let script = csharp(code, &[("runner", "dotnet-script".into())]);
+        assert!(script.main_file_path.ends_with("/main.csx"));
+        assert_eq!(script.code, code);
+    }
+
+    #[test]
+    fn the_same_code_is_not_built_again() {
+        let probe = csharp("", &[]);
+        let dotnet = Path::new(&probe.csharp_work_dir).join("fake-dotnet");
+        fs::write(
+            &dotnet,
+            "#!/bin/sh\necho \"$1\" >> \"$(dirname \"$0\")/calls\"\nprintf ran",
+        )
+        .unwrap();
+        fs::set_permissions(&dotnet, fs::Permissions::from_mode(0o755)).unwrap();
+        let options = [
+            ("runner", neovim_lib::Value::from("dotnet")),
+            ("dotnet", dotnet.to_string_lossy().as_ref().into()),
+        ];
+
+        let mut interpreter = csharp("Console.WriteLine(1+1);", &options);
+        let project = interpreter.project_dir();
+        fs::create_dir_all(project.clone() + "/bin").unwrap();
+        fs::write(project.clone() + "/snippet.csproj", "").unwrap();
+        fs::write(interpreter.dll_path(), "").unwrap();
+        fs::write(project.clone() + "/built.hash", crate::cache::key(&interpreter.code)).unwrap();
+        let calls = || fs::read_to_string(dotnet.with_file_name("calls")).unwrap_or_default();
+
+        interpreter.build().unwrap();
+        assert_eq!(calls(), "");
+        assert_eq!(interpreter.execute().unwrap().stdout, "ran");
+
+        // another code is built
+        interpreter.code = String::from("Console.WriteLine(2);");
+        interpreter.build().unwrap();
+        assert_eq!(calls().lines().last(), Some("build"));
+    }
+
+    #[test]
+    fn errors_are_located() {
+        let interpreter = csharp(
+            "var a = 1;\nint b = c;",
+            &[("runner", "dotnet".into()), ("wrap_main", true.into())],
+        );
+        let report = "/tmp/project/Program.cs(4,9): error CS0103: The name 'c' does not exist";
+        let error = interpreter.located(
+            SniprunError::CompilationError(report.to_string()),
+            &CSHARP_COMPILATION_ERROR,
+            report,
+        );
+        assert_eq!(error.line(), Some(2));
+        let trace = "Unhandled exception. System.DivideByZeroException\n   at Program.Main() in /tmp/project/Program.cs:line 3";
+        let error = interpreter.located(
+            SniprunError::RuntimeError(trace.to_string()),
+            &CSHARP_STACK_FRAME,
+            trace,
+        );
+        assert_eq!(error.line(), Some(1));
+    }
+
+    #[test]
+    fn a_snippet_runs() {
+        if check_toolchain(&["dotnet"]).is_err() {
+            return;
+        }
+        let result = csharp("Console.WriteLine(1+1);", &[]).run().unwrap();
+        assert_eq!(result.stdout.trim(), "2");
+    }
+}
@@ -7,19 +7,159 @@ pub struct C_original {
     c_work_dir: String,
     bin_path: String,
     main_file_path: String,
+    ///gcc, or the compiler set in the options (eg: clang)
     compiler: String,
+    ///the headers included before the code, eg: ["stdio.h", "stdlib.h"]
+    includes: Vec<String>,
+    line_offsets: LineOffsets,
+}
+
+lazy_static! {
+    /// a complete program defines its main function
+    static ref MAIN_FUNCTION: Regex = Regex::new(r"(?m)^\s*(?:int|void)\s+main\s*\(").unwrap();
+    /// gcc and clang locate errors as '/path/to/main.c:LINE:COLUMN: error: message' (main.cpp
+    /// for C++)
+    static ref C_LOCATION: Regex = Regex::new(r"(main\.c(?:pp)?):(\d+):(\d+:)?").unwrap();
+}
+
+impl C_original {
+    /// the compiler the user chose (interpreter option `compiler`), gcc by default
+    fn compiler(data: &DataHolder) -> String {
+        data.interpreter_option(&Self::get_name(), "compiler")
+            .and_then(|c| c.as_str())
+            .filter(|c| !c.is_empty())
+            .unwrap_or("gcc")
+            .to_string()
+    }
+
+    /// the headers the user wants included (interpreter option `includes`), by default the
+    /// ones most snippets need
+    fn includes(data: &DataHolder) -> Vec<String> {
+        data.interpreter_option(&Self::get_name(), "includes")
+            .and_then(|i| i.as_array())
+            .map(|i| {
+                i.iter()
+                    .filter_map(|header| header.as_str().map(String::from))
+                    .collect()
+            })
+            .unwrap_or_else(|| {
+                vec![
+                    String::from("stdio.h"),
+                    String::from("stdlib.h"),
+                    String::from("string.h"),
+                ]
+            })
+    }
+
+    fn is_include(line: &str) -> bool {
+        line.trim_start().starts_with("#include")
+    }
+
+    /// the #include lines of a file (the edited one, at Import level)
+    fn file_includes(filepath: &str) -> Vec<String> {
+        std::fs::read_to_string(filepath)
+            .map(|content| {
+                content
+                    .lines()
+                    .filter(|l| Self::is_include(l))
+                    .map(|l| l.trim().to_string())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// the code in a main() function, after the preamble (includes...). The includes of the
+    /// code can't be inside main(): they are moved to the preamble, and blanked in place so
+    /// that the lines of the code don't move. The lines added are recorded in `line_offsets`.
+    /// Shared with C++
+    fn wrap_in_main(code: &str, mut preamble: Vec<String>, line_offsets: &mut LineOffsets) -> String {
+        preamble.extend(
+            code.lines()
+                .filter(|l| Self::is_include(l))
+                .map(|l| l.trim().to_string()),
+        );
+        preamble.dedup();
+        let body: Vec<&str> = code
+            .lines()
+            .map(|l| if Self::is_include(l) { "" } else { l })
+            .collect();
+        line_offsets.prepend(preamble.len() as i64 + 1);
+        preamble.join("\n") + "\nint main() {\n" + &body.join("\n") + "\nreturn 0;}\n"
+    }
+
+    /// the compilation error, with the lines of the code rather than the ones of the generated
+    /// file, located in the buffer where the first error is. The whole output is kept (C++
+    /// template errors are long, but the interesting part may be anywhere)
+    fn compilation_error(
+        stderr: &str,
+        data: &DataHolder,
+        line_offsets: &LineOffsets,
+    ) -> SniprunError {
+        let mut first_line = None;
+        let message = C_LOCATION.replace_all(stderr, |captures: &regex::Captures| {
+            let generated_line = captures[2].parse::<i64>().unwrap_or(0);
+            let code_line = line_offsets.code_line(generated_line);
+            if first_line.is_none() {
+                first_line = code_line.and_then(|line| data.buffer_line(line));
+            }
+            match code_line {
+                Some(line) => format!(
+                    "{}:{}:{}",
+                    &captures[1],
+                    line,
+                    captures.get(3).map_or("", |c| c.as_str())
+                ),
+                None => captures[0].to_string(),
+            }
+        });
+        let error = SniprunError::CompilationError(message.to_string());
+        match first_line {
+            Some(line) => error.at_line(line),
+            None => error,
+        }
+    }
+
+    /// run the compiler command (that already has its arguments), in the work directory
+    fn compile(
+        command: &mut Command,
+        work_dir: &str,
+        data: &DataHolder,
+        line_offsets: &LineOffsets,
+    ) -> Result<(), SniprunError> {
+        let output = command.current_dir(work_dir).output()?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(Self::compilation_error(
+                &String::from_utf8_lossy(&output.stderr),
+                data,
+                line_offsets,
+            ))
+        }
+    }
+
+    /// run the compiled binary
+    fn run_binary(bin_path: &str, data: &DataHolder) -> Result<ExecutionResult, SniprunError> {
+        let output = run_command(&mut Command::new(bin_path), data)?;
+        if output.status.success() {
+            Ok(ExecutionResult::from_output(&output))
+        } else {
+            Err(SniprunError::RuntimeError(
+                String::from_utf8_lossy(&output.stderr).to_string(),
+            ))
+        }
+    }
 }
 
 impl Interpreter for C_original {
+    declare_interpreter!(C_original);
+
     fn new_with_level(data: DataHolder, support_level: SupportLevel) -> Box<C_original> {
-        let rwd = data.work_dir.clone() + "/c_original";
-        let mut builder = DirBuilder::new();
-        builder.recursive(true);
-        builder
-            .create(&rwd)
-            .expect("Could not create directory for rust-original");
+        let rwd = work_dir_path(&data, &Self::get_work_dir_name());
         let mfp = rwd.clone() + "/main.c";
         let bp = String::from(&mfp[..mfp.len() - 2]);
+        let compiler = Self::compiler(&data);
+        let includes = Self::includes(&data);
         Box::new(C_original {
             data,
             support_level,
@@ -27,7 +167,9 @@ impl Interpreter for C_original {
             c_work_dir: rwd,
             bin_path: bp,
             main_file_path: mfp,
-            compiler: String::from("gcc"),
+            compiler,
+            includes,
+            line_offsets: LineOffsets::default(),
         })
     }
 
@@ -35,76 +177,62 @@ impl Interpreter for C_original {
         vec![String::from("c")]
     }
 
-    fn get_name() -> String {
-        String::from("C_original")
+    fn get_generated_file(&self) -> Option<String> {
+        Some(self.main_file_path.clone())
     }
 
-    fn get_current_level(&self) -> SupportLevel {
-        self.support_level
-    }
-    fn set_current_level(&mut self, level: SupportLevel) {
-        self.support_level = level;
+    fn get_max_support_level() -> SupportLevel {
+        SupportLevel::Import
     }
 
-    fn get_data(&self) -> DataHolder {
-        self.data.clone()
+    fn get_capabilities() -> Capabilities {
+        Capabilities::SEPARATE_BUILD | Capabilities::STREAMING
     }
 
-    fn get_max_support_level() -> SupportLevel {
-        SupportLevel::Bloc
+    fn get_line_offsets(&self) -> LineOffsets {
+        self.line_offsets.clone()
     }
 
     fn fetch_code(&mut self) -> Result<(), SniprunError> {
-        if !self
-            .data
-            .current_bloc
-            .replace(&[' ', '\t', '\n', '\r'][..], "")
-            .is_empty()
-        {
-            self.code = self.data.current_bloc.clone();
-        } else if !self.data.current_line.replace(" ", "").is_empty() {
-            self.code = self.data.current_line.clone();
-        } else {
-            self.code = String::from("");
-        }
+        self.code = selected_code(&self.data, self.get_current_level());
         Ok(())
     }
 
     fn add_boilerplate(&mut self) -> Result<(), SniprunError> {
-        self.code = String::from("#include <stdio.h>\nint main() {") + &self.code + "return 0;}";
+        self.line_offsets = LineOffsets::default();
+        // a complete program (eg: a whole file) is run as-is
+        if MAIN_FUNCTION.is_match(&self.code) {
+            return Ok(());
+        }
+
+        let mut includes: Vec<String> = self
+            .includes
+            .iter()
+            .map(|header| format!("#include <{}>", header))
+            .collect();
+        if self.support_level >= SupportLevel::Import {
+            includes.extend(Self::file_includes(&self.data.filepath));
+        }
+        self.code = Self::wrap_in_main(&self.code, includes, &mut self.line_offsets);
         Ok(())
     }
 
     fn build(&mut self) -> Result<(), SniprunError> {
         //write code to file
-        let mut _file =
-            File::create(&self.main_file_path).expect("Failed to create file for rust-original");
-        write(&self.main_file_path, &self.code).expect("Unable to write to file for rust-original");
-        let output = Command::new(&self.compiler)
-            .arg(&self.main_file_path)
-            .arg("-o")
-            .arg(&self.bin_path)
-            .output()
-            .expect("Unable to start process");
-
-        //TODO if relevant, return the error number (parse it from stderr)
-        if !output.status.success() {
-            return Err(SniprunError::CompilationError("".to_string()));
-        } else {
-            return Ok(());
-        }
+        write_code(&self.main_file_path, &self.code)?;
+        check_toolchain(&[&self.compiler])?;
+        Self::compile(
+            Command::new(&self.compiler)
+                .arg(&self.main_file_path)
+                .arg("-o")
+                .arg(&self.bin_path),
+            &self.c_work_dir,
+            &self.data,
+            &self.line_offsets,
+        )
     }
 
-    fn execute(&mut self) -> Result<String, SniprunError> {
-        let output = Command::new(&self.bin_path)
-            .output()
-            .expect("Unable to start process");
-        if output.status.success() {
-            return Ok(String::from_utf8(output.stdout).unwrap());
-        } else {
-            return Err(SniprunError::RuntimeError(
-                String::from_utf8(output.stderr).unwrap(),
-            ));
-        }
+    fn execute(&mut self) -> Result<ExecutionResult, SniprunError> {
+        Self::run_binary(&self.bin_path, &self.data)
     }
 }
@@ -0,0 +1,277 @@
+#[derive(Debug, Clone)]
+#[allow(non_camel_case_types)]
+pub struct Clojure_original {
+    support_level: SupportLevel,
+    data: DataHolder,
+    code: String,
+
+    ///specific to clojure
+    clojure_work_dir: String,
+    main_file_path: String,
+    ///"bb" (babashka, starts fast) or "clojure"
+    runner: String,
+    ///run the code in a clojure process kept between runs, rather than in a new JVM each time
+    persistent: bool,
+}
+
+/// Runs in the persistent clojure process: loads the files whose path it reads on stdin, one
+/// per line, with their output written next to them (path.out, path.err), then answers "ok" or
+/// "error" on stdout
+const CLOJURE_SERVER: &str = r##"
+(require 'clojure.main '[clojure.java.io :as io])
+(loop []
+  (when-let [path (read-line)]
+    (let [ok (with-open [out (io/writer (str path ".out"))
+                         err (io/writer (str path ".err"))]
+               (binding [*out* out *err* err]
+                 (try
+                   (let [value (load-file path)]
+                     (when-not (or (nil? value) (var? value)) (prn value)))
+                   true
+                   (catch Throwable e
+                     (.write err (clojure.main/ex-str (clojure.main/ex-triage (Throwable->map e))))
+                     false))))]
+      (println (if ok "ok" "error"))
+      (flush)
+      (recur))))
+"##;
+
+/// the pipes to the persistent clojure process, and the project it was started in
+struct ClojureProcess {
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+    project: Option<PathBuf>,
+}
+
+lazy_static! {
+    /// clojure locates errors as '(main.clj:LINE)' or '(main.clj:LINE:COLUMN)'
+    static ref CLOJURE_LOCATION: Regex = Regex::new(r"main\.clj:(\d+)").unwrap();
+    /// the pipes to the persistent process, locked for the duration of a run
+    static ref CLOJURE_PROCESS: Mutex<Option<ClojureProcess>> = Mutex::new(None);
+    /// the persistent process itself, only locked to start or kill it so that it can be killed
+    /// while it runs some code
+    static ref CLOJURE_CHILD: Mutex<Option<Child>> = Mutex::new(None);
+}
+
+impl Clojure_original {
+    /// the runner the user chose (interpreter option `runner`), else babashka if installed
+    fn select_runner(data: &DataHolder) -> String {
+        if let Some(runner) = data
+            .interpreter_option(&Self::get_name(), "runner")
+            .and_then(|r| r.as_str())
+        {
+            return runner.to_string();
+        }
+        if check_toolchain(&["bb"]).is_ok() {
+            String::from("bb")
+        } else {
+            String::from("clojure")
+        }
+    }
+
+    /// the directory of the closest deps.edn above the edited file: clojure runs there, so
+    /// that the project's dependencies can be required
+    fn project(&self) -> Option<PathBuf> {
+        find_ancestor(Path::new(&self.data.filepath), "deps.edn")
+    }
+
+    /// the expression that loads the code and prints the value of its last form, like a
+    /// REPL would (but not the var of a definition)
+    fn load_expression(&self) -> String {
+        format!(
+            "(let [value (load-file {:?})] (when-not (or (nil? value) (var? value)) (prn value)))",
+            self.main_file_path
+        )
+    }
+
+    /// the error clojure reported, without the stack trace: the reader and the compiler report
+    /// a 'Syntax error' (babashka an EOF or a symbol it cannot resolve), the code an
+    /// 'Execution error'
+    fn error(&self, stderr: &str) -> SniprunError {
+        let stderr = stderr
+            .split("----- Stack trace")
+            .next()
+            .unwrap_or(stderr)
+            .lines()
+            .filter(|l| !l.starts_with("Full report at:"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let stderr = stderr.trim_end();
+        let error = if stderr.contains("Syntax error")
+            || stderr.contains("EOF while reading")
+            || stderr.contains("Could not resolve symbol")
+        {
+            SniprunError::CompilationError(stderr.to_string())
+        } else {
+            SniprunError::RuntimeError(stderr.to_string())
+        };
+        let line = CLOJURE_LOCATION
+            .captures(stderr)
+            .and_then(|captures| captures[1].parse::<i64>().ok())
+            .and_then(|line| self.to_buffer_line(line));
+        match line {
+            Some(line) => error.at_line(line),
+            None => error,
+        }
+    }
+
+    /// start the persistent process, in the project of the code
+    fn start_process(&self, project: Option<PathBuf>) -> Result<ClojureProcess, SniprunError> {
+        let server_path = self.clojure_work_dir.clone() + "/server.clj";
+        write_code(&server_path, CLOJURE_SERVER)?;
+        info!("[CLOJURE] starting a persistent process (project: {:?})", project);
+        let mut child = Command::new("clojure")
+            .arg("-M")
+            .arg(&server_path)
+            .current_dir(project.as_deref().unwrap_or(Path::new(&self.clojure_work_dir)))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+        let process = match (child.stdin.take(), child.stdout.take()) {
+            (Some(stdin), Some(stdout)) => ClojureProcess {
+                stdin,
+                stdout: BufReader::new(stdout),
+                project,
+            },
+            _ => {
+                return Err(SniprunError::InternalError(String::from(
+                    "could not talk to the clojure process",
+                )))
+            }
+        };
+        *lock(&CLOJURE_CHILD) = Some(child);
+        Ok(process)
+    }
+
+    fn execute_persistent(&self) -> Result<ExecutionResult, SniprunError> {
+        let project = self.project();
+        let mut process = lock(&CLOJURE_PROCESS);
+        // a process started in another project doesn't see this one's dependencies
+        if process.as_ref().is_some_and(|p| p.project != project) {
+            *process = None;
+            Self::kill_process();
+        }
+        if process.is_none() {
+            *process = Some(self.start_process(project)?);
+        }
+        let clojure = process.as_mut().unwrap();
+
+        let mut status = String::new();
+        let answered = writeln!(clojure.stdin, "{}", self.main_file_path)
+            .and_then(|_| clojure.stdin.flush())
+            .and_then(|_| clojure.stdout.read_line(&mut status));
+        if !matches!(answered, Ok(n) if n > 0) {
+            // killed (by :SnipReset) or crashed: the next run starts a new one
+            *process = None;
+            return Err(SniprunError::RuntimeError(String::from(
+                "the clojure process stopped, its state is lost",
+            )));
+        }
+        let stdout = std::fs::read_to_string(self.main_file_path.clone() + ".out")?;
+        let stderr = std::fs::read_to_string(self.main_file_path.clone() + ".err")?;
+        if status.trim() == "ok" {
+            Ok(ExecutionResult {
+                stdout,
+                stderr,
+                ..ExecutionResult::default()
+            })
+        } else {
+            Err(self.error(&stderr))
+        }
+    }
+
+    /// kill the persistent process, if any
+    fn kill_process() {
+        if let Some(mut child) = lock(&CLOJURE_CHILD).take() {
+            info!("[CLOJURE] killing the persistent process");
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+impl Interpreter for Clojure_original {
+    declare_interpreter!(Clojure_original);
+
+    fn new_with_level(data: DataHolder, support_level: SupportLevel) -> Box<Clojure_original> {
+        let cwd = work_dir_path(&data, &Self::get_work_dir_name());
+        let mfp = cwd.clone() + "/main.clj";
+        let persistent = data
+            .interpreter_option(&Self::get_name(), "persistent")
+            .map(|p| config_bool(p, false))
+            .unwrap_or(false);
+        let runner = Self::select_runner(&data);
+        Box::new(Clojure_original {
+            data,
+            support_level,
+            code: String::from(""),
+            clojure_work_dir: cwd,
+            main_file_path: mfp,
+            runner,
+            persistent,
+        })
+    }
+
+    fn get_supported_languages() -> Vec<String> {
+        vec![String::from("clojure"), String::from("clj")]
+    }
+
+    fn get_generated_file(&self) -> Option<String> {
+        Some(self.main_file_path.clone())
+    }
+
+    fn get_max_support_level() -> SupportLevel {
+        SupportLevel::Bloc
+    }
+
+    fn get_capabilities() -> Capabilities {
+        Capabilities::STREAMING | Capabilities::REPL | Capabilities::CLEANUP
+    }
+
+    fn clean_repl_memory(_data: &DataHolder) -> Result<(), SniprunError> {
+        Self::kill_process();
+        Ok(())
+    }
+
+    fn cleanup(&self) {
+        Self::kill_process();
+    }
+
+    fn fetch_code(&mut self) -> Result<(), SniprunError> {
+        self.code = selected_code(&self.data, self.get_current_level());
+        Ok(())
+    }
+
+    fn add_boilerplate(&mut self) -> Result<(), SniprunError> {
+        Ok(())
+    }
+
+    fn build(&mut self) -> Result<(), SniprunError> {
+        write_code(&self.main_file_path, &self.code)
+    }
+
+    fn execute(&mut self) -> Result<ExecutionResult, SniprunError> {
+        check_toolchain(&[&self.runner])?;
+        if self.persistent && self.runner == "clojure" {
+            return self.execute_persistent();
+        }
+        let mut command = Command::new(&self.runner);
+        command.current_dir(&self.clojure_work_dir);
+        if self.runner == "clojure" {
+            command.arg("-M");
+            if let Some(project) = self.project() {
+                command.current_dir(project);
+            }
+        }
+        let output = run_command(
+            command.arg("-e").arg(self.load_expression()),
+            &self.data,
+        )?;
+        if output.status.success() {
+            Ok(ExecutionResult::from_output(&output))
+        } else {
+            Err(self.error(&String::from_utf8_lossy(&output.stderr)))
+        }
+    }
+}
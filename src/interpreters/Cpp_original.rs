@@ -0,0 +1,195 @@
+#[derive(Debug, Clone)]
+#[allow(non_camel_case_types)]
+pub struct Cpp_original {
+    support_level: SupportLevel,
+    data: DataHolder,
+    code: String,
+
+    ///specific to c++, compiled and run the same way as C (see C_original)
+    cpp_work_dir: String,
+    bin_path: String,
+    main_file_path: String,
+    ///g++, or the compiler set in the options (eg: clang++)
+    compiler: String,
+    ///the standard the code is compiled with, eg: "c++17"
+    standard: String,
+    ///flags given to the compiler after the code, eg: ["-lpthread"]
+    flags: Vec<String>,
+    ///the headers included before the code
+    includes: Vec<String>,
+    line_offsets: LineOffsets,
+}
+
+impl Cpp_original {
+    /// the string option `key` the user set, or the default
+    fn string_option(data: &DataHolder, key: &str, default: &str) -> String {
+        data.interpreter_option(&Self::get_name(), key)
+            .and_then(|v| v.as_str())
+            .filter(|v| !v.is_empty())
+            .unwrap_or(default)
+            .to_string()
+    }
+
+    /// the list option `key` the user set, or the default
+    fn list_option(data: &DataHolder, key: &str, default: &[&str]) -> Vec<String> {
+        data.interpreter_option(&Self::get_name(), key)
+            .and_then(|v| v.as_array())
+            .map(|v| {
+                v.iter()
+                    .filter_map(|item| item.as_str().map(String::from))
+                    .collect()
+            })
+            .unwrap_or_else(|| default.iter().map(|item| item.to_string()).collect())
+    }
+}
+
+impl Interpreter for Cpp_original {
+    declare_interpreter!(Cpp_original);
+
+    fn new_with_level(data: DataHolder, support_level: SupportLevel) -> Box<Cpp_original> {
+        let cwd = work_dir_path(&data, &Self::get_work_dir_name());
+        let mfp = cwd.clone() + "/main.cpp";
+        let bp = cwd.clone() + "/main";
+        let compiler = Self::string_option(&data, "compiler", "g++");
+        let standard = Self::string_option(&data, "std", "c++17");
+        let flags = Self::list_option(&data, "flags", &[]);
+        let includes = Self::list_option(
+            &data,
+            "includes",
+            &["iostream", "string", "vector", "map", "algorithm"],
+        );
+        Box::new(Cpp_original {
+            data,
+            support_level,
+            code: String::from(""),
+            cpp_work_dir: cwd,
+            bin_path: bp,
+            main_file_path: mfp,
+            compiler,
+            standard,
+            flags,
+            includes,
+            line_offsets: LineOffsets::default(),
+        })
+    }
+
+    fn get_supported_languages() -> Vec<String> {
+        vec![
+            String::from("cpp"),
+            String::from("cxx"),
+            String::from("c++"),
+            String::from("cplusplus"),
+        ]
+    }
+
+    fn get_generated_file(&self) -> Option<String> {
+        Some(self.main_file_path.clone())
+    }
+
+    fn get_max_support_level() -> SupportLevel {
+        SupportLevel::Import
+    }
+
+    fn get_capabilities() -> Capabilities {
+        Capabilities::SEPARATE_BUILD | Capabilities::STREAMING
+    }
+
+    fn get_line_offsets(&self) -> LineOffsets {
+        self.line_offsets.clone()
+    }
+
+    fn fetch_code(&mut self) -> Result<(), SniprunError> {
+        self.code = selected_code(&self.data, self.get_current_level());
+        Ok(())
+    }
+
+    fn add_boilerplate(&mut self) -> Result<(), SniprunError> {
+        self.line_offsets = LineOffsets::default();
+        // a complete program (eg: a whole file) is run as-is
+        if MAIN_FUNCTION.is_match(&self.code) {
+            return Ok(());
+        }
+        let mut preamble: Vec<String> = self
+            .includes
+            .iter()
+            .map(|header| format!("#include <{}>", header))
+            .collect();
+        if self.support_level >= SupportLevel::Import {
+            preamble.extend(C_original::file_includes(&self.data.filepath));
+        }
+        preamble.push(String::from("using namespace std;"));
+        self.code = C_original::wrap_in_main(&self.code, preamble, &mut self.line_offsets);
+        Ok(())
+    }
+
+    fn build(&mut self) -> Result<(), SniprunError> {
+        write_code(&self.main_file_path, &self.code)?;
+        check_toolchain(&[&self.compiler])?;
+        C_original::compile(
+            Command::new(&self.compiler)
+                .arg(format!("-std={}", self.standard))
+                .arg(&self.main_file_path)
+                .arg("-o")
+                .arg(&self.bin_path)
+                .args(&self.flags),
+            &self.cpp_work_dir,
+            &self.data,
+            &self.line_offsets,
+        )
+    }
+
+    fn execute(&mut self) -> Result<ExecutionResult, SniprunError> {
+        C_original::run_binary(&self.bin_path, &self.data)
+    }
+}
+
+#[cfg(test)]
+mod test_cpp_original {
+    use super::*;
+
+    fn cpp(code: &str, standard: Option<&str>) -> Box<Cpp_original> {
+        let options: Vec<_> = standard
+            .map(|standard| ("std", neovim_lib::Value::from(standard)))
+            .into_iter()
+            .collect();
+        DataHolder::prepared("cpp", code, &options)
+    }
+
+    #[test]
+    fn bare_statements_run_in_main() {
+        if check_toolchain(&["g++"]).is_err() {
+            return;
+        }
+        assert_eq!(cpp("std::cout << 2+2;", None).run().unwrap().stdout, "4");
+    }
+
+    #[test]
+    fn a_program_runs_as_is() {
+        if check_toolchain(&["g++"]).is_err() {
+            return;
+        }
+        let code = "#include <cstdio>\nint main() {\n    printf(\"%d\", 6 * 7);\n    return 0;\n}";
+        assert_eq!(cpp(code, None).run().unwrap().stdout, "42");
+    }
+
+    #[test]
+    fn the_standard_can_be_chosen() {
+        if check_toolchain(&["g++"]).is_err() {
+            return;
+        }
+        // designated initializers are C++20
+        let code = "struct P { int x; int y; };\nP p{.x = 1, .y = 2};\ncout << p.y;";
+        assert_eq!(cpp(code, Some("c++20")).run().unwrap().stdout, "2");
+        assert!(cpp("cout << 1;", Some("c++nonexistent")).run().is_err());
+    }
+
+    #[test]
+    fn errors_are_located_in_the_code() {
+        if check_toolchain(&["g++"]).is_err() {
+            return;
+        }
+        let error = cpp("int a = 1;\ncout << b;", None).run().unwrap_err();
+        assert!(matches!(error.inner(), SniprunError::CompilationError(_)));
+        assert_eq!(error.line(), Some(2));
+    }
+}
@@ -0,0 +1,273 @@
+#[derive(Debug, Clone)]
+#[allow(non_camel_case_types)]
+pub struct D_original {
+    support_level: SupportLevel,
+    data: DataHolder,
+    code: String,
+
+    ///specific to d
+    d_work_dir: String,
+    bin_path: String,
+    main_file_path: String,
+    ///"dmd", "ldc2" or "gdc" (or the path to one of them)
+    compiler: String,
+    ///compile and run in one step with rdmd (only with dmd, when it is installed)
+    rdmd: bool,
+    line_offsets: LineOffsets,
+}
+
+lazy_static! {
+    /// the code defines its own entry point
+    static ref D_MAIN: Regex = Regex::new(r"\b(void|int)\s+main\s*\(").unwrap();
+    /// dmd and ldc2 locate errors as 'main.d(LINE): Error: message' (or
+    /// 'main.d(LINE,COLUMN)'), gdc as 'main.d:LINE:COLUMN: error: message', exceptions are
+    /// like 'object.Exception@main.d(LINE): message'
+    static ref D_LOCATION: Regex = Regex::new(r"main\.d([(:])(\d+)").unwrap();
+    static ref D_COMPILATION_ERROR: Regex =
+        Regex::new(r"main\.d(\(\d+(,\d+)?\): Error: |:\d+:\d+: error: )").unwrap();
+    /// the code writes to stdout with std.stdio
+    static ref D_WRITE: Regex = Regex::new(r"\b(writeln|writefln|writef|write)\s*\(").unwrap();
+}
+
+impl D_original {
+    fn is_import(line: &str) -> bool {
+        line.trim_start().starts_with("import ")
+    }
+
+    /// the error, with the lines of the code rather than the ones of the generated file,
+    /// located in the buffer where the first error is
+    fn error(&self, stderr: &str) -> SniprunError {
+        let mut first_line = None;
+        let message = D_LOCATION.replace_all(stderr, |captures: &regex::Captures| {
+            let code_line = captures[2]
+                .parse::<i64>()
+                .ok()
+                .and_then(|line| self.line_offsets.code_line(line));
+            if first_line.is_none() {
+                first_line = code_line.and_then(|line| self.data.buffer_line(line));
+            }
+            match code_line {
+                Some(line) => format!("main.d{}{}", &captures[1], line),
+                None => captures[0].to_string(),
+            }
+        });
+        let error = if D_COMPILATION_ERROR.is_match(stderr) {
+            SniprunError::CompilationError(message.to_string())
+        } else {
+            SniprunError::RuntimeError(message.to_string())
+        };
+        match first_line {
+            Some(line) => error.at_line(line),
+            None => error,
+        }
+    }
+}
+
+impl Interpreter for D_original {
+    declare_interpreter!(D_original);
+
+    fn new_with_level(data: DataHolder, support_level: SupportLevel) -> Box<D_original> {
+        let dwd = work_dir_path(&data, &Self::get_work_dir_name());
+        let mfp = Self::temp_source_path(&data);
+        let compiler = data
+            .interpreter_option(&Self::get_name(), "compiler")
+            .and_then(|c| c.as_str())
+            .unwrap_or("dmd")
+            .to_string();
+        let rdmd = compiler == "dmd" && check_toolchain(&["rdmd"]).is_ok();
+        Box::new(D_original {
+            data,
+            support_level,
+            code: String::from(""),
+            bin_path: dwd.clone() + "/main",
+            d_work_dir: dwd,
+            main_file_path: mfp,
+            compiler,
+            rdmd,
+            line_offsets: LineOffsets::default(),
+        })
+    }
+
+    fn get_supported_languages() -> Vec<String> {
+        vec![String::from("d"), String::from("dlang")]
+    }
+
+    fn get_file_extension() -> String {
+        String::from("d")
+    }
+
+    fn get_generated_file(&self) -> Option<String> {
+        Some(self.main_file_path.clone())
+    }
+
+    fn get_max_support_level() -> SupportLevel {
+        SupportLevel::Bloc
+    }
+
+    fn get_capabilities() -> Capabilities {
+        Capabilities::SEPARATE_BUILD | Capabilities::STREAMING
+    }
+
+    fn get_line_offsets(&self) -> LineOffsets {
+        self.line_offsets.clone()
+    }
+
+    fn fetch_code(&mut self) -> Result<(), SniprunError> {
+        self.code = selected_code(&self.data, self.get_current_level());
+        Ok(())
+    }
+
+    fn add_boilerplate(&mut self) -> Result<(), SniprunError> {
+        self.line_offsets = LineOffsets::default();
+        let stdio = if D_WRITE.is_match(&self.code) && !self.code.contains("std.stdio") {
+            "import std.stdio;"
+        } else {
+            ""
+        };
+        if D_MAIN.is_match(&self.code) {
+            if !stdio.is_empty() {
+                self.code = format!("{}\n{}", stdio, self.code);
+                self.line_offsets.prepend(1);
+            }
+            return Ok(());
+        }
+        // the imports are moved before main(), on a single line, and blanked in place so that
+        // the lines of the code don't move
+        let mut imports: Vec<&str> = self
+            .code
+            .lines()
+            .filter(|l| Self::is_import(l))
+            .map(|l| l.trim())
+            .collect();
+        if !stdio.is_empty() {
+            imports.push(stdio);
+        }
+        let body: Vec<&str> = self
+            .code
+            .lines()
+            .map(|l| if Self::is_import(l) { "" } else { l })
+            .collect();
+        self.code = imports.join(" ") + "\nvoid main() {\n" + &body.join("\n") + "\n}\n";
+        self.line_offsets.prepend(2);
+        Ok(())
+    }
+
+    fn build(&mut self) -> Result<(), SniprunError> {
+        write_code(&self.main_file_path, &self.code)?;
+        if self.rdmd {
+            // rdmd compiles when it runs, and keeps the binaries of the code it already compiled
+            return Ok(());
+        }
+
+        check_toolchain(&[&self.compiler])?;
+        let mut command = Command::new(&self.compiler);
+        // gdc takes gcc's options, whatever its path or version (eg: gdc-12)
+        let gdc = Path::new(&self.compiler)
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().starts_with("gdc"));
+        if gdc {
+            command.arg("-o").arg(&self.bin_path);
+        } else {
+            command.arg(format!("-of={}", self.bin_path));
+        }
+        let output = command
+            .arg(&self.main_file_path)
+            .current_dir(&self.d_work_dir)
+            .output()?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(self.error(&String::from_utf8_lossy(&output.stderr)))
+        }
+    }
+
+    fn execute(&mut self) -> Result<ExecutionResult, SniprunError> {
+        let mut command = if self.rdmd {
+            let mut command = Command::new("rdmd");
+            command.arg(&self.main_file_path);
+            command
+        } else {
+            Command::new(&self.bin_path)
+        };
+        let output = run_command(command.current_dir(&self.d_work_dir), &self.data)?;
+        if output.status.success() {
+            Ok(ExecutionResult::from_output(&output))
+        } else {
+            Err(self.error(&String::from_utf8_lossy(&output.stderr)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_d_original {
+    use super::*;
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+
+    fn d(code: &str, compiler: Option<&str>) -> Box<D_original> {
+        let options: Vec<_> = compiler
+            .map(|compiler| ("compiler", neovim_lib::Value::from(compiler)))
+            .into_iter()
+            .collect();
+        DataHolder::prepared("dlang", code, &options)
+    }
+
+    #[test]
+    fn bare_statements_are_wrapped_in_main() {
+        let interpreter = d("int a = 1;\nimport std.conv;\nwriteln(a.to!string);", None);
+        assert_eq!(
+            interpreter.code,
+            "import std.conv; import std.stdio;\nvoid main() {\nint a = 1;\n\nwriteln(a.to!string);\n}\n"
+        );
+        assert_eq!(interpreter.to_buffer_line(5), Some(3));
+
+        let program = "void main() {\n    writeln(1);\n}";
+        assert_eq!(d(program, None).code, format!("import std.stdio;\n{}", program));
+    }
+
+    #[test]
+    fn errors_point_at_the_code() {
+        let interpreter = d("int a = 1;\nstring b = a;", None);
+        for stderr in [
+            "/tmp/main.d(4): Error: cannot implicitly convert expression `a` of type `int` to `string`",
+            "/tmp/main.d:4:12: error: cannot implicitly convert expression 'a' of type 'int' to 'string'",
+        ] {
+            let error = interpreter.error(stderr);
+            assert!(matches!(error.inner(), SniprunError::CompilationError(_)));
+            assert_eq!(error.line(), Some(2));
+            assert!(error.inner().to_string().contains("main.d"));
+        }
+        let error = interpreter.error("object.Exception@/tmp/main.d(3): boom");
+        assert!(matches!(error.inner(), SniprunError::RuntimeError(_)));
+        assert_eq!(error.line(), Some(1));
+    }
+
+    #[test]
+    fn gdc_can_be_chosen() {
+        // a stand-in for gdc, that tells how it was called and 'compiles' a binary
+        let probe = d("", None);
+        let gdc = Path::new(&probe.d_work_dir).join("gdc");
+        fs::write(
+            &gdc,
+            "#!/bin/sh\necho \"$@\" > \"$(dirname \"$0\")/args\"\nprintf '#!/bin/sh\\necho 2' > \"$2\"\nchmod +x \"$2\"",
+        )
+        .unwrap();
+        fs::set_permissions(&gdc, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let mut interpreter = d("writeln(1+1);", Some(&gdc.to_string_lossy()));
+        assert!(!interpreter.rdmd);
+        assert_eq!(interpreter.run().unwrap().stdout, "2\n");
+        let args = fs::read_to_string(gdc.with_file_name("args")).unwrap();
+        assert!(args.starts_with(&format!("-o {} ", interpreter.bin_path)));
+    }
+
+    #[test]
+    fn a_snippet_runs() {
+        let compiler = ["dmd", "ldc2", "gdc"]
+            .iter()
+            .find(|compiler| check_toolchain(&[compiler]).is_ok());
+        if let Some(compiler) = compiler {
+            assert_eq!(d("writeln(1+1);", Some(compiler)).run().unwrap().stdout, "2\n");
+        }
+    }
+}
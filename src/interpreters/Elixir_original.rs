@@ -0,0 +1,179 @@
+#[derive(Debug, Clone)]
+#[allow(non_camel_case_types)]
+pub struct Elixir_original {
+    support_level: SupportLevel,
+    data: DataHolder,
+    code: String,
+
+    ///specific to elixir
+    elixir_work_dir: String,
+    main_file_path: String,
+}
+
+lazy_static! {
+    /// elixir locates errors as 'main.exs:LINE' (followed by ':COLUMN' for syntax errors)
+    static ref ELIXIR_LOCATION: Regex = Regex::new(r"main\.exs:(\d+)").unwrap();
+    /// the exceptions raised when the code doesn't compile, rather than when it runs
+    static ref ELIXIR_COMPILATION_ERROR: Regex = Regex::new(
+        r"\(CompileError\)|\(SyntaxError\)|\(TokenMissingError\)|\(MismatchedDelimiterError\)|(?m)^error: "
+    )
+    .unwrap();
+}
+
+impl Elixir_original {
+    /// whether a line of code is an expression whose value IEx would print (but elixir does not)
+    fn is_expression(line: &str) -> bool {
+        let keywords = [
+            "def", "end", "import ", "alias ", "require ", "use ", "IO.", "#", "@", "case ", "if ",
+            "for ", "with ", "cond ", "try ", "|>",
+        ];
+        let trimmed = line.trim_end();
+        if line.starts_with(char::is_whitespace)
+            || trimmed.is_empty()
+            || keywords.iter().any(|keyword| trimmed.starts_with(keyword))
+            || trimmed.ends_with(" do")
+            || trimmed.ends_with("->")
+            || trimmed.ends_with(',')
+        {
+            return false;
+        }
+        // a match (binding) has a '=' that is not part of an operator
+        !["==", "!=", "<=", ">=", "=>", "=~"]
+            .iter()
+            .fold(trimmed.to_string(), |line, operator| line.replace(operator, ""))
+            .contains('=')
+    }
+
+    /// the error of a failed run, located in the buffer where elixir says it happened
+    fn error(&self, stderr: &str) -> SniprunError {
+        let error = if ELIXIR_COMPILATION_ERROR.is_match(stderr) {
+            SniprunError::CompilationError(stderr.to_string())
+        } else {
+            SniprunError::RuntimeError(stderr.to_string())
+        };
+        let line = ELIXIR_LOCATION
+            .captures(stderr)
+            .and_then(|captures| captures[1].parse::<i64>().ok())
+            .and_then(|line| self.to_buffer_line(line));
+        match line {
+            Some(line) => error.at_line(line),
+            None => error,
+        }
+    }
+}
+
+impl Interpreter for Elixir_original {
+    declare_interpreter!(Elixir_original);
+
+    fn new_with_level(data: DataHolder, support_level: SupportLevel) -> Box<Elixir_original> {
+        let ewd = work_dir_path(&data, &Self::get_work_dir_name());
+        let mfp = ewd.clone() + "/main.exs";
+        Box::new(Elixir_original {
+            data,
+            support_level,
+            code: String::from(""),
+            elixir_work_dir: ewd,
+            main_file_path: mfp,
+        })
+    }
+
+    fn get_supported_languages() -> Vec<String> {
+        vec![String::from("elixir"), String::from("exs")]
+    }
+
+    fn get_generated_file(&self) -> Option<String> {
+        Some(self.main_file_path.clone())
+    }
+
+    fn get_max_support_level() -> SupportLevel {
+        SupportLevel::Bloc
+    }
+
+    fn get_capabilities() -> Capabilities {
+        Capabilities::STREAMING
+    }
+
+    fn fetch_code(&mut self) -> Result<(), SniprunError> {
+        self.code = selected_code(&self.data, self.get_current_level());
+        Ok(())
+    }
+
+    fn add_boilerplate(&mut self) -> Result<(), SniprunError> {
+        if !self.data.print_last_expression {
+            return Ok(());
+        }
+        // the last line is inspected in place, so that the lines of the code don't move
+        let mut lines: Vec<String> = self.code.lines().map(String::from).collect();
+        if let Some(last) = lines.iter().rposition(|l| !l.trim().is_empty()) {
+            if Self::is_expression(&lines[last]) {
+                lines[last] = format!("IO.inspect({})", lines[last].trim());
+            }
+        }
+        self.code = lines.join("\n") + "\n";
+        Ok(())
+    }
+
+    fn build(&mut self) -> Result<(), SniprunError> {
+        write_code(&self.main_file_path, &self.code)
+    }
+
+    fn execute(&mut self) -> Result<ExecutionResult, SniprunError> {
+        check_toolchain(&["elixir"])?;
+        let output = run_command(
+            Command::new("elixir")
+                .arg(&self.main_file_path)
+                .current_dir(&self.elixir_work_dir),
+            &self.data,
+        )?;
+        if output.status.success() {
+            Ok(ExecutionResult::from_output(&output))
+        } else {
+            Err(self.error(&String::from_utf8_lossy(&output.stderr)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_elixir_original {
+    use super::*;
+
+    fn elixir(code: &str, print_last_expression: bool) -> Box<Elixir_original> {
+        let mut data = DataHolder::with_code_in_temp_dir("elixir", code);
+        data.print_last_expression = print_last_expression;
+        data.into_prepared(&[])
+    }
+
+    #[test]
+    fn the_last_expression_is_inspected_on_demand() {
+        assert_eq!(elixir("x = 1\nx + 1", true).code, "x = 1\nIO.inspect(x + 1)\n");
+        assert_eq!(elixir("x = 1\nx + 1", false).code, "x = 1\nx + 1");
+        assert_eq!(elixir("x == 1", true).code, "IO.inspect(x == 1)\n");
+        for statement in ["x = 1", "IO.puts(1)", "def f do", "end", "a\n  |> f()"] {
+            assert_eq!(elixir(statement, true).code, statement.to_string() + "\n");
+        }
+    }
+
+    #[test]
+    fn compilation_errors_are_told_apart() {
+        let interpreter = elixir("x = 1\nx +", false);
+        let error = interpreter.error(
+            "** (TokenMissingError) token missing on main.exs:2:4:\n    error: syntax error",
+        );
+        assert!(matches!(error.inner(), SniprunError::CompilationError(_)));
+        assert_eq!(error.line(), Some(2));
+
+        let error = interpreter.error(
+            "** (ArithmeticError) bad argument in arithmetic expression\n    main.exs:1: (file)",
+        );
+        assert!(matches!(error.inner(), SniprunError::RuntimeError(_)));
+        assert_eq!(error.line(), Some(1));
+    }
+
+    #[test]
+    fn a_snippet_runs() {
+        if check_toolchain(&["elixir"]).is_err() {
+            return;
+        }
+        assert_eq!(elixir("IO.puts(1+1)", false).run().unwrap().stdout, "2\n");
+    }
+}
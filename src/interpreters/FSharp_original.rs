@@ -0,0 +1,332 @@
+#[derive(Debug, Clone)]
+#[allow(non_camel_case_types)]
+pub struct FSharp_original {
+    support_level: SupportLevel,
+    data: DataHolder,
+    code: String,
+
+    ///specific to f#
+    fsharp_work_dir: String,
+    main_file_path: String,
+    ///the .fsproj the whole project is run with, at File level (interpreter option `project`)
+    project: Option<PathBuf>,
+    ///fsi shows the values the code binds, like its REPL, rather than running it as a script
+    ///(interpreter option `echo`)
+    echo: bool,
+}
+
+lazy_static! {
+    /// the compiler locates errors as 'main.fsx(LINE,COLUMN): error FS0001: message', or
+    /// '/path/to/File.fs(LINE,COLUMN): error FS0001: message' in a project
+    static ref FSHARP_COMPILATION_ERROR: Regex =
+        Regex::new(r"(?m)^\s*(\S*?\.fsx?)\((\d+),\d+\): error FS\d+").unwrap();
+    /// the frames of an unhandled exception are like 'at <StartupCode$FSI_0001>.$Main.main@()
+    /// in /path/to/main.fsx:line LINE'
+    static ref FSHARP_STACK_FRAME: Regex = Regex::new(r"(\S*?\.fsx?):line (\d+)").unwrap();
+}
+
+impl FSharp_original {
+    /// whether a line of code is an expression whose value is worth printing
+    fn is_expression(line: &str) -> bool {
+        let keywords = [
+            "let", "open", "type", "module", "namespace", "do", "if", "elif", "else", "for",
+            "while", "match", "try", "with", "use", "exception", "and", "printf", "printfn",
+            "eprintf", "eprintfn", "ignore", "assert",
+        ];
+        let trimmed = line.trim_end();
+        let first_word = trimmed
+            .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .next()
+            .unwrap_or("");
+        !(line.starts_with(char::is_whitespace)
+            || trimmed.is_empty()
+            || ["//", "#", "(*", "[<", "|", ")", "]", "}"]
+                .iter()
+                .any(|start| trimmed.starts_with(start))
+            || ["=", "->", "(", "[", "{"]
+                .iter()
+                .any(|end| trimmed.ends_with(end))
+            || trimmed.contains("<-")
+            || trimmed.contains("|> ignore")
+            || keywords.contains(&first_word))
+    }
+
+    /// the project the code runs in, if the user asked for one (interpreter option `project`)
+    /// and there is one
+    fn project(data: &DataHolder) -> Option<PathBuf> {
+        data.interpreter_option(&Self::get_name(), "project")
+            .map(|p| config_bool(p, false))
+            .unwrap_or(false)
+            .then(|| Self::find_project(&data.filepath))
+            .flatten()
+    }
+
+    /// the first .fsproj found in the directory of the edited file or above it
+    fn find_project(filepath: &str) -> Option<PathBuf> {
+        let start = Path::new(filepath).parent()?;
+        start.ancestors().find_map(|dir| {
+            std::fs::read_dir(dir)
+                .ok()?
+                .filter_map(|entry| entry.ok().map(|e| e.path()))
+                .find(|path| path.extension().is_some_and(|e| e == "fsproj"))
+        })
+    }
+
+    /// the dotnet command, quiet and without telemetry
+    fn dotnet_command() -> Command {
+        let mut command = Command::new("dotnet");
+        command
+            .env("DOTNET_CLI_TELEMETRY_OPTOUT", "1")
+            .env("DOTNET_NOLOGO", "1");
+        command
+    }
+
+    /// the error, located where the regex first captures a line of the code: of the snippet
+    /// (main.fsx), or of the edited file when the whole project runs
+    fn located(&self, error: SniprunError, regex: &Regex, output: &str) -> SniprunError {
+        let line = regex.captures_iter(output).find_map(|captures| {
+            let line = captures[2].parse::<i64>().ok()?;
+            if self.project.is_some() {
+                (Path::new(&captures[1]) == Path::new(&self.data.filepath)).then_some(line)
+            } else if captures[1].ends_with("main.fsx") {
+                self.to_buffer_line(line)
+            } else {
+                None
+            }
+        });
+        match line {
+            Some(line) => error.at_line(line),
+            None => error,
+        }
+    }
+}
+
+impl Interpreter for FSharp_original {
+    declare_interpreter!(FSharp_original);
+
+    fn new_with_level(data: DataHolder, support_level: SupportLevel) -> Box<FSharp_original> {
+        let fwd = work_dir_path(&data, &Self::get_work_dir_name());
+        let mfp = Self::temp_source_path(&data);
+        let project = Self::project(&data);
+        let echo = data
+            .interpreter_option(&Self::get_name(), "echo")
+            .map(|e| config_bool(e, true))
+            .unwrap_or(true);
+        Box::new(FSharp_original {
+            data,
+            support_level,
+            code: String::from(""),
+            fsharp_work_dir: fwd,
+            main_file_path: mfp,
+            project,
+            echo,
+        })
+    }
+
+    fn negotiate_level(data: &DataHolder, level: SupportLevel) -> SupportLevel {
+        // without a project to run, only the selected code can
+        if Self::project(data).is_none() && level > SupportLevel::Bloc {
+            SupportLevel::Bloc
+        } else {
+            level
+        }
+    }
+
+    fn get_supported_languages() -> Vec<String> {
+        vec![
+            String::from("fsharp"),
+            String::from("fs"),
+            String::from("fsx"),
+        ]
+    }
+
+    fn get_file_extension() -> String {
+        String::from("fsx")
+    }
+
+    fn get_generated_file(&self) -> Option<String> {
+        Some(self.main_file_path.clone())
+    }
+
+    fn get_max_support_level() -> SupportLevel {
+        SupportLevel::File
+    }
+
+    fn get_capabilities() -> Capabilities {
+        Capabilities::STREAMING | Capabilities::PROJECT_FILES
+    }
+
+    fn fetch_code(&mut self) -> Result<(), SniprunError> {
+        if self.get_current_level() < SupportLevel::File {
+            // the selection doesn't run in the project
+            self.project = None;
+        }
+        self.code = selected_code(&self.data, self.get_current_level());
+        Ok(())
+    }
+
+    fn add_boilerplate(&mut self) -> Result<(), SniprunError> {
+        // fsi runs a script as is, '#r "nuget: ..."' directives included; when it doesn't echo
+        // the values, the last line is printed in place, so that the lines of the code don't
+        // move
+        if self.echo || self.project.is_some() || !self.data.print_last_expression {
+            return Ok(());
+        }
+        let mut lines: Vec<String> = self.code.lines().map(String::from).collect();
+        if let Some(last) = lines.iter().rposition(|l| !l.trim().is_empty()) {
+            if Self::is_expression(&lines[last]) {
+                lines[last] = format!("printfn \"%A\" ({})", lines[last].trim());
+            }
+        }
+        self.code = lines.join("\n") + "\n";
+        Ok(())
+    }
+
+    fn build(&mut self) -> Result<(), SniprunError> {
+        write_code(&self.main_file_path, &self.code)
+    }
+
+    fn execute(&mut self) -> Result<ExecutionResult, SniprunError> {
+        check_toolchain(&["dotnet"])?;
+        let mut command = Self::dotnet_command();
+        match &self.project {
+            Some(project) => {
+                info!("[FSHARP] running the project {:?}", project);
+                command
+                    .arg("run")
+                    .arg("--project")
+                    .arg(project)
+                    .current_dir(project.parent().unwrap_or_else(|| Path::new(".")));
+            }
+            // with --use, fsi shows the values of the bindings and expressions, like its REPL
+            None if self.echo => {
+                command
+                    .arg("fsi")
+                    .arg("--nologo")
+                    .arg(format!("--use:{}", self.main_file_path))
+                    .arg("--exec")
+                    .current_dir(&self.fsharp_work_dir);
+            }
+            None => {
+                command
+                    .arg("fsi")
+                    .arg("--nologo")
+                    .arg(&self.main_file_path)
+                    .current_dir(&self.fsharp_work_dir);
+            }
+        }
+        let output = run_command(&mut command, &self.data)?;
+        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        // the build of a project reports its errors on stdout
+        let report = stderr.clone() + &stdout;
+        if FSHARP_COMPILATION_ERROR.is_match(&report) {
+            let errors: Vec<&str> = report
+                .lines()
+                .filter(|l| FSHARP_COMPILATION_ERROR.is_match(l))
+                .map(|l| l.split(" [").next().unwrap_or(l).trim())
+                .collect();
+            let errors = errors.join("\n");
+            return Err(self.located(
+                SniprunError::CompilationError(errors.clone()),
+                &FSHARP_COMPILATION_ERROR,
+                &errors,
+            ));
+        }
+        if output.status.success() && !stderr.contains("Stopped due to error") {
+            Ok(ExecutionResult::from_output(&output))
+        } else {
+            Err(self.located(
+                SniprunError::RuntimeError(stderr.clone()),
+                &FSHARP_STACK_FRAME,
+                &stderr,
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_fsharp_original {
+    use super::*;
+
+    fn fsharp(code: &str, options: &[(&str, bool)]) -> Box<FSharp_original> {
+        let options: Vec<_> = options
+            .iter()
+            .map(|&(key, value)| (key, neovim_lib::Value::from(value)))
+            .collect();
+        let mut data = DataHolder::with_code_in_temp_dir("fsharp", code);
+        data.print_last_expression = true;
+        data.into_prepared(&options)
+    }
+
+    #[test]
+    fn the_last_expression_is_printed_without_echo() {
+        let code = "let a = 1\na + 1";
+        let interpreter = fsharp(code, &[("echo", false)]);
+        assert_eq!(interpreter.code, "let a = 1\nprintfn \"%A\" (a + 1)\n");
+        // fsi shows it already
+        assert_eq!(fsharp(code, &[]).code, code);
+        for statement in ["let a = 1", "printfn \"%d\" 1", "a |> ignore", "x <- 2"] {
+            let interpreter = fsharp(statement, &[("echo", false)]);
+            assert_eq!(interpreter.code, statement.to_string() + "\n");
+        }
+    }
+
+    #[test]
+    fn the_whole_project_runs_only_when_there_is_one() {
+        let mut data = fsharp("printfn \"hi\"", &[("project", true)]).data;
+        let project_dir = Path::new(&data.work_dir).join("app");
+        std::fs::create_dir_all(project_dir.join("src")).unwrap();
+        std::fs::write(project_dir.join("app.fsproj"), "").unwrap();
+        data.filepath = project_dir.join("src/Program.fs").to_string_lossy().to_string();
+        assert_eq!(
+            FSharp_original::project(&data),
+            Some(project_dir.join("app.fsproj"))
+        );
+        assert_eq!(
+            FSharp_original::negotiate_level(&data, SupportLevel::File),
+            SupportLevel::File
+        );
+
+        data.filepath = Path::new(&data.work_dir)
+            .join("Program.fs")
+            .to_string_lossy()
+            .to_string();
+        assert_eq!(FSharp_original::project(&data), None);
+        assert_eq!(
+            FSharp_original::negotiate_level(&data, SupportLevel::File),
+            SupportLevel::Bloc
+        );
+    }
+
+    #[test]
+    fn errors_are_located() {
+        let interpreter = fsharp("let a = 1\nlet b: string = a", &[]);
+        let report = "/tmp/fsharp_original/main.fsx(2,17): error FS0001: This expression was expected to have type 'string'";
+        let error = interpreter.located(
+            SniprunError::CompilationError(report.to_string()),
+            &FSHARP_COMPILATION_ERROR,
+            report,
+        );
+        assert_eq!(error.line(), Some(2));
+        let trace = "System.DivideByZeroException\n   at <StartupCode$FSI_0001>.$Main.main@() in /tmp/main.fsx:line 1";
+        let error = interpreter.located(
+            SniprunError::RuntimeError(trace.to_string()),
+            &FSHARP_STACK_FRAME,
+            trace,
+        );
+        assert_eq!(error.line(), Some(1));
+    }
+
+    #[test]
+    fn dotnet_is_required() {
+        let mut interpreter = fsharp("printfn \"%d\" (1+1)", &[]);
+        match check_toolchain(&["dotnet"]) {
+            Ok(()) => assert_eq!(interpreter.run().unwrap().stdout, "2\n"),
+            Err(_) => assert!(matches!(
+                interpreter.run(),
+                Err(SniprunError::PrerequisiteError(message)) if message.contains("dotnet")
+            )),
+        }
+    }
+}
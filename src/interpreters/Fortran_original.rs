@@ -0,0 +1,199 @@
+#[derive(Debug, Clone)]
+#[allow(non_camel_case_types)]
+pub struct Fortran_original {
+    support_level: SupportLevel,
+    data: DataHolder,
+    code: String,
+
+    ///specific to fortran
+    fortran_work_dir: String,
+    main_file_path: String,
+    ///the binary of the code, named by a hash of the code: the same code is only compiled once
+    bin_path: String,
+    ///"free" (modern fortran) or "fixed" (fortran 77 columns)
+    form: String,
+    line_offsets: LineOffsets,
+}
+
+/// how many compiled binaries are kept, the oldest ones are removed first
+const KEPT_FORTRAN_BINARIES: usize = 20;
+
+lazy_static! {
+    /// the code is a whole program already
+    static ref FORTRAN_PROGRAM: Regex = Regex::new(r"(?im)^\s*program\s+\w+").unwrap();
+    /// gfortran locates compilation errors as 'main.f90:LINE:COLUMN:', runtime errors as
+    /// 'At line LINE of file main.f90'
+    static ref FORTRAN_LOCATION: Regex =
+        Regex::new(r"(main\.f90:)(\d+)|(At line )(\d+)( of file \S*main\.f90)").unwrap();
+}
+
+impl Fortran_original {
+    /// the source form the user chose (interpreter option `form`), else the one of the
+    /// extension of the file: .f, .for and .f77 files are fixed-form
+    fn select_form(data: &DataHolder) -> String {
+        if let Some(form) = data
+            .interpreter_option(&Self::get_name(), "form")
+            .and_then(|f| f.as_str())
+        {
+            return form.to_string();
+        }
+        let extension = Path::new(&data.filepath)
+            .extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        if ["f", "for", "f77", "ftn"].contains(&extension.as_str()) {
+            String::from("fixed")
+        } else {
+            String::from("free")
+        }
+    }
+
+    /// the error, with the lines of the code rather than the ones of the generated file,
+    /// located in the buffer where the first error is
+    fn error(&self, error: fn(String) -> SniprunError, report: &str) -> SniprunError {
+        let mut first_line = None;
+        let message = FORTRAN_LOCATION.replace_all(report, |captures: &regex::Captures| {
+            let (before, line, after) = match captures.get(2) {
+                Some(line) => (&captures[1], line.as_str(), ""),
+                None => (&captures[3], &captures[4], &captures[5]),
+            };
+            let code_line = line
+                .parse::<i64>()
+                .ok()
+                .and_then(|line| self.line_offsets.code_line(line));
+            if first_line.is_none() {
+                first_line = code_line.and_then(|line| self.data.buffer_line(line));
+            }
+            match code_line {
+                Some(line) => format!("{}{}{}", before, line, after),
+                None => captures[0].to_string(),
+            }
+        });
+        let error = error(message.to_string());
+        match first_line {
+            Some(line) => error.at_line(line),
+            None => error,
+        }
+    }
+}
+
+impl Interpreter for Fortran_original {
+    declare_interpreter!(Fortran_original);
+
+    fn new_with_level(data: DataHolder, support_level: SupportLevel) -> Box<Fortran_original> {
+        let fwd = work_dir_path(&data, &Self::get_work_dir_name());
+        let mfp = Self::temp_source_path(&data);
+        let form = Self::select_form(&data);
+        Box::new(Fortran_original {
+            data,
+            support_level,
+            code: String::from(""),
+            fortran_work_dir: fwd,
+            main_file_path: mfp,
+            bin_path: String::new(),
+            form,
+            line_offsets: LineOffsets::default(),
+        })
+    }
+
+    fn get_supported_languages() -> Vec<String> {
+        vec![String::from("fortran"), String::from("f90")]
+    }
+
+    fn get_file_extension() -> String {
+        String::from("f90")
+    }
+
+    fn get_generated_file(&self) -> Option<String> {
+        Some(self.main_file_path.clone())
+    }
+
+    fn get_max_support_level() -> SupportLevel {
+        SupportLevel::Bloc
+    }
+
+    fn get_capabilities() -> Capabilities {
+        Capabilities::SEPARATE_BUILD | Capabilities::STREAMING
+    }
+
+    fn get_line_offsets(&self) -> LineOffsets {
+        self.line_offsets.clone()
+    }
+
+    fn fetch_code(&mut self) -> Result<(), SniprunError> {
+        self.code = selected_code(&self.data, self.get_current_level());
+        Ok(())
+    }
+
+    fn add_boilerplate(&mut self) -> Result<(), SniprunError> {
+        self.line_offsets = LineOffsets::default();
+        if !FORTRAN_PROGRAM.is_match(&self.code) {
+            // fixed-form statements start at the 7th column
+            let indent = if self.form == "fixed" { "      " } else { "" };
+            // implicit none must come before the declarations, it is off by default so that
+            // variables don't have to be declared
+            let implicit_none = self
+                .data
+                .interpreter_option(&Self::get_name(), "implicit_none")
+                .map(|v| config_bool(v, false))
+                .unwrap_or(false);
+            let mut header = format!("{}program main\n", indent);
+            if implicit_none {
+                header = format!("{}{}implicit none\n", header, indent);
+            }
+            self.line_offsets.prepend(header.lines().count() as i64);
+            self.code = format!(
+                "{}{}\n{}end program main\n",
+                header, self.code, indent
+            );
+        }
+        self.bin_path = format!(
+            "{}/{}.bin",
+            self.fortran_work_dir,
+            crate::cache::key(&(&self.code, &self.form))
+        );
+        Ok(())
+    }
+
+    fn build(&mut self) -> Result<(), SniprunError> {
+        write_code(&self.main_file_path, &self.code)?;
+        if Path::new(&self.bin_path).is_file() {
+            info!("[FORTRAN] reusing {}", self.bin_path);
+            return Ok(());
+        }
+
+        check_toolchain(&["gfortran"])?;
+        let output = Command::new("gfortran")
+            .arg(format!("-f{}-form", self.form))
+            .arg("-g")
+            .arg("-fbacktrace")
+            .arg("-o")
+            .arg(&self.bin_path)
+            .arg(&self.main_file_path)
+            .current_dir(&self.fortran_work_dir)
+            .output()?;
+        if !output.status.success() {
+            return Err(self.error(
+                SniprunError::CompilationError,
+                &String::from_utf8_lossy(&output.stderr),
+            ));
+        }
+        remove_oldest_files(&self.fortran_work_dir, "bin", KEPT_FORTRAN_BINARIES);
+        Ok(())
+    }
+
+    fn execute(&mut self) -> Result<ExecutionResult, SniprunError> {
+        let output = run_command(
+            Command::new(&self.bin_path).current_dir(&self.fortran_work_dir),
+            &self.data,
+        )?;
+        if output.status.success() {
+            Ok(ExecutionResult::from_output(&output))
+        } else {
+            Err(self.error(
+                SniprunError::RuntimeError,
+                &String::from_utf8_lossy(&output.stderr),
+            ))
+        }
+    }
+}
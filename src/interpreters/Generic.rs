@@ -1,5 +1,6 @@
 #[derive(Debug, Clone)]
 #[allow(non_camel_case_types)]
+#[allow(dead_code)]
 pub struct Generic {
     support_level: SupportLevel,
     data: DataHolder,
@@ -10,13 +11,10 @@ pub struct Generic {
 }
 
 impl Interpreter for Generic {
+    declare_interpreter!(Generic);
+
     fn new_with_level(data: DataHolder, support_level: SupportLevel) -> Box<Generic> {
-        let rwd = data.work_dir.clone() + "/generic";
-        let mut builder = DirBuilder::new();
-        builder.recursive(true);
-        builder
-            .create(&rwd)
-            .expect("Could not create directory for generic");
+        let rwd = work_dir_path(&data, &Self::get_work_dir_name());
         let mfp = rwd.clone() + "/main.json";
         let bp = String::from(&data.sniprun_root_dir) + "/ressources/runner";
         Box::new(Generic {
@@ -33,19 +31,8 @@ impl Interpreter for Generic {
         vec![]
     }
 
-    fn get_name() -> String {
-        String::from("Generic")
-    }
-
-    fn get_current_level(&self) -> SupportLevel {
-        self.support_level
-    }
-    fn set_current_level(&mut self, level: SupportLevel) {
-        self.support_level = level;
-    }
-
-    fn get_data(&self) -> DataHolder {
-        self.data.clone()
+    fn get_generated_file(&self) -> Option<String> {
+        Some(self.main_file_path.clone())
     }
 
     fn get_max_support_level() -> SupportLevel {
@@ -54,78 +41,72 @@ impl Interpreter for Generic {
     }
 
     fn fetch_code(&mut self) -> Result<(), SniprunError> {
-        if !self
-            .data
-            .current_bloc
-            .replace(&[' ', '\t', '\n', '\r'][..], "")
-            .is_empty()
-        {
-            self.code = self.data.current_bloc.clone();
-        } else if !self.data.current_line.replace(" ", "").is_empty() {
-            self.code = self.data.current_line.clone();
-        } else {
-            self.code = String::from("");
-        }
+        // the generic interpreter has no support level of its own, it runs what is selected
+        self.code = selected_code(&self.data, SupportLevel::Bloc);
         Ok(())
     }
 
     fn add_boilerplate(&mut self) -> Result<(), SniprunError> {
-        self.code = String::from("{")
-            + "\"language\":\""
-            + &self.data.filetype
-            + "\",\"files\":[{\"name\": \"name.any\",\"content\":\""
-            + &self.code.replace("\\\"", "\"").replace("\"", "\\\"")
-            + "\"}]}";
+        let json = serde_json::json!({
+            "language": self.data.filetype,
+            "files": [{"name": "name.any", "content": self.code}],
+        });
+        self.code = json.to_string();
         Ok(())
     }
 
     fn build(&mut self) -> Result<(), SniprunError> {
         //write json file for glot
-        let mut _file =
-            File::create(&self.main_file_path).expect("Failed to create file for generic");
-        write(&self.main_file_path, &self.code).expect("Unable to write to file for generic");
+        write_code(&self.main_file_path, &self.code)?;
         Ok(())
     }
 
-    fn execute(&mut self) -> Result<String, SniprunError> {
+    fn execute(&mut self) -> Result<ExecutionResult, SniprunError> {
         info!(
             "executing generic: args are glotpath:{}, jsonpath:{}",
             &self.glot_bin_path, &self.main_file_path
         );
+        check_toolchain(&[&self.glot_bin_path])?;
         let output = Command::new(&self.glot_bin_path)
-            .stdin(File::open(&self.main_file_path).unwrap())
-            .output()
-            .expect("Unable to start process");
+            .stdin(File::open(&self.main_file_path)?)
+            .output()?;
         info!(
             "generic executed, status.success?:{}",
             output.status.success()
         );
         if output.status.success() {
             //unwrap the json output
-            let js = String::from_utf8(output.stdout).unwrap();
+            let js = String::from_utf8_lossy(&output.stdout).to_string();
             info!("json output: {:?}", js);
-            let parsed: Value = serde_json::from_str(&js).unwrap();
-            let res_stdout = parsed.get("stdout").unwrap().to_string();
-            let res_stderr = parsed.get("stderr").unwrap().to_string();
+            let parsed: Value = serde_json::from_str(&js).map_err(|e| {
+                SniprunError::InterpreterLimitationError(format!(
+                    "Generic interpreter (!): invalid output from runner: {}",
+                    e
+                ))
+            })?;
+            let res_stdout = parsed.get("stdout").and_then(Value::as_str).unwrap_or("");
+            let res_stderr = parsed.get("stderr").and_then(Value::as_str).unwrap_or("");
 
             if !res_stdout.is_empty() {
                 info!("res_stdout :{}", res_stdout);
-                return Ok(String::from("Generic interpreter (!): ") + &res_stdout.to_string());
+                Ok(ExecutionResult::new(
+                    String::from("Generic interpreter (!): ") + res_stdout,
+                ))
             } else if !res_stderr.is_empty() {
-                return Err(SniprunError::RuntimeError(
-                    String::from("Generic interpreter (!): ") + &res_stderr.to_string(),
-                ));
+                Err(SniprunError::RuntimeError(
+                    String::from("Generic interpreter (!): ") + res_stderr,
+                ))
             } else {
-                return Err(SniprunError::CompilationError(String::from(
+                Err(SniprunError::CompilationError(String::from(
                     "Generic interpreter (!): unknown compilation error",
-                )));
+                )))
             }
         } else {
             //this should not happen but anyway
-            return Err(SniprunError::RuntimeError(
+            Err(SniprunError::RuntimeError(
                 String::from("Generic interpreter (!): ")
-                    + &String::from_utf8(output.stderr).unwrap(),
-            ));
+                    + &String::from_utf8_lossy(&output.stderr),
+            ))
         }
     }
 }
@@ -0,0 +1,204 @@
+#[derive(Debug, Clone)]
+#[allow(non_camel_case_types)]
+pub struct Go_original {
+    support_level: SupportLevel,
+    data: DataHolder,
+    code: String,
+
+    ///specific to go
+    go_work_dir: String,
+    main_file_path: String,
+    line_offsets: LineOffsets,
+}
+
+/// the packages of the standard library snippets often use, imported when the code
+/// references them (go refuses unused imports)
+const STD_PACKAGES: &[&str] = &[
+    "fmt", "strings", "strconv", "math", "os", "sort", "time", "errors", "bytes", "unicode",
+    "bufio", "io", "sync", "rand", "regexp", "json", "reflect", "utf8",
+];
+
+lazy_static! {
+    /// a reference to a package, eg: 'fmt.' in 'fmt.Println'
+    static ref PACKAGE_REFERENCE: Regex = Regex::new(r"\b([a-z][a-z0-9]*)\.[A-Z]").unwrap();
+    /// go locates errors as './main.go:LINE:COLUMN: message'
+    static ref GO_LOCATION: Regex = Regex::new(r"\S*main\.go:(\d+)(:\d+)?").unwrap();
+}
+
+impl Go_original {
+    /// the import path of a package of STD_PACKAGES
+    fn import_path(package: &str) -> String {
+        match package {
+            "rand" => String::from("math/rand"),
+            "json" => String::from("encoding/json"),
+            "utf8" => String::from("unicode/utf8"),
+            _ => package.to_string(),
+        }
+    }
+
+    /// the import lines for the standard packages the code references, and the ones it
+    /// imports itself
+    fn imports(code: &str) -> Vec<String> {
+        let mut imports: Vec<String> = code
+            .lines()
+            .filter(|l| l.trim_start().starts_with("import "))
+            .map(|l| l.trim().to_string())
+            .collect();
+        let mut packages: Vec<&str> = PACKAGE_REFERENCE
+            .captures_iter(code)
+            .filter_map(|captures| captures.get(1))
+            .map(|package| package.as_str())
+            .filter(|package| STD_PACKAGES.contains(package))
+            .collect();
+        packages.sort_unstable();
+        packages.dedup();
+        for package in packages {
+            let path = Self::import_path(package);
+            if !imports.iter().any(|import| import.contains(&format!("\"{}\"", path))) {
+                imports.push(format!("import \"{}\"", path));
+            }
+        }
+        imports
+    }
+
+    /// the compilation error, with the lines of the code rather than the ones of the generated
+    /// file, located in the buffer where the first error is
+    fn compilation_error(&self, message: &str) -> SniprunError {
+        let mut first_line = None;
+        let message = GO_LOCATION.replace_all(message, |captures: &regex::Captures| {
+            let generated_line = captures[1].parse::<i64>().unwrap_or(0);
+            if first_line.is_none() {
+                first_line = self.to_buffer_line(generated_line);
+            }
+            match self.line_offsets.code_line(generated_line) {
+                Some(line) => format!(
+                    "main.go:{}{}",
+                    line,
+                    captures.get(2).map_or("", |c| c.as_str())
+                ),
+                None => captures[0].to_string(),
+            }
+        });
+        let error = SniprunError::CompilationError(message.to_string());
+        match first_line {
+            Some(line) => error.at_line(line),
+            None => error,
+        }
+    }
+
+    /// directory of the go module (the closest ancestor with a go.mod) the edited file is in, if any
+    fn module_dir(&self) -> Option<PathBuf> {
+        find_ancestor(Path::new(&self.data.filepath), "go.mod")
+    }
+}
+
+impl Interpreter for Go_original {
+    declare_interpreter!(Go_original);
+
+    fn new_with_level(data: DataHolder, support_level: SupportLevel) -> Box<Go_original> {
+        let gwd = work_dir_path(&data, &Self::get_work_dir_name());
+        let mfp = gwd.clone() + "/main.go";
+        Box::new(Go_original {
+            data,
+            support_level,
+            code: String::from(""),
+            go_work_dir: gwd,
+            main_file_path: mfp,
+            line_offsets: LineOffsets::default(),
+        })
+    }
+
+    fn get_supported_languages() -> Vec<String> {
+        vec![String::from("go"), String::from("golang")]
+    }
+
+    fn get_generated_file(&self) -> Option<String> {
+        Some(self.main_file_path.clone())
+    }
+
+    fn get_max_support_level() -> SupportLevel {
+        SupportLevel::Bloc
+    }
+
+    fn get_capabilities() -> Capabilities {
+        Capabilities::STREAMING | Capabilities::PROJECT_FILES
+    }
+
+    fn fetch_code(&mut self) -> Result<(), SniprunError> {
+        self.code = selected_code(&self.data, self.get_current_level());
+        Ok(())
+    }
+
+    fn get_line_offsets(&self) -> LineOffsets {
+        self.line_offsets.clone()
+    }
+
+    fn add_boilerplate(&mut self) -> Result<(), SniprunError> {
+        self.line_offsets = LineOffsets::default();
+        // a complete program (eg: a whole file) is run as-is
+        if self.code.trim_start().starts_with("package ") {
+            return Ok(());
+        }
+        // the imports can't be inside main(): the ones of the code are moved before it, and
+        // blanked in place so that the lines of the code don't move
+        let imports = Self::imports(&self.code);
+        let body: Vec<&str> = self
+            .code
+            .lines()
+            .map(|l| {
+                if l.trim_start().starts_with("import ") {
+                    ""
+                } else {
+                    l
+                }
+            })
+            .collect();
+        self.line_offsets.prepend(imports.len() as i64 + 2);
+        self.code = String::from("package main\n")
+            + &imports
+                .iter()
+                .map(|import| import.clone() + "\n")
+                .collect::<String>()
+            + "func main() {\n"
+            + &body.join("\n")
+            + "\n}\n";
+        Ok(())
+    }
+
+    fn build(&mut self) -> Result<(), SniprunError> {
+        write_code(&self.main_file_path, &self.code)?;
+        // outside of a module, the code is its own one
+        let go_mod = self.go_work_dir.clone() + "/go.mod";
+        if self.module_dir().is_none() && !Path::new(&go_mod).is_file() {
+            write_code(&go_mod, "module sniprun_snippet\n\ngo 1.16\n")?;
+        }
+        Ok(())
+    }
+
+    fn execute(&mut self) -> Result<ExecutionResult, SniprunError> {
+        check_toolchain(&["go"])?;
+        // run from the module the edited file is in, so imports resolve
+        let run_dir = self
+            .module_dir()
+            .unwrap_or_else(|| PathBuf::from(&self.go_work_dir));
+        info!("[GO] running from {:?}", run_dir);
+        let output = run_command(
+            Command::new("go")
+                .arg("run")
+                .arg(&self.main_file_path)
+                .current_dir(run_dir),
+            &self.data,
+        )?;
+
+        if output.status.success() {
+            return Ok(ExecutionResult::from_output(&output));
+        }
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        // build failures are reported under a '# package' header, panics are not
+        if stderr.starts_with("# ") && !stderr.contains("panic:") {
+            Err(self.compilation_error(&stderr.lines().skip(1).collect::<Vec<_>>().join("\n")))
+        } else {
+            Err(SniprunError::RuntimeError(stderr))
+        }
+    }
+}
@@ -0,0 +1,217 @@
+#[derive(Debug, Clone)]
+#[allow(non_camel_case_types)]
+pub struct Groovy_original {
+    support_level: SupportLevel,
+    data: DataHolder,
+    code: String,
+
+    ///specific to groovy
+    groovy_work_dir: String,
+    main_file_path: String,
+    ///"groovy" (a script) or "gradle" (applied to the project of the closest gradlew, that
+    ///runs it)
+    runner: String,
+}
+
+lazy_static! {
+    /// groovy locates compilation errors as '/path/to/main.groovy: LINE: message', the frames
+    /// of a stack trace as 'at main.run(main.groovy:LINE)'
+    static ref GROOVY_LOCATION: Regex = Regex::new(r"main\.(?:groovy|gradle):\s?(\d+)").unwrap();
+    /// an assignment, not a comparison: its value isn't worth printing
+    static ref GROOVY_ASSIGNMENT: Regex =
+        Regex::new(r"^[\w.\[\]'\x22 ]+\s*([-+*/%]|<<|>>)?=[^=~]").unwrap();
+}
+
+impl Groovy_original {
+    /// whether a line of code is an expression whose value is worth printing
+    fn is_expression(line: &str) -> bool {
+        let keywords = [
+            "def", "var", "import", "package", "class", "interface", "enum", "trait", "if",
+            "else", "for", "while", "do", "switch", "case", "default", "try", "catch", "finally",
+            "return", "throw", "break", "continue", "assert", "static", "final", "abstract",
+            "public", "private", "protected", "println", "print", "printf",
+        ];
+        let trimmed = line.trim_end();
+        let first_word = trimmed
+            .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .next()
+            .unwrap_or("");
+        !(line.starts_with(char::is_whitespace)
+            || trimmed.is_empty()
+            || ["//", "/*", "*", "@", "}", ")", "]", "."]
+                .iter()
+                .any(|start| trimmed.starts_with(start))
+            || ["{", "(", "[", ",", "->", ";"]
+                .iter()
+                .any(|end| trimmed.ends_with(end))
+            || GROOVY_ASSIGNMENT.is_match(trimmed)
+            || keywords.contains(&first_word))
+    }
+
+    /// whether the expression goes on, on the next line
+    fn continues(line: &str) -> bool {
+        let trimmed = line.trim_end();
+        ["(", "[", "{", ",", "=", "+", "-", "*", "/", ".", "&&", "||", "?", ":", "->"]
+            .iter()
+            .any(|end| trimmed.ends_with(end))
+    }
+
+    /// the directory of the closest gradle wrapper above the edited file
+    fn gradle_project(&self) -> Result<PathBuf, SniprunError> {
+        find_ancestor(Path::new(&self.data.filepath), "gradlew").ok_or_else(|| {
+            SniprunError::CustomError(String::from(
+                "no gradlew found above the file, the gradle runner needs a gradle project",
+            ))
+        })
+    }
+
+    /// the error of a failed run, located in the buffer where the first error is: the script
+    /// doesn't compile ('startup failed') or threw
+    fn error(&self, stderr: &str) -> SniprunError {
+        let message = stderr.trim().to_string();
+        let error = if stderr.contains("startup failed") || stderr.contains("Could not compile") {
+            SniprunError::CompilationError(message)
+        } else {
+            SniprunError::RuntimeError(message)
+        };
+        match GROOVY_LOCATION
+            .captures(stderr)
+            .and_then(|captures| captures[1].parse::<i64>().ok())
+            .and_then(|line| self.to_buffer_line(line))
+        {
+            Some(line) => error.at_line(line),
+            None => error,
+        }
+    }
+}
+
+impl Interpreter for Groovy_original {
+    declare_interpreter!(Groovy_original);
+
+    fn new_with_level(data: DataHolder, support_level: SupportLevel) -> Box<Groovy_original> {
+        let gwd = work_dir_path(&data, &Self::get_work_dir_name());
+        let runner = data
+            .interpreter_option(&Self::get_name(), "runner")
+            .and_then(|r| r.as_str())
+            .unwrap_or("groovy")
+            .to_string();
+        let mfp = gwd.clone()
+            + if runner == "gradle" {
+                "/main.gradle"
+            } else {
+                "/main.groovy"
+            };
+        Box::new(Groovy_original {
+            data,
+            support_level,
+            code: String::from(""),
+            groovy_work_dir: gwd,
+            main_file_path: mfp,
+            runner,
+        })
+    }
+
+    fn get_supported_languages() -> Vec<String> {
+        vec![String::from("groovy"), String::from("gradle")]
+    }
+
+    fn get_generated_file(&self) -> Option<String> {
+        Some(self.main_file_path.clone())
+    }
+
+    fn get_max_support_level() -> SupportLevel {
+        SupportLevel::Bloc
+    }
+
+    fn get_capabilities() -> Capabilities {
+        Capabilities::STREAMING
+    }
+
+    fn negotiate_capabilities(data: &DataHolder) -> Capabilities {
+        // the gradle runner applies the code to the project
+        match data.interpreter_option(&Self::get_name(), "runner") {
+            Some(runner) if runner.as_str() == Some("gradle") => {
+                Self::get_capabilities() | Capabilities::PROJECT_FILES
+            }
+            _ => Self::get_capabilities(),
+        }
+    }
+
+    fn fetch_code(&mut self) -> Result<(), SniprunError> {
+        self.code = selected_code(&self.data, self.get_current_level());
+        Ok(())
+    }
+
+    fn add_boilerplate(&mut self) -> Result<(), SniprunError> {
+        // like groovysh, the value of the last line is printed, unless it is null: the line is
+        // wrapped in place, so that the lines of the code don't move (and @Grab annotations
+        // stay where they are)
+        if self.runner == "gradle" {
+            return Ok(());
+        }
+        let mut lines: Vec<String> = self.code.lines().map(String::from).collect();
+        if let Some(last) = lines.iter().rposition(|l| !l.trim().is_empty()) {
+            let continued = lines[..last]
+                .iter()
+                .rev()
+                .find(|l| !l.trim().is_empty())
+                .is_some_and(|l| Self::continues(l));
+            if !continued && Self::is_expression(&lines[last]) {
+                lines[last] = format!(
+                    "Optional.ofNullable(({})).ifPresent {{ println it }}",
+                    lines[last].trim()
+                );
+            }
+        }
+        self.code = lines.join("\n") + "\n";
+        Ok(())
+    }
+
+    fn build(&mut self) -> Result<(), SniprunError> {
+        write_code(&self.main_file_path, &self.code)
+    }
+
+    fn execute(&mut self) -> Result<ExecutionResult, SniprunError> {
+        let mut command = if self.runner == "gradle" {
+            // the code is applied to the root project by an init script, then the task runs
+            // (by default none: the project is only configured)
+            let project = self.gradle_project()?;
+            let init_script = self.groovy_work_dir.clone() + "/init.gradle";
+            write_code(
+                &init_script,
+                &format!(
+                    "rootProject {{ apply from: '{}' }}\n",
+                    self.main_file_path.replace('\\', "/")
+                ),
+            )?;
+            let task = self
+                .data
+                .interpreter_option(&Self::get_name(), "task")
+                .and_then(|t| t.as_str())
+                .unwrap_or("");
+            info!("[GROOVY] running the gradle task '{}' in {:?}", task, project);
+            let mut command = Command::new(project.join("gradlew"));
+            command.arg("-q").arg("--init-script").arg(init_script);
+            if task.is_empty() {
+                command.arg("--dry-run").arg("help");
+            } else {
+                command.args(task.split_whitespace());
+            }
+            command.current_dir(project);
+            command
+        } else {
+            check_toolchain(&["groovy"])?;
+            let mut command = Command::new("groovy");
+            command
+                .arg(&self.main_file_path)
+                .current_dir(&self.groovy_work_dir);
+            command
+        };
+        let output = run_command(&mut command, &self.data)?;
+        if output.status.success() {
+            Ok(ExecutionResult::from_output(&output))
+        } else {
+            Err(self.error(&String::from_utf8_lossy(&output.stderr)))
+        }
+    }
+}
@@ -0,0 +1,352 @@
+#[derive(Debug, Clone)]
+#[allow(non_camel_case_types)]
+pub struct Haskell_original {
+    support_level: SupportLevel,
+    data: DataHolder,
+    code: String,
+
+    ///specific to haskell
+    haskell_work_dir: String,
+    main_file_path: String,
+    bin_path: String,
+    ///"runghc", "stack" (stack runghc) or "ghc" (compiled with -O0, then run)
+    runner: String,
+}
+
+lazy_static! {
+    /// ghc locates errors as 'Main.hs:line:column: error:', the message may follow on the next lines
+    static ref GHC_ERROR: Regex = Regex::new(r"Main\.hs:(\d+):\d+(?:-\d+)?: error:(.*)").unwrap();
+    /// a language pragma, eg: '{-# LANGUAGE LambdaCase #-}'
+    static ref LANGUAGE_PRAGMA: Regex = Regex::new(r"^\s*\{-#\s*LANGUAGE\b.*#-\}\s*$").unwrap();
+}
+
+impl Haskell_original {
+    /// the runner the user chose (interpreter option `runner`), runghc by default
+    fn runner(data: &DataHolder) -> String {
+        data.interpreter_option(&Self::get_name(), "runner")
+            .and_then(|r| r.as_str())
+            .filter(|r| !r.is_empty())
+            .unwrap_or("runghc")
+            .to_string()
+    }
+
+    /// whether the code is a whole program: it defines its own entry point or module
+    fn is_program(code: &str) -> bool {
+        code.lines().any(|l| {
+            l.starts_with("main ")
+                || l.starts_with("main=")
+                || l.starts_with("main::")
+                || l.starts_with("module ")
+        })
+    }
+
+    /// whether a top-level line is a declaration (import, type signature, definition...)
+    /// rather than an expression to evaluate
+    fn is_declaration(line: &str) -> bool {
+        let keywords = [
+            "import ", "module ", "data ", "type ", "newtype ", "class ", "instance ", "deriving ",
+            "{-", "--",
+        ];
+        if keywords.iter().any(|keyword| line.starts_with(keyword)) || line.contains("::") {
+            return true;
+        }
+        // a definition has a '=' that is not part of an operator
+        ["==", "<=", ">=", "/=", "=>"]
+            .iter()
+            .fold(line.to_string(), |line, operator| line.replace(operator, ""))
+            .contains('=')
+    }
+
+    /// whether a top-level line is an action of the main do block (binding, let, expression)
+    fn is_statement(line: &str) -> bool {
+        line.starts_with("let ") || line.contains("<-") || !Self::is_declaration(line)
+    }
+
+    /// whether an expression is already an IO action, that is run rather than printed
+    fn is_action(expression: &str) -> bool {
+        let actions = [
+            "putStr", "print", "mapM", "forM", "when ", "unless ", "sequence", "return ", "pure ",
+            "interact ", "hPut", "do",
+        ];
+        actions.iter().any(|action| expression.starts_with(action))
+    }
+
+    /// the code split in units: a top-level line and the indented lines that follow it, with
+    /// the (1-based) line of the code the unit starts at
+    fn units(code: &str) -> Vec<(i64, Vec<&str>)> {
+        let mut units: Vec<(i64, Vec<&str>)> = vec![];
+        for (i, line) in code.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            match units.last_mut() {
+                Some((_, unit)) if line.starts_with(|c: char| c.is_whitespace()) => {
+                    unit.push(line)
+                }
+                _ => units.push((i as i64 + 1, vec![line])),
+            }
+        }
+        units
+    }
+
+    /// a LINE pragma, so that ghc reports the errors at the lines of the code
+    fn line_pragma(line: i64, indent: &str) -> String {
+        format!("{}{{-# LINE {} \"Main.hs\" #-}}", indent, line)
+    }
+
+    /// add the lines of a unit to the module, after a LINE pragma if they don't follow the
+    /// lines added before in the code
+    fn push_unit(
+        module: &mut Vec<String>,
+        next_line: &mut Option<i64>,
+        line: i64,
+        unit: &[&str],
+        indent: &str,
+    ) {
+        if *next_line != Some(line) {
+            module.push(Self::line_pragma(line, indent));
+        }
+        module.extend(unit.iter().map(|l| format!("{}{}", indent, l)));
+        *next_line = Some(line + unit.len() as i64);
+    }
+
+    /// the code as a module: the language pragmas first, then the declarations, then the
+    /// other lines in the do block of main, re-indented under it. A single expression is
+    /// printed
+    fn wrap_in_module(code: &str) -> String {
+        let mut pragmas = vec![];
+        let mut body = String::new();
+        for line in code.lines() {
+            if LANGUAGE_PRAGMA.is_match(line) {
+                pragmas.push(line.trim().to_string());
+                body.push('\n');
+            } else {
+                body.push_str(line);
+                body.push('\n');
+            }
+        }
+        let units = Self::units(&body);
+        let (statements, declarations): (Vec<_>, Vec<_>) = units
+            .into_iter()
+            .partition(|(_, unit)| Self::is_statement(unit[0]));
+
+        let mut module = pragmas;
+        module.push(String::from("module Main where"));
+        // the line of the code the next line of the module is at, if they follow each other
+        let mut next_line = None;
+        for (line, unit) in &declarations {
+            Self::push_unit(&mut module, &mut next_line, *line, unit, "");
+        }
+        module.push(String::from("main :: IO ()"));
+        match statements.as_slice() {
+            [] => module.push(String::from("main = return ()")),
+            [(line, unit)] if !unit[0].starts_with("let ")
+                && !unit[0].contains("<-")
+                && !Self::is_action(unit[0]) =>
+            {
+                module.push(String::from("main = print $ ("));
+                next_line = None;
+                Self::push_unit(&mut module, &mut next_line, *line, unit, "  ");
+                module.push(String::from("  )"));
+            }
+            statements => {
+                module.push(String::from("main = do"));
+                next_line = None;
+                for (line, unit) in statements {
+                    Self::push_unit(&mut module, &mut next_line, *line, unit, "  ");
+                }
+            }
+        }
+        module.join("\n") + "\n"
+    }
+
+    /// the first ghc error of the output, located in the buffer
+    fn compilation_error(&self, stderr: &str) -> Option<SniprunError> {
+        let captures = GHC_ERROR.captures(stderr)?;
+        let start = captures.get(0)?.end();
+        // the details are the indented lines that follow the location
+        let details: Vec<&str> = stderr[start..]
+            .lines()
+            .skip(1)
+            .take_while(|l| l.starts_with(' '))
+            .map(|l| l.trim())
+            .filter(|l| !l.is_empty() && !l.starts_with('|'))
+            .collect();
+        let message = (captures[2].trim().to_string() + " " + &details.join(" "))
+            .trim()
+            .to_string();
+        let error = SniprunError::CompilationError(message);
+        let line = captures[1]
+            .parse::<i64>()
+            .ok()
+            .and_then(|line| self.to_buffer_line(line));
+        Some(match line {
+            Some(line) => error.at_line(line),
+            None => error,
+        })
+    }
+
+    /// the error of a failed compilation or run
+    fn error(&self, stderr: &str) -> SniprunError {
+        self.compilation_error(stderr)
+            .unwrap_or_else(|| SniprunError::RuntimeError(stderr.to_string()))
+    }
+}
+
+impl Interpreter for Haskell_original {
+    declare_interpreter!(Haskell_original);
+
+    fn new_with_level(data: DataHolder, support_level: SupportLevel) -> Box<Haskell_original> {
+        let hwd = work_dir_path(&data, &Self::get_work_dir_name());
+        let mfp = hwd.clone() + "/Main.hs";
+        let bp = hwd.clone() + "/main";
+        let runner = Self::runner(&data);
+        Box::new(Haskell_original {
+            data,
+            support_level,
+            code: String::from(""),
+            haskell_work_dir: hwd,
+            main_file_path: mfp,
+            bin_path: bp,
+            runner,
+        })
+    }
+
+    fn get_supported_languages() -> Vec<String> {
+        vec![String::from("haskell"), String::from("hs")]
+    }
+
+    fn get_generated_file(&self) -> Option<String> {
+        Some(self.main_file_path.clone())
+    }
+
+    fn get_max_support_level() -> SupportLevel {
+        SupportLevel::Bloc
+    }
+
+    fn get_capabilities() -> Capabilities {
+        Capabilities::STREAMING
+    }
+
+    fn fetch_code(&mut self) -> Result<(), SniprunError> {
+        self.code = unindent(&selected_code(&self.data, self.get_current_level()));
+        Ok(())
+    }
+
+    fn add_boilerplate(&mut self) -> Result<(), SniprunError> {
+        // a whole program is run as-is
+        if !Self::is_program(&self.code) {
+            self.code = Self::wrap_in_module(&self.code);
+        }
+        Ok(())
+    }
+
+    fn build(&mut self) -> Result<(), SniprunError> {
+        write_code(&self.main_file_path, &self.code)?;
+        if self.runner != "ghc" {
+            return Ok(());
+        }
+        check_toolchain(&["ghc"])?;
+        let output = Command::new("ghc")
+            .arg("-O0")
+            .arg("-outputdir")
+            .arg(&self.haskell_work_dir)
+            .arg("-o")
+            .arg(&self.bin_path)
+            .arg(&self.main_file_path)
+            .current_dir(&self.haskell_work_dir)
+            .output()?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+            Err(self
+                .compilation_error(&stderr)
+                .unwrap_or(SniprunError::CompilationError(stderr)))
+        }
+    }
+
+    fn execute(&mut self) -> Result<ExecutionResult, SniprunError> {
+        info!("[HASKELL] running with {}", self.runner);
+        let mut command = match self.runner.as_str() {
+            "ghc" => Command::new(&self.bin_path),
+            "stack" => {
+                check_toolchain(&["stack"])?;
+                let mut command = Command::new("stack");
+                command.arg("runghc").arg(&self.main_file_path);
+                command
+            }
+            runner => {
+                check_toolchain(&[runner])?;
+                let mut command = Command::new(runner);
+                command.arg(&self.main_file_path);
+                command
+            }
+        };
+        // runghc type checks the code before running it
+        let output = run_command(command.current_dir(&self.haskell_work_dir), &self.data)?;
+        if output.status.success() {
+            Ok(ExecutionResult::from_output(&output))
+        } else {
+            Err(self.error(&String::from_utf8_lossy(&output.stderr)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_haskell_original {
+    use super::*;
+
+    fn haskell(code: &str) -> Box<Haskell_original> {
+        DataHolder::prepared("haskell", code, &[])
+    }
+
+    #[test]
+    fn a_bare_expression_is_printed() {
+        assert_eq!(
+            haskell("1+1").code,
+            "module Main where\nmain :: IO ()\nmain = print $ (\n  {-# LINE 1 \"Main.hs\" #-}\n  1+1\n  )\n"
+        );
+    }
+
+    #[test]
+    fn declarations_go_before_main_and_statements_in_it() {
+        let code = "{-# LANGUAGE LambdaCase #-}\nsquare :: Int -> Int\nsquare x = x * x\nlet y = square 3\nprint y";
+        assert_eq!(
+            haskell(code).code,
+            "{-# LANGUAGE LambdaCase #-}\nmodule Main where\n{-# LINE 2 \"Main.hs\" #-}\nsquare :: Int -> Int\nsquare x = x * x\nmain :: IO ()\nmain = do\n  {-# LINE 4 \"Main.hs\" #-}\n  let y = square 3\n  print y\n"
+        );
+    }
+
+    #[test]
+    fn a_program_is_not_wrapped_again() {
+        let code = "main :: IO ()\nmain = print 2";
+        assert!(Haskell_original::is_program(code));
+        assert_eq!(haskell(code).code, code);
+        assert!(!Haskell_original::is_program("mainly = 1"));
+    }
+
+    #[test]
+    fn type_errors_are_located() {
+        let interpreter = haskell("let a = 1 :: Int\nputStrLn a");
+        let stderr = "/tmp/Main.hs:2:12: error:\n    • Couldn't match type ‘Int’ with ‘[Char]’\n      |\n    2 | putStrLn a\n";
+        let error = interpreter.error(stderr);
+        assert!(matches!(error.inner(), SniprunError::CompilationError(_)));
+        assert_eq!(error.line(), Some(2));
+        assert!(error.inner().to_string().contains("Couldn't match type"));
+        assert!(matches!(
+            interpreter.error("Main: Prelude.head: empty list"),
+            SniprunError::RuntimeError(_)
+        ));
+    }
+
+    #[test]
+    fn snippets_run() {
+        if check_toolchain(&["runghc"]).is_err() {
+            return;
+        }
+        assert_eq!(haskell("1+1").run().unwrap().stdout, "2\n");
+        let code = "main :: IO ()\nmain = putStrLn \"program\"";
+        assert_eq!(haskell(code).run().unwrap().stdout, "program\n");
+    }
+}
@@ -0,0 +1,179 @@
+#[derive(Debug, Clone)]
+#[allow(non_camel_case_types)]
+pub struct JS_original {
+    support_level: SupportLevel,
+    data: DataHolder,
+    code: String,
+
+    ///specific to javascript
+    js_work_dir: String,
+    ///main.js, or main.mjs for code using ES modules
+    main_file_path: String,
+    ///the command running the code, eg: ["node"], ["deno", "run"]
+    runtime: Vec<String>,
+    line_offsets: LineOffsets,
+}
+
+lazy_static! {
+    /// node locates errors as '/path/to/main.js:LINE' (in the header of syntax errors) or
+    /// '/path/to/main.js:LINE:COLUMN' (in the frames of a stack trace)
+    static ref JS_LOCATION: Regex = Regex::new(r"main\.m?js:(\d+)").unwrap();
+}
+
+impl JS_original {
+    /// the runtime the user chose (interpreter option `runtime`, eg: 'deno run' or 'bun'),
+    /// node by default
+    fn runtime(data: &DataHolder) -> Vec<String> {
+        data.interpreter_option(&Self::get_name(), "runtime")
+            .and_then(|r| r.as_str())
+            .map(|r| r.split_whitespace().map(String::from).collect::<Vec<_>>())
+            .filter(|r| !r.is_empty())
+            .unwrap_or_else(|| vec![String::from("node")])
+    }
+
+    /// the closest directory above the edited file with a package.json or a node_modules, so
+    /// that the project's dependencies can be required
+    fn project_dir(&self) -> Option<PathBuf> {
+        let start = Path::new(&self.data.filepath);
+        let package = find_ancestor(start, "package.json");
+        let modules = find_ancestor(start, "node_modules");
+        // the deepest one is the closest
+        match (package, modules) {
+            (Some(package), Some(modules)) => Some(std::cmp::max_by_key(package, modules, |dir| {
+                dir.components().count()
+            })),
+            (package, modules) => package.or(modules),
+        }
+    }
+
+    /// whether the code is an ES module (import/export statements), that node only runs
+    /// from a .mjs file
+    fn is_module(code: &str) -> bool {
+        code.lines().any(|l| {
+            let l = l.trim_start();
+            (l.starts_with("import ") && !l.starts_with("import(")) || l.starts_with("export ")
+        })
+    }
+
+    /// the error of a failed run, located in the buffer where the code failed
+    fn error(&self, stderr: &str) -> SniprunError {
+        let error = if stderr.contains("SyntaxError") {
+            SniprunError::CompilationError(stderr.to_string())
+        } else {
+            SniprunError::RuntimeError(stderr.to_string())
+        };
+        let line = JS_LOCATION
+            .captures_iter(stderr)
+            .filter_map(|captures| captures[1].parse::<i64>().ok())
+            .find_map(|line| self.to_buffer_line(line));
+        match line {
+            Some(line) => error.at_line(line),
+            None => error,
+        }
+    }
+}
+
+impl Interpreter for JS_original {
+    declare_interpreter!(JS_original);
+
+    fn new_with_level(data: DataHolder, support_level: SupportLevel) -> Box<JS_original> {
+        let jwd = work_dir_path(&data, &Self::get_work_dir_name());
+        let mfp = jwd.clone() + "/main.js";
+        let runtime = Self::runtime(&data);
+        Box::new(JS_original {
+            data,
+            support_level,
+            code: String::from(""),
+            js_work_dir: jwd,
+            main_file_path: mfp,
+            runtime,
+            line_offsets: LineOffsets::default(),
+        })
+    }
+
+    fn get_supported_languages() -> Vec<String> {
+        vec![String::from("javascript"), String::from("js")]
+    }
+
+    fn get_generated_file(&self) -> Option<String> {
+        Some(self.main_file_path.clone())
+    }
+
+    fn get_max_support_level() -> SupportLevel {
+        SupportLevel::Bloc
+    }
+
+    fn get_capabilities() -> Capabilities {
+        Capabilities::STREAMING | Capabilities::PROJECT_FILES
+    }
+
+    fn get_line_offsets(&self) -> LineOffsets {
+        self.line_offsets.clone()
+    }
+
+    fn fetch_code(&mut self) -> Result<(), SniprunError> {
+        self.code = selected_code(&self.data, self.get_current_level());
+        Ok(())
+    }
+
+    fn add_boilerplate(&mut self) -> Result<(), SniprunError> {
+        self.line_offsets = LineOffsets::default();
+        if Self::is_module(&self.code) {
+            // modules support top-level await as-is
+            self.main_file_path = self.js_work_dir.clone() + "/main.mjs";
+        } else {
+            self.main_file_path = self.js_work_dir.clone() + "/main.js";
+            // scripts don't: the code then runs in an async function. A rejection is not
+            // caught, the runtime reports it and fails
+            if self.code.contains("await") {
+                self.code = String::from("(async () => {\n") + &self.code + "\n})();\n";
+                self.line_offsets.prepend(1);
+            }
+        }
+        Ok(())
+    }
+
+    fn build(&mut self) -> Result<(), SniprunError> {
+        write_code(&self.main_file_path, &self.code)
+    }
+
+    fn check(&mut self) -> Result<ExecutionResult, SniprunError> {
+        self.build()?;
+        // only node has a syntax check
+        if self.runtime != ["node"] {
+            return Ok(ExecutionResult::new(format!(
+                "no syntax check with {}, nothing was checked\n",
+                self.runtime.join(" ")
+            )));
+        }
+        check_toolchain(&["node"])?;
+        syntax_check(
+            Command::new("node").arg("--check").arg(&self.main_file_path),
+            |stderr| self.error(stderr),
+        )
+    }
+
+    fn execute(&mut self) -> Result<ExecutionResult, SniprunError> {
+        check_toolchain(&[&self.runtime[0]])?;
+        let mut command = Command::new(&self.runtime[0]);
+        command.args(&self.runtime[1..]).arg(&self.main_file_path);
+        // from the project, so that its dependencies can be required
+        match self.project_dir() {
+            Some(project_dir) => {
+                info!("[JS] running from {:?}", project_dir);
+                command
+                    .env("NODE_PATH", project_dir.join("node_modules"))
+                    .current_dir(project_dir);
+            }
+            None => {
+                command.current_dir(&self.js_work_dir);
+            }
+        }
+        let output = run_command(&mut command, &self.data)?;
+        if output.status.success() {
+            Ok(ExecutionResult::from_output(&output))
+        } else {
+            Err(self.error(&String::from_utf8_lossy(&output.stderr)))
+        }
+    }
+}
@@ -0,0 +1,250 @@
+#[derive(Debug, Clone)]
+#[allow(non_camel_case_types)]
+pub struct Java_original {
+    support_level: SupportLevel,
+    data: DataHolder,
+    code: String,
+
+    ///specific to java
+    java_work_dir: String,
+    ///javac wants the file to be named after its public class: <class_name>.java
+    main_file_path: String,
+    class_name: String,
+    line_offsets: LineOffsets,
+}
+
+lazy_static! {
+    /// the public class of the code, if it declares one
+    static ref PUBLIC_CLASS: Regex =
+        Regex::new(r"(?m)^\s*public\s+(?:(?:final|abstract|static)\s+)*class\s+(\w+)").unwrap();
+    /// any class declaration, public or not
+    static ref CLASS: Regex =
+        Regex::new(r"(?m)^\s*(?:(?:public|final|abstract|static)\s+)*class\s+(\w+)").unwrap();
+    /// javac locates errors as 'Main.java:LINE: error: message'
+    static ref JAVAC_ERROR: Regex = Regex::new(r"\w+\.java:(\d+): error: (.*)").unwrap();
+    /// the frames of a stack trace are like 'at Main.main(Main.java:LINE)'
+    static ref STACK_FRAME: Regex = Regex::new(r"at \S+\((\w+)\.java:(\d+)\)").unwrap();
+}
+
+impl Java_original {
+    /// the class to run, when the code is whole classes: the public one, else the one whose
+    /// main method it is
+    fn main_class(code: &str) -> Option<String> {
+        if let Some(captures) = PUBLIC_CLASS.captures(code) {
+            return Some(captures[1].to_string());
+        }
+        let main = code.find("static void main")?;
+        CLASS
+            .captures_iter(&code[..main])
+            .last()
+            .map(|captures| captures[1].to_string())
+    }
+
+    /// whether the code is a single expression (eg: `1 + 1`, `list.size()`), which is printed
+    fn is_expression(code: &str) -> bool {
+        let code = code.trim();
+        let keywords = [
+            "import ", "return", "if ", "if(", "for ", "for(", "while ", "while(", "try", "throw ",
+            "//", "/*",
+        ];
+        !code.is_empty()
+            && !code.contains('\n')
+            && !code.ends_with(';')
+            && !code.ends_with('{')
+            && !code.ends_with('}')
+            && !keywords.iter().any(|keyword| code.starts_with(keyword))
+    }
+
+    /// the error located at a line of the generated file, located in the buffer
+    fn located(&self, error: SniprunError, generated_line: &str) -> SniprunError {
+        match generated_line
+            .parse::<i64>()
+            .ok()
+            .and_then(|line| self.to_buffer_line(line))
+        {
+            Some(line) => error.at_line(line),
+            None => error,
+        }
+    }
+}
+
+impl Interpreter for Java_original {
+    declare_interpreter!(Java_original);
+
+    fn new_with_level(data: DataHolder, support_level: SupportLevel) -> Box<Java_original> {
+        let jwd = work_dir_path(&data, &Self::get_work_dir_name());
+        let mfp = jwd.clone() + "/Main.java";
+        Box::new(Java_original {
+            data,
+            support_level,
+            code: String::from(""),
+            java_work_dir: jwd,
+            main_file_path: mfp,
+            class_name: String::from("Main"),
+            line_offsets: LineOffsets::default(),
+        })
+    }
+
+    fn get_supported_languages() -> Vec<String> {
+        vec![String::from("java")]
+    }
+
+    fn get_generated_file(&self) -> Option<String> {
+        Some(self.main_file_path.clone())
+    }
+
+    fn get_max_support_level() -> SupportLevel {
+        SupportLevel::Bloc
+    }
+
+    fn get_capabilities() -> Capabilities {
+        Capabilities::SEPARATE_BUILD | Capabilities::STREAMING
+    }
+
+    fn get_line_offsets(&self) -> LineOffsets {
+        self.line_offsets.clone()
+    }
+
+    fn fetch_code(&mut self) -> Result<(), SniprunError> {
+        self.code = selected_code(&self.data, self.get_current_level());
+        Ok(())
+    }
+
+    fn add_boilerplate(&mut self) -> Result<(), SniprunError> {
+        self.line_offsets = LineOffsets::default();
+        // complete classes are compiled as-is
+        if let Some(class_name) = Self::main_class(&self.code) {
+            self.class_name = class_name;
+        } else {
+            if Self::is_expression(&self.code) {
+                self.code = format!("System.out.println({});", self.code.trim());
+            }
+            // bare statements go in the main method of a Main class; imports can't, they are
+            // moved before it (and blanked in place, so that the lines of the code don't move)
+            self.class_name = String::from("Main");
+            let is_import = |l: &str| l.trim_start().starts_with("import ");
+            let imports: String = self.code.lines().filter(|l| is_import(l)).collect();
+            let body: Vec<&str> = self
+                .code
+                .lines()
+                .map(|l| if is_import(l) { "" } else { l })
+                .collect();
+            self.code = imports
+                + "public class Main { public static void main(String[] args) throws Exception {\n"
+                + &body.join("\n")
+                + "\n}}\n";
+            self.line_offsets.prepend(1);
+        }
+        self.main_file_path = format!("{}/{}.java", self.java_work_dir, self.class_name);
+        Ok(())
+    }
+
+    fn build(&mut self) -> Result<(), SniprunError> {
+        write_code(&self.main_file_path, &self.code)?;
+        check_toolchain(&["javac"])?;
+        let output = Command::new("javac")
+            .arg("-d")
+            .arg(&self.java_work_dir)
+            .arg(&self.main_file_path)
+            .current_dir(&self.java_work_dir)
+            .output()?;
+        if output.status.success() {
+            return Ok(());
+        }
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        Err(match JAVAC_ERROR.captures(&stderr) {
+            Some(captures) => self.located(
+                SniprunError::CompilationError(captures[2].to_string()),
+                &captures[1],
+            ),
+            None => SniprunError::CompilationError(stderr),
+        })
+    }
+
+    fn execute(&mut self) -> Result<ExecutionResult, SniprunError> {
+        check_toolchain(&["java"])?;
+        let output = run_command(
+            Command::new("java")
+                .arg("-cp")
+                .arg(&self.java_work_dir)
+                .arg(&self.class_name)
+                .current_dir(&self.java_work_dir),
+            &self.data,
+        )?;
+        if output.status.success() {
+            return Ok(ExecutionResult::from_output(&output));
+        }
+        // an uncaught exception: located at the first frame of the code's class
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        let error = SniprunError::RuntimeError(stderr.clone());
+        Err(
+            match STACK_FRAME
+                .captures_iter(&stderr)
+                .find(|captures| captures[1] == self.class_name)
+            {
+                Some(captures) => self.located(error, &captures[2]),
+                None => error,
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod test_java_original {
+    use super::*;
+
+    fn run(code: &str) -> (Box<Java_original>, Result<ExecutionResult, SniprunError>) {
+        let mut interpreter: Box<Java_original> = DataHolder::prepared("java", code, &[]);
+        let result = interpreter.run();
+        (interpreter, result)
+    }
+
+    #[test]
+    fn the_file_is_named_after_the_public_class() {
+        let code = "class Helper {}\npublic final class Greeter {\n    public static void main(String[] args) {}\n}";
+        assert_eq!(Java_original::main_class(code), Some(String::from("Greeter")));
+        let code = "class Helper {}\nclass App {\n    public static void main(String[] args) {}\n}";
+        assert_eq!(Java_original::main_class(code), Some(String::from("App")));
+        assert_eq!(Java_original::main_class("int a = 1;"), None);
+    }
+
+    #[test]
+    fn bare_statements_run_in_a_main_class() {
+        if check_toolchain(&["javac"]).is_err() {
+            return;
+        }
+        let (interpreter, result) = run("import java.util.List;\nint a = List.of(1, 2).size();\nSystem.out.println(a + 1);");
+        assert!(interpreter.main_file_path.ends_with("/Main.java"));
+        assert_eq!(result.unwrap().stdout.trim(), "3");
+
+        let (_, result) = run("2 + 2");
+        assert_eq!(result.unwrap().stdout.trim(), "4");
+    }
+
+    #[test]
+    fn a_public_class_runs_as_is() {
+        if check_toolchain(&["javac"]).is_err() {
+            return;
+        }
+        let code = "public class Greeter {\n    public static void main(String[] args) {\n        System.out.println(\"hi\");\n    }\n}";
+        let (interpreter, result) = run(code);
+        assert!(interpreter.main_file_path.ends_with("/Greeter.java"));
+        assert_eq!(result.unwrap().stdout.trim(), "hi");
+    }
+
+    #[test]
+    fn errors_are_located() {
+        if check_toolchain(&["javac"]).is_err() {
+            return;
+        }
+        let (_, result) = run("int a = 1;\nint b = \"x\";");
+        let error = result.unwrap_err();
+        assert!(matches!(error.inner(), SniprunError::CompilationError(_)));
+        assert_eq!(error.line(), Some(2));
+
+        let (_, result) = run("int a = 1;\nthrow new RuntimeException(\"boom\");");
+        let error = result.unwrap_err();
+        assert!(matches!(error.inner(), SniprunError::RuntimeError(_)));
+        assert_eq!(error.line(), Some(2));
+    }
+}
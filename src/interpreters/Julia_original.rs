@@ -0,0 +1,417 @@
+#[derive(Debug, Clone)]
+#[allow(non_camel_case_types)]
+pub struct Julia_original {
+    support_level: SupportLevel,
+    data: DataHolder,
+    code: String,
+
+    ///specific to julia
+    julia_work_dir: String,
+    main_file_path: String,
+    ///run the code in a julia process kept between runs, rather than in a new one each time
+    persistent: bool,
+}
+
+/// Runs in the persistent julia process: includes the files whose path it reads on stdin, one
+/// per line, with their output written next to them (path.out, path.err), then answers "ok" or
+/// "error" on stdout
+const JULIA_SERVER: &str = r##"
+function serve()
+    while !eof(stdin)
+        path = readline(stdin)
+        failed = false
+        open(path * ".out", "w") do out
+            open(path * ".err", "w") do err
+                redirect_stdout(out) do
+                    redirect_stderr(err) do
+                        try
+                            Base.include(Main, path)
+                        catch e
+                            showerror(stderr, e, catch_backtrace())
+                            println(stderr)
+                            failed = true
+                        end
+                    end
+                end
+            end
+        end
+        println(failed ? "error" : "ok")
+        flush(stdout)
+    end
+end
+serve()
+"##;
+
+/// the persistent julia process, and the project it was started with
+struct JuliaProcess {
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+    project: Option<PathBuf>,
+}
+
+lazy_static! {
+    /// julia locates errors as 'main.jl:LINE' (in the stacktrace, or 'syntax: ... at main.jl:LINE')
+    static ref JULIA_LOCATION: Regex = Regex::new(r"main\.jl:(\d+)").unwrap();
+    /// the pipes to the persistent process, locked for the duration of a run
+    static ref JULIA_PROCESS: Mutex<Option<JuliaProcess>> = Mutex::new(None);
+    /// the persistent process itself, only locked to start or kill it so that it can be killed
+    /// while it runs some code
+    static ref JULIA_CHILD: Mutex<Option<Child>> = Mutex::new(None);
+}
+
+impl Julia_original {
+    /// whether a line of code is an expression whose value the REPL would show (but a script
+    /// does not)
+    fn is_expression(line: &str) -> bool {
+        let keywords = [
+            "end", "function", "using", "import", "export", "for", "while", "if", "else",
+            "elseif", "begin", "let", "try", "catch", "finally", "struct", "mutable", "abstract",
+            "module", "macro", "const", "global", "local", "return", "print", "println",
+        ];
+        let trimmed = line.trim_end();
+        let first_word = trimmed
+            .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .next()
+            .unwrap_or("");
+        if line.starts_with(char::is_whitespace)
+            || trimmed.is_empty()
+            || trimmed.starts_with('#')
+            || trimmed.starts_with('@')
+            // a ';' silences the value, in the REPL too
+            || trimmed.ends_with(';')
+            || trimmed.ends_with(',')
+            || keywords.contains(&first_word)
+        {
+            return false;
+        }
+        // an assignment has a '=' outside of brackets (that is not a comparison)
+        let line = ["==", "!=", "<=", ">=", "=>"]
+            .iter()
+            .fold(trimmed.to_string(), |line, operator| line.replace(operator, ""));
+        let mut depth = 0;
+        for c in line.chars() {
+            match c {
+                '(' | '[' | '{' => depth += 1,
+                ')' | ']' | '}' => depth -= 1,
+                '=' if depth == 0 => return false,
+                _ => (),
+            }
+        }
+        true
+    }
+
+    /// the directory of the closest Project.toml above the edited file, activated with --project
+    fn project(&self) -> Option<PathBuf> {
+        find_ancestor(Path::new(&self.data.filepath), "Project.toml")
+    }
+
+    fn julia_command(project: &Option<PathBuf>) -> Command {
+        let mut command = Command::new("julia");
+        command.arg("--startup-file=no");
+        if let Some(project) = project {
+            command.arg(format!("--project={}", project.display()));
+        }
+        command
+    }
+
+    /// the error julia reported, located in the buffer where it happened
+    fn error(&self, stderr: &str) -> SniprunError {
+        let error = if stderr.contains("ParseError") || stderr.contains("syntax:") {
+            SniprunError::CompilationError(stderr.to_string())
+        } else {
+            SniprunError::RuntimeError(stderr.to_string())
+        };
+        let line = JULIA_LOCATION
+            .captures(stderr)
+            .and_then(|captures| captures[1].parse::<i64>().ok())
+            .and_then(|line| self.to_buffer_line(line));
+        match line {
+            Some(line) => error.at_line(line),
+            None => error,
+        }
+    }
+
+    /// start the persistent process, with the project of the code
+    fn start_process(&self, project: Option<PathBuf>) -> Result<JuliaProcess, SniprunError> {
+        let server_path = self.julia_work_dir.clone() + "/server.jl";
+        write_code(&server_path, JULIA_SERVER)?;
+        info!("[JULIA] starting a persistent process (project: {:?})", project);
+        let mut command = Self::julia_command(&project);
+        // in its own process group, so that what the code starts is killed along with it
+        #[cfg(unix)]
+        std::os::unix::process::CommandExt::process_group(&mut command, 0);
+        let mut child = command
+            .arg(&server_path)
+            .current_dir(&self.julia_work_dir)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+        let process = match (child.stdin.take(), child.stdout.take()) {
+            (Some(stdin), Some(stdout)) => JuliaProcess {
+                stdin,
+                stdout: BufReader::new(stdout),
+                project,
+            },
+            _ => {
+                return Err(SniprunError::InternalError(String::from(
+                    "could not talk to the julia process",
+                )))
+            }
+        };
+        *lock(&JULIA_CHILD) = Some(child);
+        Ok(process)
+    }
+
+    /// whether the code runs in the persistent process (interpreter option `persistent`)
+    fn persistent(data: &DataHolder) -> bool {
+        data.interpreter_option(&Self::get_name(), "persistent")
+            .map(|p| config_bool(p, false))
+            .unwrap_or(false)
+    }
+
+    /// the answer of the persistent process to the code it was sent: it is killed (and its
+    /// state lost) if the code runs for longer than the timeout or if the run is cancelled,
+    /// like a process of run_command would be
+    fn wait_answer(&self, julia: &mut JuliaProcess) -> Result<String, SniprunError> {
+        let mut status = String::new();
+        let start = Instant::now();
+        let stopped = std::thread::scope(|scope| {
+            let reader = scope.spawn(|| julia.stdout.read_line(&mut status));
+            let stopped = loop {
+                if reader.is_finished() {
+                    break None;
+                }
+                if self.data.cancel.is_cancelled() {
+                    break Some(SniprunError::InternalError(String::from(
+                        "the run was cancelled",
+                    )));
+                }
+                if let Some(timeout) = self.data.timeout.filter(|t| start.elapsed() > *t) {
+                    break Some(SniprunError::TimeoutError(format!(
+                        "the code did not finish within {}s, the julia process was stopped and \
+                         its state is lost",
+                        timeout.as_secs_f64()
+                    )));
+                }
+                std::thread::sleep(Duration::from_millis(10));
+            };
+            if stopped.is_some() {
+                // the reader then reads the end of the output, and returns
+                Self::kill_process();
+            }
+            let _ = reader.join();
+            stopped
+        });
+        match stopped {
+            Some(error) => Err(error),
+            None => Ok(status),
+        }
+    }
+
+    fn execute_persistent(&self) -> Result<ExecutionResult, SniprunError> {
+        let project = self.project();
+        let mut process = lock(&JULIA_PROCESS);
+        // a process started for another project doesn't see this one's packages
+        if process.as_ref().is_some_and(|p| p.project != project) {
+            *process = None;
+            Self::kill_process();
+        }
+        if process.is_none() {
+            *process = Some(self.start_process(project)?);
+        }
+        let julia = process.as_mut().ok_or_else(|| {
+            SniprunError::InternalError(String::from("the julia process could not be started"))
+        })?;
+
+        let sent = writeln!(julia.stdin, "{}", self.main_file_path).and_then(|_| julia.stdin.flush());
+        let answer = match sent {
+            Ok(()) => self.wait_answer(julia),
+            Err(_) => Ok(String::new()),
+        };
+        let status = match answer {
+            Ok(status) if !status.is_empty() => status,
+            Ok(_) => {
+                // killed (by :SnipReset) or crashed: the next run starts a new one
+                *process = None;
+                return Err(SniprunError::RuntimeError(String::from(
+                    "the julia process stopped, its state is lost",
+                )));
+            }
+            Err(e) => {
+                *process = None;
+                return Err(e);
+            }
+        };
+        let stdout = std::fs::read_to_string(self.main_file_path.clone() + ".out")?;
+        let stderr = std::fs::read_to_string(self.main_file_path.clone() + ".err")?;
+        if status.trim() == "ok" {
+            Ok(ExecutionResult {
+                stdout,
+                stderr,
+                ..ExecutionResult::default()
+            })
+        } else {
+            Err(self.error(&stderr))
+        }
+    }
+
+    /// kill the persistent process, if any
+    fn kill_process() {
+        if let Some(mut child) = lock(&JULIA_CHILD).take() {
+            info!("[JULIA] killing the persistent process");
+            kill_process_group(&mut child);
+            let _ = child.wait();
+        }
+    }
+}
+
+impl Interpreter for Julia_original {
+    declare_interpreter!(Julia_original);
+
+    fn new_with_level(data: DataHolder, support_level: SupportLevel) -> Box<Julia_original> {
+        let jwd = work_dir_path(&data, &Self::get_work_dir_name());
+        let mfp = jwd.clone() + "/main.jl";
+        let persistent = Self::persistent(&data);
+        Box::new(Julia_original {
+            data,
+            support_level,
+            code: String::from(""),
+            julia_work_dir: jwd,
+            main_file_path: mfp,
+            persistent,
+        })
+    }
+
+    fn get_supported_languages() -> Vec<String> {
+        vec![String::from("julia"), String::from("jl")]
+    }
+
+    fn get_generated_file(&self) -> Option<String> {
+        Some(self.main_file_path.clone())
+    }
+
+    fn get_max_support_level() -> SupportLevel {
+        SupportLevel::Bloc
+    }
+
+    fn get_capabilities() -> Capabilities {
+        Capabilities::STREAMING | Capabilities::REPL | Capabilities::CLEANUP
+    }
+
+    fn negotiate_capabilities(data: &DataHolder) -> Capabilities {
+        // the persistent process writes the output to files, read once the code is done
+        if Self::persistent(data) {
+            Self::get_capabilities().without(Capabilities::STREAMING)
+        } else {
+            Self::get_capabilities()
+        }
+    }
+
+    fn clean_repl_memory(_data: &DataHolder) -> Result<(), SniprunError> {
+        Self::kill_process();
+        Ok(())
+    }
+
+    fn cleanup(&self) {
+        Self::kill_process();
+    }
+
+    fn fetch_code(&mut self) -> Result<(), SniprunError> {
+        self.code = selected_code(&self.data, self.get_current_level());
+        Ok(())
+    }
+
+    fn add_boilerplate(&mut self) -> Result<(), SniprunError> {
+        if !self.data.print_last_expression {
+            return Ok(());
+        }
+        // the last line is displayed in place, so that the lines of the code don't move
+        let mut lines: Vec<String> = self.code.lines().map(String::from).collect();
+        if let Some(last) = lines.iter().rposition(|l| !l.trim().is_empty()) {
+            if Self::is_expression(&lines[last]) {
+                lines[last] = format!(
+                    "let value = ({}); value === nothing || display(value); end",
+                    lines[last].trim()
+                );
+            }
+        }
+        self.code = lines.join("\n") + "\n";
+        Ok(())
+    }
+
+    fn build(&mut self) -> Result<(), SniprunError> {
+        write_code(&self.main_file_path, &self.code)
+    }
+
+    fn execute(&mut self) -> Result<ExecutionResult, SniprunError> {
+        check_toolchain(&["julia"])?;
+        if self.persistent {
+            return self.execute_persistent();
+        }
+        let output = run_command(
+            Self::julia_command(&self.project())
+                .arg(&self.main_file_path)
+                .current_dir(&self.julia_work_dir),
+            &self.data,
+        )?;
+        if output.status.success() {
+            Ok(ExecutionResult::from_output(&output))
+        } else {
+            Err(self.error(&String::from_utf8_lossy(&output.stderr)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_julia_original {
+    use super::*;
+
+    fn persistent_data() -> DataHolder {
+        let mut data = DataHolder::with_code_in_temp_dir("julia", "x = 1");
+        let option = (String::from("persistent"), neovim_lib::Value::from(true));
+        data.interpreter_options
+            .insert(Julia_original::get_name(), vec![option].into_iter().collect());
+        data
+    }
+
+    #[test]
+    fn the_persistent_process_doesnt_stream() {
+        let data = DataHolder::with_code_in_temp_dir("julia", "x = 1");
+        assert!(Julia_original::negotiate_capabilities(&data).contains(Capabilities::STREAMING));
+        let capabilities = Julia_original::negotiate_capabilities(&persistent_data());
+        assert!(!capabilities.contains(Capabilities::STREAMING));
+        assert!(capabilities.contains(Capabilities::REPL));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn code_running_for_too_long_stops_the_process() {
+        let mut data = persistent_data();
+        data.timeout = Some(Duration::from_millis(200));
+        let julia = Julia_original::new_with_level(data, SupportLevel::Bloc);
+        // stands for a julia process stuck in an infinite loop
+        let mut command = Command::new("sh");
+        std::os::unix::process::CommandExt::process_group(&mut command, 0);
+        let mut child = command
+            .arg("-c")
+            .arg("read path; sleep 30")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let mut process = JuliaProcess {
+            stdin: child.stdin.take().unwrap(),
+            stdout: BufReader::new(child.stdout.take().unwrap()),
+            project: None,
+        };
+        *lock(&JULIA_CHILD) = Some(child);
+        writeln!(process.stdin, "main.jl").unwrap();
+
+        let start = Instant::now();
+        let answer = julia.wait_answer(&mut process);
+        assert!(matches!(answer, Err(SniprunError::TimeoutError(_))));
+        assert!(start.elapsed() < Duration::from_secs(10));
+        assert!(lock(&JULIA_CHILD).is_none());
+    }
+}
@@ -0,0 +1,231 @@
+#[derive(Debug, Clone)]
+#[allow(non_camel_case_types)]
+pub struct Jupyter_original {
+    support_level: SupportLevel,
+    data: DataHolder,
+    code: String,
+
+    ///specific to jupyter
+    jupyter_work_dir: String,
+    main_file_path: String,
+    client_file_path: String,
+}
+
+lazy_static! {
+    /// color and cursor escape sequences, kernels use them in tracebacks
+    static ref ANSI_ESCAPE: Regex = Regex::new(r"\x1b\[[0-9;?]*[A-Za-z]").unwrap();
+}
+
+/// talks to the kernel with jupyter_client: runs the code (or restarts the kernel), prints what
+/// the kernel sends back as text. Exits with 1 if the code raised an error, 3 if the kernel
+/// does not answer
+const JUPYTER_CLIENT: &str = r##"import sys
+from jupyter_client import BlockingKernelClient
+
+connection_file, mode = sys.argv[1], sys.argv[2]
+client = BlockingKernelClient(connection_file=connection_file)
+client.load_connection_file()
+client.start_channels()
+try:
+    client.wait_for_ready(timeout=5)
+except RuntimeError as e:
+    print("kernel not responding: " + str(e), file=sys.stderr)
+    sys.exit(3)
+
+if mode == "restart":
+    client.shutdown(restart=True)
+    sys.exit(0)
+
+with open(sys.argv[3]) as code_file:
+    msg_id = client.execute(code_file.read(), store_history=False)
+failed = False
+while True:
+    msg = client.get_iopub_msg()
+    if msg["parent_header"].get("msg_id") != msg_id:
+        continue
+    kind, content = msg["msg_type"], msg["content"]
+    if kind == "stream":
+        (sys.stdout if content["name"] == "stdout" else sys.stderr).write(content["text"])
+    elif kind in ("execute_result", "display_data"):
+        data = content["data"]
+        if "text/plain" in data:
+            print(data["text/plain"])
+        else:
+            print("<" + ", ".join(data) + " output>")
+    elif kind == "error":
+        sys.stderr.write("\n".join(content["traceback"]) + "\n")
+        failed = True
+    elif kind == "status" and content["execution_state"] == "idle":
+        break
+sys.exit(1 if failed else 0)
+"##;
+
+impl Jupyter_original {
+    /// the connection file of the kernel to use: the one set in the options (a path, or a
+    /// file name in jupyter's runtime directory), or the most recent one of the runtime directory
+    fn connection_file(data: &DataHolder) -> Option<PathBuf> {
+        let runtime_dir = std::env::var_os("JUPYTER_RUNTIME_DIR")
+            .map(PathBuf::from)
+            .or_else(|| dirs::data_dir().map(|dir| dir.join("jupyter/runtime")))?;
+        if let Some(file) = data
+            .interpreter_option(&Self::get_name(), "connection_file")
+            .and_then(|f| f.as_str())
+        {
+            let file = Path::new(file);
+            return Some(if file.is_absolute() {
+                file.to_path_buf()
+            } else {
+                runtime_dir.join(file)
+            });
+        }
+        std::fs::read_dir(&runtime_dir)
+            .ok()?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .map(|name| name.starts_with("kernel-") && name.ends_with(".json"))
+                    .unwrap_or(false)
+            })
+            .max_by_key(|path| path.metadata().and_then(|m| m.modified()).ok())
+    }
+
+    /// the python that runs the client (interpreter option `python`, default python3)
+    fn python(data: &DataHolder) -> String {
+        data.interpreter_option(&Self::get_name(), "python")
+            .and_then(|p| p.as_str())
+            .unwrap_or("python3")
+            .to_string()
+    }
+
+    /// write the client next to the code and get the command running it
+    fn client_command(
+        data: &DataHolder,
+        client_file_path: &str,
+        connection_file: &Path,
+        mode: &str,
+    ) -> Result<Command, SniprunError> {
+        let python = Self::python(data);
+        check_toolchain(&[&python])?;
+        write_code(client_file_path, JUPYTER_CLIENT)?;
+        let mut command = Command::new(python);
+        command.arg(client_file_path).arg(connection_file).arg(mode);
+        Ok(command)
+    }
+
+    fn no_kernel_error() -> SniprunError {
+        SniprunError::PrerequisiteError(String::from(
+            "no running jupyter kernel found, start one (eg: 'jupyter console') or set the 'connection_file' option",
+        ))
+    }
+}
+
+impl Interpreter for Jupyter_original {
+    declare_interpreter!(Jupyter_original);
+
+    fn new_with_level(data: DataHolder, support_level: SupportLevel) -> Box<Jupyter_original> {
+        let jwd = work_dir_path(&data, &Self::get_work_dir_name());
+        let mfp = jwd.clone() + "/code";
+        let cfp = jwd.clone() + "/client.py";
+        Box::new(Jupyter_original {
+            data,
+            support_level,
+            code: String::from(""),
+            jupyter_work_dir: jwd,
+            main_file_path: mfp,
+            client_file_path: cfp,
+        })
+    }
+
+    fn get_supported_languages() -> Vec<String> {
+        vec![
+            String::from("python"),
+            String::from("python3"),
+            String::from("julia"),
+            String::from("r"),
+        ]
+    }
+
+    fn get_generated_file(&self) -> Option<String> {
+        Some(self.main_file_path.clone())
+    }
+
+    fn get_max_support_level() -> SupportLevel {
+        SupportLevel::Bloc
+    }
+
+    fn get_capabilities() -> Capabilities {
+        Capabilities::REPL
+    }
+
+    fn clean_repl_memory(data: &DataHolder) -> Result<(), SniprunError> {
+        // nothing to forget if no kernel runs
+        let connection_file = match Self::connection_file(data) {
+            Some(file) if file.is_file() => file,
+            _ => return Ok(()),
+        };
+        let client_file_path = create_work_dir(data, &Self::get_work_dir_name())? + "/client.py";
+        info!("[JUPYTER] restarting kernel {:?}", connection_file);
+        let output =
+            Self::client_command(data, &client_file_path, &connection_file, "restart")?.output()?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(SniprunError::RuntimeError(
+                String::from_utf8_lossy(&output.stderr).to_string(),
+            ))
+        }
+    }
+
+    fn fetch_code(&mut self) -> Result<(), SniprunError> {
+        self.code = selected_code(&self.data, self.get_current_level());
+        Ok(())
+    }
+
+    fn add_boilerplate(&mut self) -> Result<(), SniprunError> {
+        Ok(())
+    }
+
+    fn build(&mut self) -> Result<(), SniprunError> {
+        write_code(&self.main_file_path, &self.code)
+    }
+
+    fn execute(&mut self) -> Result<ExecutionResult, SniprunError> {
+        let connection_file = match Self::connection_file(&self.data) {
+            Some(file) if file.is_file() => file,
+            _ => return Err(Self::no_kernel_error()),
+        };
+        info!("[JUPYTER] running in kernel {:?}", connection_file);
+        let mut command = Self::client_command(
+            &self.data,
+            &self.client_file_path,
+            &connection_file,
+            "run",
+        )?;
+        let output = run_command(
+            command
+                .arg(&self.main_file_path)
+                .current_dir(&self.jupyter_work_dir),
+            &self.data,
+        )?;
+        let stderr = ANSI_ESCAPE
+            .replace_all(&String::from_utf8_lossy(&output.stderr), "")
+            .to_string();
+        match output.status.code() {
+            Some(0) => {
+                let mut result = ExecutionResult::from_output(&output);
+                result.stdout = ANSI_ESCAPE.replace_all(&result.stdout, "").to_string();
+                result.stderr = stderr;
+                Ok(result)
+            }
+            Some(3) => Err(SniprunError::PrerequisiteError(stderr)),
+            _ if stderr.contains("No module named 'jupyter_client'") => {
+                Err(SniprunError::PrerequisiteError(String::from(
+                    "the jupyter_client python module is needed to talk to the kernel",
+                )))
+            }
+            _ => Err(SniprunError::RuntimeError(stderr)),
+        }
+    }
+}
@@ -0,0 +1,311 @@
+#[derive(Debug, Clone)]
+#[allow(non_camel_case_types)]
+pub struct Kotlin_original {
+    support_level: SupportLevel,
+    data: DataHolder,
+    code: String,
+
+    ///specific to kotlin
+    kotlin_work_dir: String,
+    main_file_path: String,
+    ///the jar of the code, named by a hash of the code: kotlinc takes seconds to start, the
+    ///same code is only compiled once
+    jar_path: String,
+    ///run the code as a script with `kotlinc -script` rather than compiling a jar: quicker
+    ///for small snippets that change at each run
+    script: bool,
+    line_offsets: LineOffsets,
+}
+
+/// how many compiled jars are kept, the oldest ones are removed first
+const KEPT_JARS: usize = 20;
+
+lazy_static! {
+    /// the code defines its own entry point
+    static ref KOTLIN_MAIN: Regex = Regex::new(r"(?m)^\s*fun\s+main\s*\(").unwrap();
+    /// kotlinc locates errors as 'main.kt:LINE:COLUMN: error: message' (or
+    /// 'main.kt:(LINE, COLUMN): error: message' for older versions), 'main.kts' for scripts
+    static ref KOTLINC_ERROR: Regex =
+        Regex::new(r"main\.kts?:(?:(\d+):\d+|\((\d+), ?\d+\)): error: ").unwrap();
+    /// the frames of a stack trace are like 'at MainKt.main(main.kt:LINE)'
+    static ref KOTLIN_STACK_FRAME: Regex = Regex::new(r"\(main\.kts?:(\d+)\)").unwrap();
+    /// any line of the generated file mentioned in an output
+    static ref KOTLIN_LOCATION: Regex = Regex::new(r"(main\.kts?(?::\(|:|\())(\d+)").unwrap();
+}
+
+impl Kotlin_original {
+    fn is_import(line: &str) -> bool {
+        line.trim_start().starts_with("import ")
+    }
+
+    /// the output with the lines of the code rather than the ones of the generated file
+    fn remap_lines(&self, output: &str) -> String {
+        KOTLIN_LOCATION
+            .replace_all(output, |captures: &regex::Captures| {
+                match captures[2]
+                    .parse::<i64>()
+                    .ok()
+                    .and_then(|line| self.line_offsets.code_line(line))
+                {
+                    Some(line) => format!("{}{}", &captures[1], line),
+                    None => captures[0].to_string(),
+                }
+            })
+            .to_string()
+    }
+
+    /// the error of the output, located at the first line of the generated file the regex
+    /// captures
+    fn located(
+        &self,
+        error: fn(String) -> SniprunError,
+        regex: &Regex,
+        output: &str,
+    ) -> SniprunError {
+        let error = error(self.remap_lines(output));
+        let line = regex
+            .captures(output)
+            .and_then(|captures| captures.iter().skip(1).flatten().next())
+            .and_then(|line| line.as_str().parse::<i64>().ok())
+            .and_then(|line| self.to_buffer_line(line));
+        match line {
+            Some(line) => error.at_line(line),
+            None => error,
+        }
+    }
+}
+
+impl Interpreter for Kotlin_original {
+    declare_interpreter!(Kotlin_original);
+
+    fn new_with_level(data: DataHolder, support_level: SupportLevel) -> Box<Kotlin_original> {
+        let kwd = work_dir_path(&data, &Self::get_work_dir_name());
+        let script = data
+            .interpreter_option(&Self::get_name(), "script")
+            .map(|v| config_bool(v, false))
+            .unwrap_or(false);
+        let mfp = kwd.clone() + if script { "/main.kts" } else { "/main.kt" };
+        Box::new(Kotlin_original {
+            data,
+            support_level,
+            code: String::from(""),
+            kotlin_work_dir: kwd,
+            main_file_path: mfp,
+            jar_path: String::new(),
+            script,
+            line_offsets: LineOffsets::default(),
+        })
+    }
+
+    fn get_supported_languages() -> Vec<String> {
+        vec![String::from("kotlin"), String::from("kt")]
+    }
+
+    fn get_generated_file(&self) -> Option<String> {
+        Some(self.main_file_path.clone())
+    }
+
+    fn get_max_support_level() -> SupportLevel {
+        SupportLevel::Bloc
+    }
+
+    fn get_capabilities() -> Capabilities {
+        Capabilities::SEPARATE_BUILD | Capabilities::STREAMING
+    }
+
+    fn get_line_offsets(&self) -> LineOffsets {
+        self.line_offsets.clone()
+    }
+
+    fn fetch_code(&mut self) -> Result<(), SniprunError> {
+        self.code = selected_code(&self.data, self.get_current_level());
+        Ok(())
+    }
+
+    fn add_boilerplate(&mut self) -> Result<(), SniprunError> {
+        self.line_offsets = LineOffsets::default();
+        if self.script {
+            // a script runs its statements as they are
+            return Ok(());
+        }
+        if !KOTLIN_MAIN.is_match(&self.code) {
+            // the imports can't be inside main(): they are moved before it, and blanked in
+            // place so that the lines of the code don't move
+            let imports: Vec<&str> = self
+                .code
+                .lines()
+                .filter(|l| Self::is_import(l))
+                .map(|l| l.trim())
+                .collect();
+            let body: Vec<&str> = self
+                .code
+                .lines()
+                .map(|l| if Self::is_import(l) { "" } else { l })
+                .collect();
+            self.code = imports.join("; ") + "\nfun main() {\n" + &body.join("\n") + "\n}\n";
+            self.line_offsets.prepend(2);
+        }
+        self.jar_path = format!(
+            "{}/{}.jar",
+            self.kotlin_work_dir,
+            crate::cache::key(&self.code)
+        );
+        Ok(())
+    }
+
+    fn build(&mut self) -> Result<(), SniprunError> {
+        write_code(&self.main_file_path, &self.code)?;
+        if self.script {
+            return Ok(());
+        }
+        if Path::new(&self.jar_path).is_file() {
+            info!("[KOTLIN] reusing {}", self.jar_path);
+            return Ok(());
+        }
+
+        check_toolchain(&["kotlinc"])?;
+        // compiled aside, so that an interrupted compilation doesn't leave a broken jar
+        let partial_jar = self.kotlin_work_dir.clone() + "/partial.jar.tmp";
+        let output = Command::new("kotlinc")
+            .arg(&self.main_file_path)
+            .arg("-include-runtime")
+            .arg("-d")
+            .arg(&partial_jar)
+            .current_dir(&self.kotlin_work_dir)
+            .output()?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(self.located(
+                SniprunError::CompilationError,
+                &KOTLINC_ERROR,
+                &stderr,
+            ));
+        }
+        std::fs::rename(&partial_jar, &self.jar_path)?;
+        remove_oldest_files(&self.kotlin_work_dir, "jar", KEPT_JARS);
+        Ok(())
+    }
+
+    fn execute(&mut self) -> Result<ExecutionResult, SniprunError> {
+        let mut command = if self.script {
+            check_toolchain(&["kotlinc"])?;
+            let mut command = Command::new("kotlinc");
+            command.arg("-script").arg(&self.main_file_path);
+            command
+        } else {
+            check_toolchain(&["java"])?;
+            let mut command = Command::new("java");
+            command.arg("-jar").arg(&self.jar_path);
+            command
+        };
+        let output = run_command(command.current_dir(&self.kotlin_work_dir), &self.data)?;
+        if output.status.success() {
+            return Ok(ExecutionResult::from_output(&output));
+        }
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        // a script is compiled when it runs
+        if self.script && KOTLINC_ERROR.is_match(&stderr) {
+            Err(self.located(
+                SniprunError::CompilationError,
+                &KOTLINC_ERROR,
+                &stderr,
+            ))
+        } else {
+            Err(self.located(
+                SniprunError::RuntimeError,
+                &KOTLIN_STACK_FRAME,
+                &stderr,
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_kotlin_original {
+    use super::*;
+    use std::fs;
+
+    fn kotlin(code: &str) -> Box<Kotlin_original> {
+        DataHolder::prepared("kotlin", code, &[])
+    }
+
+    #[test]
+    fn the_jar_is_named_after_the_code() {
+        let jar = |interpreter: &Kotlin_original| {
+            let path = Path::new(&interpreter.jar_path);
+            path.file_name().unwrap().to_string_lossy().to_string()
+        };
+        let first = kotlin("println(1)");
+        assert_eq!(jar(&first), jar(&kotlin("println(1)")));
+        assert_ne!(jar(&first), jar(&kotlin("println(2)")));
+        assert!(first.jar_path.starts_with(&first.kotlin_work_dir));
+        assert!(first.code.contains("fun main() {\nprintln(1)\n}"));
+        assert_eq!(first.to_buffer_line(3), Some(1));
+    }
+
+    #[test]
+    fn a_compiled_jar_is_run_without_compiling_again() {
+        if check_toolchain(&["javac"]).is_err() || check_toolchain(&["jar"]).is_err() {
+            return;
+        }
+        let mut interpreter = kotlin("println(\"compiled\")");
+        // the jar kotlinc would have made, made without it
+        let dir = Path::new(&interpreter.kotlin_work_dir);
+        fs::write(
+            dir.join("MainKt.java"),
+            "public class MainKt { public static void main(String[] a) { System.out.print(\"cached\"); } }",
+        )
+        .unwrap();
+        for command in [
+            vec!["javac", "MainKt.java"],
+            vec!["jar", "cfe", &interpreter.jar_path, "MainKt", "MainKt.class"],
+        ] {
+            let status = Command::new(command[0])
+                .args(&command[1..])
+                .current_dir(dir)
+                .status()
+                .unwrap();
+            assert!(status.success());
+        }
+
+        interpreter.build().unwrap();
+        assert_eq!(interpreter.execute().unwrap().stdout, "cached");
+    }
+
+    #[test]
+    fn only_the_newest_jars_are_kept() {
+        let interpreter = kotlin("println(1)");
+        let dir = Path::new(&interpreter.kotlin_work_dir);
+        for i in 0..KEPT_JARS + 3 {
+            fs::write(dir.join(format!("{}.jar", i)), "").unwrap();
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        remove_oldest_files(&interpreter.kotlin_work_dir, "jar", KEPT_JARS);
+        let jars = fs::read_dir(dir)
+            .unwrap()
+            .filter(|e| e.as_ref().unwrap().path().extension() == Some("jar".as_ref()))
+            .count();
+        assert_eq!(jars, KEPT_JARS);
+        assert!(!dir.join("0.jar").exists());
+        assert!(dir.join(format!("{}.jar", KEPT_JARS + 2)).exists());
+    }
+
+    #[test]
+    fn errors_are_located_in_the_code() {
+        let interpreter = kotlin("val a = 1\nval b: String = a");
+        for stderr in [
+            "main.kt:4:17: error: type mismatch",
+            "main.kt:(4, 17): error: type mismatch",
+        ] {
+            let error = interpreter.located(SniprunError::CompilationError, &KOTLINC_ERROR, stderr);
+            assert_eq!(error.line(), Some(2));
+            assert!(error.to_string().contains("main.kt") && error.to_string().contains("2"));
+        }
+        let error = interpreter.located(
+            SniprunError::RuntimeError,
+            &KOTLIN_STACK_FRAME,
+            "Exception in thread \"main\"\n\tat MainKt.main(main.kt:3)",
+        );
+        assert_eq!(error.line(), Some(1));
+    }
+}
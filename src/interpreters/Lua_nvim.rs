@@ -0,0 +1,84 @@
+/// Lua_original, with neovim's lua as the default backend: the code can use `vim.api` and
+/// change the buffers
+#[derive(Debug, Clone)]
+#[allow(non_camel_case_types)]
+pub struct Lua_nvim {
+    lua: Lua_original,
+}
+
+impl Lua_nvim {
+    /// backend asked by the user, neovim by default
+    fn select_backend(data: &DataHolder) -> String {
+        data.interpreter_option(&Self::get_name(), "backend")
+            .and_then(|b| b.as_str())
+            .filter(|b| !b.is_empty())
+            .unwrap_or("nvim")
+            .to_string()
+    }
+}
+
+impl Interpreter for Lua_nvim {
+    fn new_with_level(data: DataHolder, support_level: SupportLevel) -> Box<Lua_nvim> {
+        let backend = Self::select_backend(&data);
+        Box::new(Lua_nvim {
+            lua: Lua_original::with_backend(
+                data,
+                support_level,
+                backend,
+                &Self::get_work_dir_name(),
+            ),
+        })
+    }
+
+    fn get_name() -> String {
+        String::from("Lua_nvim")
+    }
+
+    fn get_current_level(&self) -> SupportLevel {
+        self.lua.get_current_level()
+    }
+
+    fn set_current_level(&mut self, level: SupportLevel) {
+        self.lua.set_current_level(level)
+    }
+
+    fn get_data(&self) -> DataHolder {
+        self.lua.get_data()
+    }
+
+    fn get_supported_languages() -> Vec<String> {
+        Lua_original::get_supported_languages()
+    }
+
+    fn get_generated_file(&self) -> Option<String> {
+        self.lua.get_generated_file()
+    }
+
+    fn get_max_support_level() -> SupportLevel {
+        Lua_original::get_max_support_level()
+    }
+
+    fn get_capabilities() -> Capabilities {
+        Lua_original::get_capabilities()
+    }
+
+    fn negotiate_capabilities(data: &DataHolder) -> Capabilities {
+        Lua_original::capabilities_with(&Self::select_backend(data))
+    }
+
+    fn fetch_code(&mut self) -> Result<(), SniprunError> {
+        self.lua.fetch_code()
+    }
+
+    fn add_boilerplate(&mut self) -> Result<(), SniprunError> {
+        self.lua.add_boilerplate()
+    }
+
+    fn build(&mut self) -> Result<(), SniprunError> {
+        self.lua.build()
+    }
+
+    fn execute(&mut self) -> Result<ExecutionResult, SniprunError> {
+        self.lua.execute()
+    }
+}
@@ -0,0 +1,296 @@
+#[derive(Debug, Clone)]
+#[allow(non_camel_case_types)]
+pub struct Lua_original {
+    support_level: SupportLevel,
+    data: DataHolder,
+    code: String,
+
+    ///specific to lua
+    main_file_path: String,
+    ///either a lua binary ("lua", "luajit") or "nvim" to run inside neovim's own lua
+    backend: String,
+}
+
+/// Runs the code (given as argument) inside neovim, capturing what it prints (or writes with
+/// io.write) and the value it returns. Neovim can't do anything else meanwhile: if a timeout
+/// is given (in seconds, 0 for none), the code is stopped with an error once it's over
+const LUA_NVIM_WRAPPER: &str = r##"
+local code, timeout = ...
+local chunk, load_error = loadstring(code, "=sniprun")
+if not chunk then
+  return {false, "compile", load_error}
+end
+local output = {}
+local original_print, original_write = print, io.write
+print = function(...)
+  local parts = {}
+  for i = 1, select("#", ...) do
+    parts[#parts + 1] = tostring((select(i, ...)))
+  end
+  output[#output + 1] = table.concat(parts, "\t") .. "\n"
+end
+io.write = function(...)
+  for i = 1, select("#", ...) do
+    output[#output + 1] = tostring((select(i, ...)))
+  end
+end
+if timeout > 0 then
+  -- the hooks aren't called in compiled code
+  if jit then
+    jit.off(chunk, true)
+  end
+  local deadline = vim.loop.hrtime() + timeout * 1e9
+  debug.sethook(function()
+    if vim.loop.hrtime() > deadline then
+      debug.sethook()
+      error("timeout: the code did not finish within " .. timeout .. "s", 2)
+    end
+  end, "", 10000)
+end
+local ok, result = xpcall(chunk, debug.traceback)
+debug.sethook()
+print, io.write = original_print, original_write
+if not ok then
+  local kind = tostring(result):find("timeout: the code did not finish") and "timeout" or "runtime"
+  return {false, kind, tostring(result)}
+end
+if result ~= nil then
+  output[#output + 1] = vim.inspect(result) .. "\n"
+end
+return {true, table.concat(output)}
+"##;
+
+lazy_static! {
+    /// lua locates errors as 'sniprun:LINE: message' (the name of the chunk run inside neovim)
+    /// or '/path/to/main.lua:LINE: message'
+    static ref LUA_LOCATION: Regex = Regex::new(r"(?:^|\s)(?:sniprun|\S*main\.lua):(\d+):").unwrap();
+}
+
+impl Lua_original {
+    /// backend asked by the user, or the first lua binary found, or neovim's lua
+    fn select_backend(data: &DataHolder) -> String {
+        if let Some(backend) = data
+            .interpreter_option(&Self::get_name(), "backend")
+            .and_then(|b| b.as_str())
+        {
+            return backend.to_string();
+        }
+        ["lua", "luajit"]
+            .iter()
+            .find(|binary| check_toolchain(&[binary]).is_ok())
+            .map(|binary| binary.to_string())
+            .unwrap_or_else(|| String::from("nvim"))
+    }
+
+    /// an interpreter running the code with that backend, in the work directory of the given
+    /// name. Shared with Lua_nvim, that only chooses the backend differently
+    fn with_backend(
+        data: DataHolder,
+        support_level: SupportLevel,
+        backend: String,
+        dir_name: &str,
+    ) -> Self {
+        let lwd = work_dir_path(&data, dir_name);
+        let mfp = lwd + "/main.lua";
+        Lua_original {
+            data,
+            support_level,
+            code: String::from(""),
+            main_file_path: mfp,
+            backend,
+        }
+    }
+
+    /// what the interpreter can do with that backend: the code run inside neovim returns its
+    /// output at the end
+    fn capabilities_with(backend: &str) -> Capabilities {
+        if backend == "nvim" {
+            Capabilities::NONE
+        } else {
+            Capabilities::STREAMING
+        }
+    }
+
+    /// the error lua reported, located in the buffer where it happened
+    fn error(error: SniprunError, message: &str, data: &DataHolder) -> SniprunError {
+        let line = LUA_LOCATION
+            .captures(message)
+            .and_then(|captures| captures[1].parse::<i64>().ok())
+            .and_then(|line| data.buffer_line(line));
+        match line {
+            Some(line) => error.at_line(line),
+            None => error,
+        }
+    }
+
+    /// run the code inside neovim, from the thread of the run: the neovim session is only
+    /// locked for the call, that neovim answers while sniprun waits
+    fn execute_in_nvim(&self) -> Result<ExecutionResult, SniprunError> {
+        let nvim = self.data.nvim_instance.get().ok_or_else(|| {
+            SniprunError::InterpreterLimitationError(String::from(
+                "there is no neovim instance to run the code in",
+            ))
+        })?;
+        let timeout = self.data.timeout.map_or(0.0, |t| t.as_secs_f64());
+        let result = lock(nvim)
+            .execute_lua(
+                LUA_NVIM_WRAPPER,
+                vec![
+                    neovim_lib::Value::from(self.code.as_str()),
+                    neovim_lib::Value::from(timeout),
+                ],
+            )
+            .map_err(|e| SniprunError::RuntimeError(e.to_string()))?;
+
+        let result = result.as_array().cloned().unwrap_or_default();
+        let text = |i: usize| {
+            result
+                .get(i)
+                .and_then(|v| v.as_str())
+                .unwrap_or("")
+                .to_string()
+        };
+        let error = match (result.first().and_then(|v| v.as_bool()), text(1).as_str()) {
+            (Some(true), _) => return Ok(ExecutionResult::new(text(1))),
+            (Some(false), "compile") => SniprunError::CompilationError(text(2)),
+            (Some(false), "timeout") => SniprunError::TimeoutError(text(2)),
+            (Some(false), _) => SniprunError::RuntimeError(text(2)),
+            _ => return Err(SniprunError::InterpreterError),
+        };
+        Err(Self::error(error, &text(2), &self.data))
+    }
+
+    /// run the file with the lua binary of the backend
+    fn execute_with_binary(&self) -> Result<ExecutionResult, SniprunError> {
+        check_toolchain(&[&self.backend])?;
+        let output = run_command(
+            Command::new(&self.backend).arg(&self.main_file_path),
+            &self.data,
+        )?;
+        if output.status.success() {
+            Ok(ExecutionResult::from_output(&output))
+        } else {
+            let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+            Err(Self::error(
+                SniprunError::RuntimeError(stderr.clone()),
+                &stderr,
+                &self.data,
+            ))
+        }
+    }
+}
+
+impl Interpreter for Lua_original {
+    declare_interpreter!(Lua_original);
+
+    fn new_with_level(data: DataHolder, support_level: SupportLevel) -> Box<Lua_original> {
+        let backend = Self::select_backend(&data);
+        Box::new(Self::with_backend(
+            data,
+            support_level,
+            backend,
+            &Self::get_work_dir_name(),
+        ))
+    }
+
+    fn get_supported_languages() -> Vec<String> {
+        vec![String::from("lua")]
+    }
+
+    fn get_generated_file(&self) -> Option<String> {
+        if self.backend == "nvim" {
+            None
+        } else {
+            Some(self.main_file_path.clone())
+        }
+    }
+
+    fn get_max_support_level() -> SupportLevel {
+        SupportLevel::Bloc
+    }
+
+    fn get_capabilities() -> Capabilities {
+        Capabilities::STREAMING
+    }
+
+    fn negotiate_capabilities(data: &DataHolder) -> Capabilities {
+        Self::capabilities_with(&Self::select_backend(data))
+    }
+
+    fn fetch_code(&mut self) -> Result<(), SniprunError> {
+        self.code = selected_code(&self.data, self.get_current_level());
+        Ok(())
+    }
+
+    fn add_boilerplate(&mut self) -> Result<(), SniprunError> {
+        Ok(())
+    }
+
+    fn build(&mut self) -> Result<(), SniprunError> {
+        if self.backend != "nvim" {
+            write_code(&self.main_file_path, &self.code)?;
+        }
+        Ok(())
+    }
+
+    fn execute(&mut self) -> Result<ExecutionResult, SniprunError> {
+        info!("[LUA] running with backend {}", self.backend);
+        if self.backend == "nvim" {
+            self.execute_in_nvim()
+        } else {
+            self.execute_with_binary()
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_lua_original {
+    use super::*;
+
+    fn with_backend(interpreter: &str, backend: &str) -> DataHolder {
+        let mut data = DataHolder::with_code_in_temp_dir("lua", "print(1)");
+        let option = (String::from("backend"), neovim_lib::Value::from(backend));
+        data.interpreter_options
+            .insert(interpreter.to_string(), vec![option].into_iter().collect());
+        data
+    }
+
+    #[test]
+    fn only_the_binaries_stream() {
+        let streams = |capabilities: Capabilities| capabilities.contains(Capabilities::STREAMING);
+        let data = DataHolder::with_code_in_temp_dir("lua", "print(1)");
+        assert!(!streams(Lua_nvim::negotiate_capabilities(&data)));
+        let data = with_backend("Lua_nvim", "luajit");
+        assert!(streams(Lua_nvim::negotiate_capabilities(&data)));
+        let data = with_backend("Lua_original", "nvim");
+        assert!(!streams(Lua_original::negotiate_capabilities(&data)));
+        let data = with_backend("Lua_original", "lua");
+        assert!(streams(Lua_original::negotiate_capabilities(&data)));
+    }
+
+    #[test]
+    fn both_share_the_code_but_not_the_work_dir() {
+        let data = with_backend("Lua_nvim", "lua");
+        let nvim = Lua_nvim::new(data.clone());
+        let original = Lua_original::new(with_backend("Lua_original", "lua"));
+        assert_eq!(nvim.lua.backend, "lua");
+        assert_ne!(nvim.get_generated_file(), original.get_generated_file());
+        assert!(nvim
+            .get_generated_file()
+            .unwrap()
+            .starts_with(&work_dir_path(&data, &Lua_nvim::get_work_dir_name())));
+    }
+
+    #[test]
+    fn errors_are_located() {
+        let mut data = DataHolder::with_code_in_temp_dir("lua", "local a = 1\nerror('x')");
+        data.ranges = vec![[10, 11]];
+        let located = |message: &str| {
+            Lua_original::error(SniprunError::RuntimeError(message.to_string()), message, &data)
+                .line()
+        };
+        assert_eq!(located("sniprun:2: x"), Some(11));
+        assert_eq!(located("lua: /tmp/lua_original/main.lua:1: x"), Some(10));
+        assert_eq!(located("attempt to call a nil value"), None);
+    }
+}
@@ -0,0 +1,289 @@
+#[derive(Debug, Clone)]
+#[allow(non_camel_case_types)]
+pub struct Nim_original {
+    support_level: SupportLevel,
+    data: DataHolder,
+    code: String,
+
+    ///specific to nim
+    nim_work_dir: String,
+    main_file_path: String,
+    ///the binary of the code, named by a hash of the code and flags: the same code is only
+    ///compiled once
+    bin_path: String,
+    ///flags given to the compiler, eg: ["-d:release"]
+    flags: Vec<String>,
+    ///the backend the code is compiled with: "c", "cpp" or "objc"
+    backend: String,
+}
+
+/// how many compiled binaries are kept, the oldest ones are removed first
+const KEPT_BINARIES: usize = 20;
+
+lazy_static! {
+    /// nim locates compilation errors as 'main.nim(LINE, COLUMN) Error: message'
+    static ref NIM_COMPILATION_ERROR: Regex =
+        Regex::new(r"main\.nim\((\d+), ?\d+\) Error: ").unwrap();
+    /// the frames of a traceback are like 'main.nim(LINE) procedure', the last one is where
+    /// the exception was raised
+    static ref NIM_FRAME: Regex = Regex::new(r"main\.nim\((\d+)\)").unwrap();
+}
+
+impl Nim_original {
+    /// the flags the user gives to the compiler (interpreter option `flags`)
+    fn flags(data: &DataHolder) -> Vec<String> {
+        data.interpreter_option(&Self::get_name(), "flags")
+            .and_then(|f| f.as_array())
+            .map(|f| {
+                f.iter()
+                    .filter_map(|flag| flag.as_str().map(String::from))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// whether a line of code is a bare expression, whose value is worth echoing
+    fn is_expression(line: &str) -> bool {
+        let keywords = [
+            "echo", "let", "var", "const", "proc", "func", "method", "iterator", "template",
+            "macro", "type", "import", "from", "include", "if", "elif", "else", "when", "case",
+            "of", "for", "while", "block", "try", "except", "finally", "return", "discard",
+            "raise", "yield", "break", "continue", "assert", "doAssert",
+        ];
+        let line = line.trim();
+        let first_word = line
+            .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .next()
+            .unwrap_or("");
+        if line.is_empty()
+            || line.starts_with('#')
+            || line.ends_with(':')
+            || keywords.contains(&first_word)
+        {
+            return false;
+        }
+        // an assignment has a '=' outside of brackets (that is not a comparison)
+        let line = ["==", "!=", "<=", ">="]
+            .iter()
+            .fold(line.to_string(), |line, operator| line.replace(operator, ""));
+        let mut depth = 0;
+        for c in line.chars() {
+            match c {
+                '(' | '[' | '{' => depth += 1,
+                ')' | ']' | '}' => depth -= 1,
+                '=' if depth == 0 => return false,
+                _ => (),
+            }
+        }
+        true
+    }
+
+    /// the report of a failed compilation, without the hints and the C compiler commands nim
+    /// prints along with the errors
+    fn compiler_report(output: &std::process::Output) -> String {
+        let report = String::from_utf8_lossy(&output.stdout).to_string()
+            + &String::from_utf8_lossy(&output.stderr);
+        report
+            .lines()
+            .filter(|l| !l.starts_with("Hint: ") && !l.starts_with("CC: ") && !l.contains(" Hint: "))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// the error, located at the line the regex captures (the last one found if `last`)
+    fn located(&self, error: SniprunError, regex: &Regex, output: &str, last: bool) -> SniprunError {
+        let mut lines = regex
+            .captures_iter(output)
+            .filter_map(|captures| captures[1].parse::<i64>().ok());
+        let line = if last { lines.last() } else { lines.next() };
+        match line.and_then(|line| self.to_buffer_line(line)) {
+            Some(line) => error.at_line(line),
+            None => error,
+        }
+    }
+}
+
+impl Interpreter for Nim_original {
+    declare_interpreter!(Nim_original);
+
+    fn new_with_level(data: DataHolder, support_level: SupportLevel) -> Box<Nim_original> {
+        let nwd = work_dir_path(&data, &Self::get_work_dir_name());
+        let mfp = nwd.clone() + "/main.nim";
+        let flags = Self::flags(&data);
+        let backend = data
+            .interpreter_option(&Self::get_name(), "backend")
+            .and_then(|b| b.as_str())
+            .unwrap_or("c")
+            .to_string();
+        Box::new(Nim_original {
+            data,
+            support_level,
+            code: String::from(""),
+            nim_work_dir: nwd,
+            main_file_path: mfp,
+            bin_path: String::new(),
+            flags,
+            backend,
+        })
+    }
+
+    fn get_supported_languages() -> Vec<String> {
+        vec![String::from("nim")]
+    }
+
+    fn get_generated_file(&self) -> Option<String> {
+        Some(self.main_file_path.clone())
+    }
+
+    fn get_max_support_level() -> SupportLevel {
+        SupportLevel::Bloc
+    }
+
+    fn get_capabilities() -> Capabilities {
+        Capabilities::SEPARATE_BUILD | Capabilities::STREAMING
+    }
+
+    fn fetch_code(&mut self) -> Result<(), SniprunError> {
+        self.code = selected_code(&self.data, self.get_current_level());
+        Ok(())
+    }
+
+    fn add_boilerplate(&mut self) -> Result<(), SniprunError> {
+        // nim runs the top-level statements of a module, only a single expression needs to be
+        // echoed to show its value
+        if self.code.trim().lines().count() == 1 && Self::is_expression(&self.code) {
+            self.code = format!("echo {}", self.code.trim());
+        }
+        self.bin_path = format!(
+            "{}/{}.bin",
+            self.nim_work_dir,
+            crate::cache::key(&(&self.code, &self.flags, &self.backend))
+        );
+        Ok(())
+    }
+
+    fn build(&mut self) -> Result<(), SniprunError> {
+        write_code(&self.main_file_path, &self.code)?;
+        if Path::new(&self.bin_path).is_file() {
+            info!("[NIM] reusing {}", self.bin_path);
+            return Ok(());
+        }
+
+        check_toolchain(&["nim"])?;
+        // nim's own cache goes in a directory of its own, next to the code
+        let output = Command::new("nim")
+            .arg(&self.backend)
+            .arg("--hints:off")
+            .arg(format!("--nimcache:{}/nimcache", self.nim_work_dir))
+            .arg(format!("--out:{}", self.bin_path))
+            .args(&self.flags)
+            .arg(&self.main_file_path)
+            .current_dir(&self.nim_work_dir)
+            .output()?;
+        if !output.status.success() {
+            let report = Self::compiler_report(&output);
+            return Err(self.located(
+                SniprunError::CompilationError(report.clone()),
+                &NIM_COMPILATION_ERROR,
+                &report,
+                false,
+            ));
+        }
+        remove_oldest_files(&self.nim_work_dir, "bin", KEPT_BINARIES);
+        Ok(())
+    }
+
+    fn execute(&mut self) -> Result<ExecutionResult, SniprunError> {
+        let output = run_command(
+            Command::new(&self.bin_path).current_dir(&self.nim_work_dir),
+            &self.data,
+        )?;
+        if output.status.success() {
+            Ok(ExecutionResult::from_output(&output))
+        } else {
+            let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+            Err(self.located(
+                SniprunError::RuntimeError(stderr.clone()),
+                &NIM_FRAME,
+                &stderr,
+                true,
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_nim_original {
+    use super::*;
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+
+    fn nim(code: &str, flags: &[&str]) -> Box<Nim_original> {
+        let flags: Vec<neovim_lib::Value> = flags.iter().map(|&f| f.into()).collect();
+        DataHolder::prepared("nim", code, &[("flags", flags.into())])
+    }
+
+    fn binary(interpreter: &Nim_original) -> String {
+        let path = Path::new(&interpreter.bin_path);
+        path.file_name().unwrap().to_string_lossy().to_string()
+    }
+
+    #[test]
+    fn a_single_expression_is_echoed() {
+        assert_eq!(nim("1+1", &[]).code, "echo 1+1");
+        assert_eq!(nim("f(x = 1)", &[]).code, "echo f(x = 1)");
+        for statement in ["echo 1", "let a = 1", "a = 1", "proc f() =", "a = 1\na"] {
+            assert_eq!(nim(statement, &[]).code, statement);
+        }
+    }
+
+    #[test]
+    fn the_binary_is_named_after_the_code_and_the_flags() {
+        let first = nim("echo 1", &[]);
+        assert_eq!(binary(&first), binary(&nim("echo 1", &[])));
+        assert_ne!(binary(&first), binary(&nim("echo 2", &[])));
+        assert_ne!(binary(&first), binary(&nim("echo 1", &["-d:release"])));
+        // apart from nim's own cache
+        assert!(first.bin_path.starts_with(&first.nim_work_dir));
+        assert!(!first.bin_path.contains("nimcache"));
+    }
+
+    #[test]
+    fn a_compiled_binary_is_run_without_compiling_again() {
+        let mut interpreter = nim("echo 1", &[]);
+        fs::write(&interpreter.bin_path, "#!/bin/sh\nprintf cached").unwrap();
+        fs::set_permissions(&interpreter.bin_path, fs::Permissions::from_mode(0o755)).unwrap();
+        interpreter.build().unwrap();
+        assert_eq!(interpreter.execute().unwrap().stdout, "cached");
+    }
+
+    #[test]
+    fn errors_are_located() {
+        let interpreter = nim("let a = 1\nlet b: string = a", &[]);
+        let report = "main.nim(2, 17) Error: type mismatch: got 'int' for 'a'";
+        let error = interpreter.located(
+            SniprunError::CompilationError(report.to_string()),
+            &NIM_COMPILATION_ERROR,
+            report,
+            false,
+        );
+        assert_eq!(error.line(), Some(2));
+
+        let traceback = "Traceback (most recent call last)\nmain.nim(3) main\nmain.nim(1) f\nError: unhandled exception";
+        let error = interpreter.located(
+            SniprunError::RuntimeError(traceback.to_string()),
+            &NIM_FRAME,
+            traceback,
+            true,
+        );
+        assert_eq!(error.line(), Some(1));
+    }
+
+    #[test]
+    fn a_snippet_runs() {
+        if check_toolchain(&["nim"]).is_err() {
+            return;
+        }
+        assert_eq!(nim("echo 1+1", &[]).run().unwrap().stdout, "2\n");
+    }
+}
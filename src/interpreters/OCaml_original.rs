@@ -0,0 +1,296 @@
+#[derive(Debug, Clone)]
+#[allow(non_camel_case_types)]
+pub struct OCaml_original {
+    support_level: SupportLevel,
+    data: DataHolder,
+    code: String,
+
+    ///specific to ocaml
+    ocaml_work_dir: String,
+    main_file_path: String,
+    ///compile the code to a native binary with ocamlfind ocamlopt (the binary of the same code
+    ///is reused), rather than running it with the toplevel
+    compiled: bool,
+    ///the findlib packages the compiled code is linked with
+    packages: Vec<String>,
+    ///the binary of the code, named by a hash of the code, when compiled
+    bin_path: String,
+    line_offsets: LineOffsets,
+}
+
+/// how many compiled binaries are kept, the oldest ones are removed first
+const KEPT_OCAML_BINARIES: usize = 20;
+
+lazy_static! {
+    /// ocaml locates errors as 'File "main.ml", line LINE, characters C1-C2:' (or 'lines
+    /// L1-L2' when the error spans several lines)
+    static ref OCAML_LOCATION: Regex =
+        Regex::new(r#"(File "[^"]*main\.ml", lines? )(\d+)"#).unwrap();
+    /// the toplevel also prints the lines an error is on, as 'LINE | code'
+    static ref OCAML_QUOTED_LINE: Regex = Regex::new(r"(?m)^(\d+) \|").unwrap();
+}
+
+impl OCaml_original {
+    /// whether the last line of the code is a whole expression (and not the end of a longer
+    /// one, or a definition), whose value the toplevel can print
+    fn is_last_expression(previous: Option<&str>, line: &str) -> bool {
+        let keywords = [
+            "let", "open", "type", "module", "exception", "external", "class", "include", "and",
+            "in", "end", "done",
+        ];
+        let continued = ["=", "in", "->", "then", "else", "do", "(", "[", "{", ",", ";", "begin"];
+        let line = line.trim_end().trim_end_matches(";;");
+        let first_word = line
+            .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .next()
+            .unwrap_or("");
+        !line.is_empty()
+            && !line.starts_with(char::is_whitespace)
+            && !["#", "(*", "|"].iter().any(|start| line.starts_with(start))
+            && !keywords.contains(&first_word)
+            && !previous.is_some_and(|previous| {
+                let previous = previous.trim_end();
+                !previous.ends_with(";;") && continued.iter().any(|c| previous.ends_with(c))
+            })
+    }
+
+    /// the error, with the lines of the code rather than the ones of the generated file,
+    /// located in the buffer where the first error is
+    fn error(&self, report: &str, compilation: bool) -> SniprunError {
+        let mut first_line = None;
+        let code_line = |line: &str| {
+            line.parse::<i64>()
+                .ok()
+                .and_then(|line| self.line_offsets.code_line(line))
+        };
+        let message = OCAML_LOCATION.replace_all(report, |captures: &regex::Captures| {
+            let line = code_line(&captures[2]);
+            if first_line.is_none() {
+                first_line = line.and_then(|line| self.data.buffer_line(line));
+            }
+            match line {
+                Some(line) => format!("{}{}", &captures[1], line),
+                None => captures[0].to_string(),
+            }
+        });
+        let message = OCAML_QUOTED_LINE.replace_all(&message, |captures: &regex::Captures| {
+            match code_line(&captures[1]) {
+                Some(line) => format!("{} |", line),
+                None => captures[0].to_string(),
+            }
+        });
+        let error = if compilation || message.contains("\nError: ") || message.starts_with("Error: ")
+        {
+            SniprunError::CompilationError(message.to_string())
+        } else {
+            SniprunError::RuntimeError(message.to_string())
+        };
+        match first_line {
+            Some(line) => error.at_line(line),
+            None => error,
+        }
+    }
+}
+
+impl Interpreter for OCaml_original {
+    declare_interpreter!(OCaml_original);
+
+    fn new_with_level(data: DataHolder, support_level: SupportLevel) -> Box<OCaml_original> {
+        let owd = work_dir_path(&data, &Self::get_work_dir_name());
+        let mfp = Self::temp_source_path(&data);
+        let compiled = data
+            .interpreter_option(&Self::get_name(), "compiled")
+            .map(|c| config_bool(c, false))
+            .unwrap_or(false);
+        let packages = data
+            .interpreter_option(&Self::get_name(), "packages")
+            .and_then(|p| p.as_array())
+            .map(|p| {
+                p.iter()
+                    .filter_map(|package| package.as_str().map(String::from))
+                    .collect()
+            })
+            .unwrap_or_default();
+        Box::new(OCaml_original {
+            data,
+            support_level,
+            code: String::from(""),
+            ocaml_work_dir: owd,
+            main_file_path: mfp,
+            compiled,
+            packages,
+            bin_path: String::new(),
+            line_offsets: LineOffsets::default(),
+        })
+    }
+
+    fn get_supported_languages() -> Vec<String> {
+        vec![String::from("ocaml"), String::from("ml")]
+    }
+
+    fn get_file_extension() -> String {
+        String::from("ml")
+    }
+
+    fn get_generated_file(&self) -> Option<String> {
+        Some(self.main_file_path.clone())
+    }
+
+    fn get_max_support_level() -> SupportLevel {
+        SupportLevel::Bloc
+    }
+
+    fn get_capabilities() -> Capabilities {
+        Capabilities::STREAMING
+    }
+
+    fn get_line_offsets(&self) -> LineOffsets {
+        self.line_offsets.clone()
+    }
+
+    fn fetch_code(&mut self) -> Result<(), SniprunError> {
+        self.code = selected_code(&self.data, self.get_current_level());
+        Ok(())
+    }
+
+    fn add_boilerplate(&mut self) -> Result<(), SniprunError> {
+        self.line_offsets = LineOffsets::default();
+        self.bin_path = format!(
+            "{}/{}.bin",
+            self.ocaml_work_dir,
+            crate::cache::key(&(&self.code, &self.packages))
+        );
+        // only the toplevel knows how to print a value of any type
+        if self.compiled || !self.data.print_last_expression {
+            return Ok(());
+        }
+        let mut lines: Vec<String> = self.code.lines().map(String::from).collect();
+        let last = match lines.iter().rposition(|l| !l.trim().is_empty()) {
+            Some(last) => last,
+            None => return Ok(()),
+        };
+        let previous = lines[..last].iter().rev().find(|l| !l.trim().is_empty());
+        if !Self::is_last_expression(previous.map(String::as_str), &lines[last]) {
+            return Ok(());
+        }
+        // the expression is given to the toplevel running the script, that prints it (as
+        // '- : int = 2') in place, so that the lines of the code don't move
+        let expression = lines[last].trim().trim_end_matches(";;").to_string();
+        lines[last] = format!(
+            ";; let () = ignore (Toploop.execute_phrase true Format.std_formatter \
+             (!Toploop.parse_toplevel_phrase (Lexing.from_string {{sniprun|{};;|sniprun}})))",
+            expression
+        );
+        self.code = String::from("#directory \"+compiler-libs\";;\n") + &lines.join("\n") + "\n";
+        self.line_offsets.prepend(1);
+        Ok(())
+    }
+
+    fn build(&mut self) -> Result<(), SniprunError> {
+        write_code(&self.main_file_path, &self.code)?;
+        if !self.compiled {
+            return Ok(());
+        }
+        if Path::new(&self.bin_path).is_file() {
+            info!("[OCAML] reusing {}", self.bin_path);
+            return Ok(());
+        }
+
+        check_toolchain(&["ocamlfind"])?;
+        let mut command = Command::new("ocamlfind");
+        command.arg("ocamlopt").arg("-g");
+        if !self.packages.is_empty() {
+            command
+                .arg("-package")
+                .arg(self.packages.join(","))
+                .arg("-linkpkg");
+        }
+        let output = command
+            .arg(&self.main_file_path)
+            .arg("-o")
+            .arg(&self.bin_path)
+            .current_dir(&self.ocaml_work_dir)
+            .output()?;
+        if !output.status.success() {
+            let report = String::from_utf8_lossy(&output.stdout).to_string()
+                + &String::from_utf8_lossy(&output.stderr);
+            return Err(self.error(&report, true));
+        }
+        remove_oldest_files(&self.ocaml_work_dir, "bin", KEPT_OCAML_BINARIES);
+        Ok(())
+    }
+
+    fn execute(&mut self) -> Result<ExecutionResult, SniprunError> {
+        let mut command = if self.compiled {
+            // the backtrace of an uncaught exception locates it
+            let mut command = Command::new(&self.bin_path);
+            command.env("OCAMLRUNPARAM", "b");
+            command
+        } else {
+            check_toolchain(&["ocaml"])?;
+            let mut command = Command::new("ocaml");
+            command.arg("-color=never").arg(&self.main_file_path);
+            command
+        };
+        let output = run_command(command.current_dir(&self.ocaml_work_dir), &self.data)?;
+        if output.status.success() {
+            Ok(ExecutionResult::from_output(&output))
+        } else {
+            Err(self.error(&String::from_utf8_lossy(&output.stderr), false))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_ocaml_original {
+    use super::*;
+
+    fn ocaml(code: &str, print_last_expression: bool) -> Box<OCaml_original> {
+        let mut data = DataHolder::with_code_in_temp_dir("ocaml", code);
+        data.print_last_expression = print_last_expression;
+        data.into_prepared(&[])
+    }
+
+    #[test]
+    fn only_a_whole_last_expression_is_printed() {
+        let last = OCaml_original::is_last_expression;
+        assert!(last(None, "1 + 1"));
+        assert!(last(Some("let a = 1;;"), "a + 1;;"));
+        assert!(!last(Some("let a ="), "1"));
+        assert!(!last(Some("let a = 1 in"), "a + 1"));
+        assert!(!last(None, "let a = 1"));
+        assert!(!last(None, "(* a comment *)"));
+
+        let interpreter = ocaml("let a = 1;;\na + 1", true);
+        assert!(interpreter.code.starts_with("#directory \"+compiler-libs\";;\nlet a = 1;;\n;; "));
+        assert!(interpreter.code.contains("{sniprun|a + 1;;|sniprun}"));
+        assert_eq!(ocaml("let a = 1;;\na + 1", false).code, "let a = 1;;\na + 1");
+    }
+
+    #[test]
+    fn errors_point_at_the_code() {
+        let interpreter = ocaml("let a = 1;;\nlet b : string = a;;\nb", true);
+        let report = "File \"/tmp/main.ml\", line 3, characters 17-18:\n3 | let b : string = a;;\n                     ^\nError: This expression has type int but an expression was expected of type string";
+        let error = interpreter.error(report, false);
+        assert!(matches!(error.inner(), SniprunError::CompilationError(_)));
+        assert_eq!(error.line(), Some(2));
+        let message = error.inner().to_string();
+        assert!(message.contains("line 2, characters"));
+        assert!(message.contains("2 | let b"));
+
+        let interpreter = ocaml("let a = 1 / 0", false);
+        let error = interpreter.error(
+            "Exception: Division_by_zero.\nRaised at file \"main.ml\", line 1, characters 8-13",
+            false,
+        );
+        assert!(matches!(error.inner(), SniprunError::RuntimeError(_)));
+    }
+
+    #[test]
+    fn a_snippet_runs() {
+        if check_toolchain(&["ocaml"]).is_err() {
+            return;
+        }
+        assert_eq!(ocaml("print_int (1+1)", false).run().unwrap().stdout, "2");
+    }
+}
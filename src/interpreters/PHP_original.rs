@@ -0,0 +1,158 @@
+#[derive(Debug, Clone)]
+#[allow(non_camel_case_types)]
+pub struct PHP_original {
+    support_level: SupportLevel,
+    data: DataHolder,
+    code: String,
+
+    ///specific to php
+    php_work_dir: String,
+    main_file_path: String,
+    line_offsets: LineOffsets,
+}
+
+lazy_static! {
+    /// php reports errors as '[PHP ]Parse error: message in /path/main.php on line N'
+    static ref PHP_ERROR: Regex =
+        Regex::new(r"(?:PHP )?(Parse|Fatal) error:\s*(.*?) in \S+ on line (\d+)").unwrap();
+}
+
+impl PHP_original {
+    /// the error php reported, parse errors are compilation errors, located in the buffer
+    fn error(&self, stderr: &str) -> SniprunError {
+        let captures = match PHP_ERROR.captures(stderr) {
+            Some(captures) => captures,
+            None => return SniprunError::RuntimeError(stderr.to_string()),
+        };
+        let error = if &captures[1] == "Parse" {
+            SniprunError::CompilationError(captures[2].to_string())
+        } else {
+            SniprunError::RuntimeError(stderr.to_string())
+        };
+        let line = captures[3]
+            .parse::<i64>()
+            .ok()
+            .and_then(|line| self.to_buffer_line(line));
+        match line {
+            Some(line) => error.at_line(line),
+            None => error,
+        }
+    }
+}
+
+impl Interpreter for PHP_original {
+    declare_interpreter!(PHP_original);
+
+    fn new_with_level(data: DataHolder, support_level: SupportLevel) -> Box<PHP_original> {
+        let pwd = work_dir_path(&data, &Self::get_work_dir_name());
+        let mfp = pwd.clone() + "/main.php";
+        Box::new(PHP_original {
+            data,
+            support_level,
+            code: String::from(""),
+            php_work_dir: pwd,
+            main_file_path: mfp,
+            line_offsets: LineOffsets::default(),
+        })
+    }
+
+    fn get_supported_languages() -> Vec<String> {
+        vec![String::from("php")]
+    }
+
+    fn get_generated_file(&self) -> Option<String> {
+        Some(self.main_file_path.clone())
+    }
+
+    fn get_max_support_level() -> SupportLevel {
+        SupportLevel::Bloc
+    }
+
+    fn get_capabilities() -> Capabilities {
+        Capabilities::STREAMING
+    }
+
+    fn fetch_code(&mut self) -> Result<(), SniprunError> {
+        self.code = selected_code(&self.data, self.get_current_level());
+        Ok(())
+    }
+
+    fn get_line_offsets(&self) -> LineOffsets {
+        self.line_offsets.clone()
+    }
+
+    fn add_boilerplate(&mut self) -> Result<(), SniprunError> {
+        self.line_offsets = LineOffsets::default();
+        // snippets usually come without the opening tag
+        if !self.code.trim_start().starts_with("<?php") {
+            self.code = String::from("<?php\n") + &self.code;
+            self.line_offsets.prepend(1);
+        }
+        Ok(())
+    }
+
+    fn build(&mut self) -> Result<(), SniprunError> {
+        write_code(&self.main_file_path, &self.code)
+    }
+
+    fn execute(&mut self) -> Result<ExecutionResult, SniprunError> {
+        check_toolchain(&["php"])?;
+        let output = run_command(
+            Command::new("php")
+                .arg("-d")
+                .arg("display_errors=stderr")
+                .arg(&self.main_file_path)
+                .current_dir(&self.php_work_dir),
+            &self.data,
+        )?;
+        if output.status.success() {
+            Ok(ExecutionResult::from_output(&output))
+        } else {
+            Err(self.error(&String::from_utf8_lossy(&output.stderr)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_php_original {
+    use super::*;
+
+    fn php(code: &str) -> Box<PHP_original> {
+        DataHolder::prepared("php", code, &[])
+    }
+
+    #[test]
+    fn the_opening_tag_is_added_when_missing() {
+        let interpreter = php("echo 2+2;");
+        assert_eq!(interpreter.code, "<?php\necho 2+2;");
+        assert_eq!(interpreter.to_buffer_line(2), Some(1));
+
+        let interpreter = php("<?php echo 1;");
+        assert_eq!(interpreter.code, "<?php echo 1;");
+    }
+
+    #[test]
+    fn parse_errors_are_compilation_errors() {
+        let interpreter = php("echo 1\necho 2;");
+        let error = interpreter.error(
+            "PHP Parse error:  syntax error, unexpected token \"echo\" in /tmp/main.php on line 3",
+        );
+        assert!(matches!(error.inner(), SniprunError::CompilationError(_)));
+        assert_eq!(error.line(), Some(2));
+
+        let error = interpreter.error(
+            "PHP Fatal error:  Uncaught Error: Call to undefined function f() in /tmp/main.php on line 2",
+        );
+        assert!(matches!(error.inner(), SniprunError::RuntimeError(_)));
+        assert_eq!(error.line(), Some(1));
+    }
+
+    #[test]
+    fn a_snippet_runs() {
+        if check_toolchain(&["php"]).is_err() {
+            return;
+        }
+        let result = php("echo 2+2;").run().unwrap();
+        assert_eq!(result.stdout, "4");
+    }
+}
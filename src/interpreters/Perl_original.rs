@@ -0,0 +1,187 @@
+#[derive(Debug, Clone)]
+#[allow(non_camel_case_types)]
+pub struct Perl_original {
+    support_level: SupportLevel,
+    data: DataHolder,
+    code: String,
+
+    ///specific to perl
+    perl_work_dir: String,
+    main_file_path: String,
+    ///add 'use strict; use warnings;' before the code
+    strict: bool,
+    ///run perl with -w
+    warnings: bool,
+    line_offsets: LineOffsets,
+}
+
+lazy_static! {
+    /// perl locates errors as 'message at /path/to/main.pl line N.'
+    static ref PERL_LOCATION: Regex = Regex::new(r"main\.pl line (\d+)").unwrap();
+    /// what perl says when the code doesn't compile, rather than when it dies
+    static ref PERL_COMPILATION_ERROR: Regex =
+        Regex::new(r"compilation aborted|aborted due to compilation errors|syntax error at").unwrap();
+}
+
+impl Perl_original {
+    /// the boolean option `key` the user set, false by default
+    fn bool_option(data: &DataHolder, key: &str) -> bool {
+        data.interpreter_option(&Self::get_name(), key)
+            .map(|v| config_bool(v, false))
+            .unwrap_or(false)
+    }
+
+    /// the error of a failed run, located in the buffer where perl says it happened
+    fn error(&self, stderr: &str) -> SniprunError {
+        let error = if PERL_COMPILATION_ERROR.is_match(stderr) {
+            SniprunError::CompilationError(stderr.to_string())
+        } else {
+            SniprunError::RuntimeError(stderr.to_string())
+        };
+        let line = PERL_LOCATION
+            .captures(stderr)
+            .and_then(|captures| captures[1].parse::<i64>().ok())
+            .and_then(|line| self.to_buffer_line(line));
+        match line {
+            Some(line) => error.at_line(line),
+            None => error,
+        }
+    }
+}
+
+impl Interpreter for Perl_original {
+    declare_interpreter!(Perl_original);
+
+    fn new_with_level(data: DataHolder, support_level: SupportLevel) -> Box<Perl_original> {
+        let pwd = work_dir_path(&data, &Self::get_work_dir_name());
+        let mfp = pwd.clone() + "/main.pl";
+        let strict = Self::bool_option(&data, "strict");
+        let warnings = Self::bool_option(&data, "warnings");
+        Box::new(Perl_original {
+            data,
+            support_level,
+            code: String::from(""),
+            perl_work_dir: pwd,
+            main_file_path: mfp,
+            strict,
+            warnings,
+            line_offsets: LineOffsets::default(),
+        })
+    }
+
+    fn get_supported_languages() -> Vec<String> {
+        vec![String::from("perl"), String::from("pl")]
+    }
+
+    fn get_generated_file(&self) -> Option<String> {
+        Some(self.main_file_path.clone())
+    }
+
+    fn get_max_support_level() -> SupportLevel {
+        SupportLevel::Bloc
+    }
+
+    fn get_capabilities() -> Capabilities {
+        Capabilities::STREAMING
+    }
+
+    fn get_line_offsets(&self) -> LineOffsets {
+        self.line_offsets.clone()
+    }
+
+    fn fetch_code(&mut self) -> Result<(), SniprunError> {
+        self.code = selected_code(&self.data, self.get_current_level());
+        Ok(())
+    }
+
+    fn add_boilerplate(&mut self) -> Result<(), SniprunError> {
+        self.line_offsets = LineOffsets::default();
+        if self.strict {
+            self.code = String::from("use strict; use warnings;\n") + &self.code;
+            self.line_offsets.prepend(1);
+        }
+        Ok(())
+    }
+
+    fn build(&mut self) -> Result<(), SniprunError> {
+        write_code(&self.main_file_path, &self.code)
+    }
+
+    fn check(&mut self) -> Result<ExecutionResult, SniprunError> {
+        self.build()?;
+        check_toolchain(&["perl"])?;
+        syntax_check(
+            Command::new("perl").arg("-c").arg(&self.main_file_path),
+            |stderr| self.error(stderr),
+        )
+    }
+
+    fn execute(&mut self) -> Result<ExecutionResult, SniprunError> {
+        check_toolchain(&["perl"])?;
+        let mut command = Command::new("perl");
+        if self.warnings {
+            command.arg("-w");
+        }
+        let output = run_command(
+            command
+                .arg(&self.main_file_path)
+                .current_dir(&self.perl_work_dir),
+            &self.data,
+        )?;
+        if output.status.success() {
+            Ok(ExecutionResult::from_output(&output))
+        } else {
+            Err(self.error(&String::from_utf8_lossy(&output.stderr)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_perl_original {
+    use super::*;
+
+    fn perl(code: &str, options: &[(&str, bool)]) -> Box<Perl_original> {
+        let options: Vec<_> = options
+            .iter()
+            .map(|&(key, value)| (key, neovim_lib::Value::from(value)))
+            .collect();
+        DataHolder::prepared("perl", code, &options)
+    }
+
+    #[test]
+    fn a_snippet_runs() {
+        if check_toolchain(&["perl"]).is_err() {
+            return;
+        }
+        assert_eq!(perl("print 2+2;", &[]).run().unwrap().stdout, "4");
+    }
+
+    #[test]
+    fn strict_and_warnings_can_be_asked_for() {
+        if check_toolchain(&["perl"]).is_err() {
+            return;
+        }
+        // an undeclared variable is fine, unless strict
+        assert!(perl("$x = 1;\nprint $x;", &[]).run().is_ok());
+        let error = perl("my $x = 1;\n$y = 2;", &[("strict", true)])
+            .run()
+            .unwrap_err();
+        assert!(matches!(error.inner(), SniprunError::CompilationError(_)));
+        assert_eq!(error.line(), Some(2));
+
+        let result = perl("my @a = (1);\nprint @a[0];", &[("warnings", true)])
+            .run()
+            .unwrap();
+        assert!(result.stderr.contains("line 2"));
+    }
+
+    #[test]
+    fn dying_is_a_runtime_error() {
+        if check_toolchain(&["perl"]).is_err() {
+            return;
+        }
+        let error = perl("print 1;\ndie \"boom\";", &[]).run().unwrap_err();
+        assert!(matches!(error.inner(), SniprunError::RuntimeError(_)));
+        assert_eq!(error.line(), Some(2));
+    }
+}
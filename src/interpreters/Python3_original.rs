@@ -5,6 +5,17 @@ pub struct Python3_original {
     data: DataHolder,
     code: String,
     imports: String,
+
+    ///python binary the code runs with: the one the user chose, the project's environment's
+    ///(so that its packages import) or python3; the embedded python is used if there is none
+    python: Option<String>,
+    main_file_path: String,
+    line_offsets: LineOffsets,
+}
+
+lazy_static! {
+    /// python locates the frames of a traceback as 'File "/path/to/file.py", line N'
+    static ref FRAME_LOCATION: Regex = Regex::new(r#"File "([^"]*)", line (\d+)"#).unwrap();
 }
 
 fn module_used(line: &str, code: &str) -> bool {
@@ -26,14 +37,94 @@ fn module_used(line: &str, code: &str) -> bool {
             return true;
         }
     }
-    return false;
+    false
 }
 
 impl Python3_original {
+    /// the python binary the user chose (interpreter option `python`), or the one of the
+    /// environment the edited file belongs to, or python3 if installed
+    fn select_python(data: &DataHolder) -> Option<String> {
+        if let Some(python) = data
+            .interpreter_option(&Self::get_name(), "python")
+            .and_then(|p| p.as_str())
+        {
+            info!("[PYTHON3] using the python set in the options: {}", python);
+            return Some(python.to_string());
+        }
+        if let Some(python) = python_environment(&data.filepath) {
+            info!("[PYTHON3] using the environment's python: {:?}", python);
+            return Some(python.to_string_lossy().to_string());
+        }
+        if check_toolchain(&["python3"]).is_ok() {
+            return Some(String::from("python3"));
+        }
+        info!("[PYTHON3] no python3 found, using the embedded python");
+        None
+    }
+
+    /// whether a line of code is a bare expression, whose value is worth printing
+    fn is_expression(line: &str) -> bool {
+        let line = line.trim();
+        let keywords = [
+            "import ", "from ", "def ", "class ", "if ", "elif ", "else", "for ", "while ",
+            "with ", "try", "except", "finally", "return", "raise", "del ", "pass", "break",
+            "continue", "global ", "nonlocal ", "assert ", "async ", "await ", "lambda", "@",
+            "#", "print(",
+        ];
+        if line.is_empty()
+            || line.ends_with(':')
+            || keywords.iter().any(|keyword| line.starts_with(keyword))
+        {
+            return false;
+        }
+        // an assignment has a '=' outside of brackets (that is not a comparison)
+        let line = ["==", "!=", "<=", ">="]
+            .iter()
+            .fold(line.to_string(), |line, operator| line.replace(operator, ""));
+        let mut depth = 0;
+        for c in line.chars() {
+            match c {
+                '(' | '[' | '{' => depth += 1,
+                ')' | ']' | '}' => depth -= 1,
+                '=' if depth == 0 => return false,
+                _ => (),
+            }
+        }
+        true
+    }
+
+    /// the error python reported: the traceback, with the lines of the code rather than the
+    /// ones of the generated file, located in the buffer
+    fn error(&self, stderr: &str) -> SniprunError {
+        let mut line = None;
+        let traceback = FRAME_LOCATION.replace_all(stderr, |captures: &regex::Captures| {
+            let generated_line = captures[2].parse::<i64>().unwrap_or(0);
+            if captures[1] != self.main_file_path {
+                return captures[0].to_string();
+            }
+            // the last frame of the code is where the error comes from
+            line = self.to_buffer_line(generated_line).or(line);
+            match self.line_offsets.code_line(generated_line) {
+                Some(code_line) => format!("File \"main.py\", line {}", code_line),
+                None => captures[0].to_string(),
+            }
+        });
+        let error = if traceback.contains("SyntaxError") || traceback.contains("IndentationError") {
+            SniprunError::CompilationError(traceback.to_string())
+        } else {
+            SniprunError::RuntimeError(traceback.to_string())
+        };
+        match line {
+            Some(line) => error.at_line(line),
+            None => error,
+        }
+    }
+
     pub fn fetch_imports(&mut self) -> std::io::Result<()> {
         if self.support_level < SupportLevel::Line {
             return Ok(());
         }
+        self.imports = String::new();
         //no matter if it fails, we should try to run the rest
         let mut file = File::open(&self.data.filepath)?;
         let mut contents = String::new();
@@ -42,7 +133,7 @@ impl Python3_original {
         for line in contents.lines() {
             info!("lines are : {}", line);
             if line.contains("import ") //basic selection
-                && line.trim().chars().next() != Some('#')
+                && !line.trim().starts_with('#')
             && module_used(line, &contents)
             {
                 // embed in try catch blocs in case uneeded module is unavailable
@@ -58,19 +149,22 @@ try:\n" + "\t" + line
 }
 
 impl Interpreter for Python3_original {
+    declare_interpreter!(Python3_original);
+
     fn new_with_level(data: DataHolder, level: SupportLevel) -> Box<Python3_original> {
+        let pwd = work_dir_path(&data, &Self::get_work_dir_name());
+        let python = Self::select_python(&data);
         Box::new(Python3_original {
             data,
             support_level: level,
             code: String::from(""),
             imports: String::from(""),
+            python,
+            main_file_path: pwd + "/main.py",
+            line_offsets: LineOffsets::default(),
         })
     }
 
-    fn get_name() -> String {
-        String::from("Python3_original")
-    }
-
     fn get_supported_languages() -> Vec<String> {
         vec![
             String::from("python"),
@@ -79,41 +173,42 @@ impl Interpreter for Python3_original {
         ]
     }
 
-    fn get_current_level(&self) -> SupportLevel {
-        self.support_level
-    }
-    fn set_current_level(&mut self, level: SupportLevel) {
-        self.support_level = level;
-    }
-
-    fn get_data(&self) -> DataHolder {
-        self.data.clone()
+    fn get_capabilities() -> Capabilities {
+        // the packages of the environment of the file
+        Capabilities::PROJECT_FILES
     }
 
     fn get_max_support_level() -> SupportLevel {
         SupportLevel::Import
     }
 
+    fn get_generated_file(&self) -> Option<String> {
+        self.python.as_ref().map(|_| self.main_file_path.clone())
+    }
+
     fn fetch_code(&mut self) -> Result<(), SniprunError> {
         let _res = self.fetch_imports();
-        if !self
-            .data
-            .current_bloc
-            .replace(&[' ', '\t', '\n', '\r'][..], "")
-            .is_empty()
-            && self.get_current_level() >= SupportLevel::Bloc
-        {
-            self.code = self.data.current_bloc.clone();
-        } else if !self.data.current_line.replace(" ", "").is_empty()
-            && self.get_current_level() >= SupportLevel::Line
-        {
-            self.code = self.data.current_line.clone();
-        } else {
-            self.code = String::from("");
-        }
+        self.code = selected_code(&self.data, self.get_current_level());
         Ok(())
     }
+    fn get_line_offsets(&self) -> LineOffsets {
+        self.line_offsets.clone()
+    }
+
     fn add_boilerplate(&mut self) -> Result<(), SniprunError> {
+        // a single expression shows its value, like in the REPL
+        if self.code.trim().lines().count() == 1 && Self::is_expression(&self.code) {
+            self.code = format!("print({})", self.code.trim());
+        }
+        self.line_offsets = LineOffsets::default();
+        if self.python.is_some() {
+            // run as a script, its output is captured as any other program's
+            let prelude = self.imports.clone() + "\n";
+            self.line_offsets
+                .prepend(prelude.matches('\n').count() as i64);
+            self.code = prelude + &unindent(&self.code);
+            return Ok(());
+        }
         self.code = self.imports.clone()
             + &String::from(
                 "from io import StringIO
@@ -129,9 +224,44 @@ exit_value1428571999 = str(mystdout1427851999.getvalue())";
         Ok(())
     }
     fn build(&mut self) -> Result<(), SniprunError> {
+        if self.python.is_some() {
+            write_code(&self.main_file_path, &self.code)?;
+        }
         Ok(())
     }
-    fn execute(&mut self) -> Result<String, SniprunError> {
+    fn check(&mut self) -> Result<ExecutionResult, SniprunError> {
+        let python = match &self.python {
+            Some(python) => python.clone(),
+            None => {
+                return Ok(ExecutionResult::new(String::from(
+                    "no syntax check with the embedded python, nothing was checked\n",
+                )))
+            }
+        };
+        self.build()?;
+        check_toolchain(&[&python])?;
+        syntax_check(
+            Command::new(&python)
+                .arg("-m")
+                .arg("py_compile")
+                .arg(&self.main_file_path),
+            |stderr| self.error(stderr),
+        )
+    }
+
+    fn execute(&mut self) -> Result<ExecutionResult, SniprunError> {
+        if let Some(python) = &self.python {
+            check_toolchain(&[python])?;
+            let output = run_command(
+                Command::new(python).arg(&self.main_file_path),
+                &self.data,
+            )?;
+            return if output.status.success() {
+                Ok(ExecutionResult::from_output(&output))
+            } else {
+                Err(self.error(&String::from_utf8_lossy(&output.stderr)))
+            };
+        }
         let py = pyo3::Python::acquire_gil();
         let locals = PyDict::new(py.python());
         match py.python().run(self.code.as_str(), None, Some(locals)) {
@@ -144,13 +274,13 @@ exit_value1428571999 = str(mystdout1427851999.getvalue())";
         if let Some(unwrapped_stdout) = py_stdout {
             let result: Result<String, _> = unwrapped_stdout.extract();
             match result {
-                Ok(unwrapped_result) => return Ok(unwrapped_result),
-                Err(_e) => return Err(SniprunError::InterpreterError),
+                Ok(unwrapped_result) => Ok(ExecutionResult::new(unwrapped_result)),
+                Err(_e) => Err(SniprunError::InterpreterError),
             }
         } else {
-            return Err(SniprunError::InterpreterLimitationError(String::from(
+            Err(SniprunError::InterpreterLimitationError(String::from(
                 "Code erased a needed value to get standart output)",
-            )));
+            )))
         }
     }
 }
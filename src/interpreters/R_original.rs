@@ -0,0 +1,199 @@
+#[derive(Debug, Clone)]
+#[allow(non_camel_case_types)]
+pub struct R_original {
+    support_level: SupportLevel,
+    data: DataHolder,
+    code: String,
+
+    ///specific to r
+    r_work_dir: String,
+    main_file_path: String,
+}
+
+/// Runs the file given as argument like an interactive session would: the value of every
+/// top-level expression that is visible (eg: `1+1`, but not `x <- 1`) is printed
+const R_RUNNER: &str = "source(commandArgs(TRUE)[1], print.eval = TRUE)";
+
+lazy_static! {
+    /// R locates syntax errors as '/path/to/main.R:LINE:COLUMN: unexpected ...'
+    static ref R_SYNTAX_ERROR: Regex = Regex::new(r"main\.R:(\d+):\d+: unexpected").unwrap();
+    /// the fences of a chunk of R code in R markdown: '```{r}', '```{r name, echo=FALSE}'...
+    static ref R_CHUNK_START: Regex = Regex::new(r"^\s*```+\s*\{[rR]\b").unwrap();
+    static ref CHUNK_FENCE: Regex = Regex::new(r"^\s*```").unwrap();
+}
+
+impl R_original {
+    /// the lines of the R chunk of an R markdown document around `line` (1-based), and the
+    /// line of the document its code starts at
+    fn chunk_around(document: &str, line: i64) -> Option<(i64, Vec<&str>)> {
+        let lines: Vec<&str> = document.lines().collect();
+        let cursor = (line - 1) as usize;
+        if cursor >= lines.len() {
+            return None;
+        }
+        // the closest fence above the cursor opens an R chunk
+        let start = (0..=cursor).rev().find(|&i| CHUNK_FENCE.is_match(lines[i]))?;
+        if !R_CHUNK_START.is_match(lines[start]) {
+            return None;
+        }
+        let end = (start + 1..lines.len()).find(|&i| CHUNK_FENCE.is_match(lines[i]))?;
+        if end < cursor {
+            return None;
+        }
+        Some((start as i64 + 2, lines[start + 1..end].to_vec()))
+    }
+
+    /// in line mode in an R markdown document, the code of the chunk under the cursor (from
+    /// the file, so save it first)
+    fn current_chunk(&mut self) -> Option<String> {
+        let document = std::fs::read_to_string(&self.data.filepath).ok()?;
+        let (first_line, lines) = Self::chunk_around(&document, self.data.range[0])?;
+        info!("[R] running the chunk starting at line {}", first_line);
+        self.data.range = [first_line, first_line + lines.len() as i64 - 1];
+        self.data.ranges = vec![self.data.range];
+        Some(lines.join("\n"))
+    }
+
+    /// the error of a failed run, located in the buffer for syntax errors
+    fn error(&self, stderr: &str) -> SniprunError {
+        match R_SYNTAX_ERROR
+            .captures(stderr)
+            .and_then(|captures| captures[1].parse::<i64>().ok())
+        {
+            Some(line) => {
+                let error = SniprunError::CompilationError(stderr.to_string());
+                match self.to_buffer_line(line) {
+                    Some(line) => error.at_line(line),
+                    None => error,
+                }
+            }
+            None => SniprunError::RuntimeError(stderr.to_string()),
+        }
+    }
+}
+
+impl Interpreter for R_original {
+    declare_interpreter!(R_original);
+
+    fn new_with_level(data: DataHolder, support_level: SupportLevel) -> Box<R_original> {
+        let rwd = work_dir_path(&data, &Self::get_work_dir_name());
+        let mfp = rwd.clone() + "/main.R";
+        Box::new(R_original {
+            data,
+            support_level,
+            code: String::from(""),
+            r_work_dir: rwd,
+            main_file_path: mfp,
+        })
+    }
+
+    fn get_supported_languages() -> Vec<String> {
+        vec![String::from("r"), String::from("R"), String::from("rmd")]
+    }
+
+    fn get_generated_file(&self) -> Option<String> {
+        Some(self.main_file_path.clone())
+    }
+
+    fn get_max_support_level() -> SupportLevel {
+        SupportLevel::Bloc
+    }
+
+    fn get_capabilities() -> Capabilities {
+        Capabilities::STREAMING
+    }
+
+    fn fetch_code(&mut self) -> Result<(), SniprunError> {
+        let line_mode = self.data.range[0] == self.data.range[1];
+        if self.data.filetype == "rmd" && line_mode && self.support_level >= SupportLevel::Bloc {
+            if let Some(chunk) = self.current_chunk() {
+                self.code = chunk;
+                return Ok(());
+            }
+        }
+        self.code = selected_code(&self.data, self.get_current_level());
+        if self.data.filetype == "rmd" {
+            // the fences of a selection of several chunks, blanked so that the lines don't move
+            self.code = self
+                .code
+                .lines()
+                .map(|l| if CHUNK_FENCE.is_match(l) { "" } else { l })
+                .collect::<Vec<_>>()
+                .join("\n");
+        }
+        Ok(())
+    }
+
+    fn add_boilerplate(&mut self) -> Result<(), SniprunError> {
+        Ok(())
+    }
+
+    fn build(&mut self) -> Result<(), SniprunError> {
+        write_code(&self.main_file_path, &self.code)
+    }
+
+    fn execute(&mut self) -> Result<ExecutionResult, SniprunError> {
+        check_toolchain(&["Rscript"])?;
+        let output = run_command(
+            Command::new("Rscript")
+                .arg("-e")
+                .arg(R_RUNNER)
+                .arg(&self.main_file_path)
+                .current_dir(&self.r_work_dir),
+            &self.data,
+        )?;
+        if output.status.success() {
+            Ok(ExecutionResult::from_output(&output))
+        } else {
+            Err(self.error(&String::from_utf8_lossy(&output.stderr)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_r_original {
+    use super::*;
+
+    fn r(filetype: &str, code: &str) -> Box<R_original> {
+        DataHolder::prepared(filetype, code, &[])
+    }
+
+    #[test]
+    fn the_chunk_under_the_cursor_is_found() {
+        let document = "# title\n```{r setup, echo=FALSE}\nx <- 1\nx + 1\n```\ntext\n```python\n1\n```";
+        assert_eq!(
+            R_original::chunk_around(document, 4),
+            Some((3, vec!["x <- 1", "x + 1"]))
+        );
+        assert_eq!(R_original::chunk_around(document, 6), None);
+        // not an R chunk
+        assert_eq!(R_original::chunk_around(document, 8), None);
+        assert_eq!(R_original::chunk_around(document, 42), None);
+    }
+
+    #[test]
+    fn the_fences_of_a_selection_are_blanked() {
+        let mut interpreter = r("rmd", "```{r}\n1 + 1\n```\n");
+        interpreter.fetch_code().unwrap();
+        assert_eq!(interpreter.code, "\n1 + 1\n");
+    }
+
+    #[test]
+    fn syntax_errors_are_located() {
+        let interpreter = r("r", "x <- 1\nx +* 2");
+        let error = interpreter.error("Error in source(): /tmp/main.R:2:4: unexpected '*'");
+        assert!(matches!(error.inner(), SniprunError::CompilationError(_)));
+        assert_eq!(error.line(), Some(2));
+        let error = interpreter.error("Error: object 'y' not found");
+        assert!(matches!(error, SniprunError::RuntimeError(_)));
+    }
+
+    #[test]
+    fn a_snippet_runs() {
+        if check_toolchain(&["Rscript"]).is_err() {
+            return;
+        }
+        assert_eq!(r("r", "cat(1+1)").run().unwrap().stdout, "2");
+        assert_eq!(r("r", "x <- 1\nx + 1").run().unwrap().stdout.trim(), "[1] 2");
+    }
+}
@@ -0,0 +1,141 @@
+#[derive(Debug, Clone)]
+#[allow(non_camel_case_types)]
+pub struct Ruby_original {
+    support_level: SupportLevel,
+    data: DataHolder,
+    code: String,
+
+    ///specific to ruby
+    ruby_work_dir: String,
+    main_file_path: String,
+}
+
+impl Ruby_original {
+    /// directory of the closest Gemfile above the edited file, if any
+    fn bundle_dir(&self) -> Option<PathBuf> {
+        find_ancestor(Path::new(&self.data.filepath), "Gemfile")
+    }
+}
+
+impl Interpreter for Ruby_original {
+    declare_interpreter!(Ruby_original);
+
+    fn new_with_level(data: DataHolder, support_level: SupportLevel) -> Box<Ruby_original> {
+        let rwd = work_dir_path(&data, &Self::get_work_dir_name());
+        let mfp = rwd.clone() + "/main.rb";
+        Box::new(Ruby_original {
+            data,
+            support_level,
+            code: String::from(""),
+            ruby_work_dir: rwd,
+            main_file_path: mfp,
+        })
+    }
+
+    fn get_supported_languages() -> Vec<String> {
+        vec![String::from("ruby"), String::from("rb")]
+    }
+
+    fn get_generated_file(&self) -> Option<String> {
+        Some(self.main_file_path.clone())
+    }
+
+    fn get_max_support_level() -> SupportLevel {
+        SupportLevel::Bloc
+    }
+
+    fn get_capabilities() -> Capabilities {
+        Capabilities::STREAMING | Capabilities::PROJECT_FILES
+    }
+
+    fn fetch_code(&mut self) -> Result<(), SniprunError> {
+        self.code = selected_code(&self.data, self.get_current_level());
+        Ok(())
+    }
+
+    fn add_boilerplate(&mut self) -> Result<(), SniprunError> {
+        Ok(())
+    }
+
+    fn build(&mut self) -> Result<(), SniprunError> {
+        write_code(&self.main_file_path, &self.code)
+    }
+
+    fn check(&mut self) -> Result<ExecutionResult, SniprunError> {
+        self.build()?;
+        check_toolchain(&["ruby"])?;
+        syntax_check(
+            Command::new("ruby").arg("-c").arg(&self.main_file_path),
+            |stderr| SniprunError::CompilationError(stderr.to_string()),
+        )
+    }
+
+    fn execute(&mut self) -> Result<ExecutionResult, SniprunError> {
+        // with a Gemfile, run through bundler from its directory so the project's gems resolve
+        let mut command = match self.bundle_dir() {
+            Some(bundle_dir) => {
+                check_toolchain(&["bundle", "ruby"])?;
+                info!("[RUBY] running with bundler from {:?}", bundle_dir);
+                let mut command = Command::new("bundle");
+                command.arg("exec").arg("ruby").current_dir(bundle_dir);
+                command
+            }
+            None => {
+                check_toolchain(&["ruby"])?;
+                let mut command = Command::new("ruby");
+                command.current_dir(&self.ruby_work_dir);
+                command
+            }
+        };
+        let output = run_command(command.arg(&self.main_file_path), &self.data)?;
+        if output.status.success() {
+            Ok(ExecutionResult::from_output(&output))
+        } else {
+            Err(SniprunError::RuntimeError(
+                String::from_utf8_lossy(&output.stderr).to_string(),
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_ruby_original {
+    use super::*;
+
+    fn ruby(code: &str) -> Box<Ruby_original> {
+        DataHolder::prepared("ruby", code, &[])
+    }
+
+    #[test]
+    fn the_gemfile_above_the_file_is_found() {
+        let mut interpreter = ruby("puts 1");
+        let project = Path::new(&interpreter.data.work_dir)
+            .canonicalize()
+            .unwrap()
+            .join("project");
+        std::fs::create_dir_all(project.join("lib")).unwrap();
+        interpreter.data.filepath = project.join("lib/a.rb").to_string_lossy().to_string();
+        assert_eq!(interpreter.bundle_dir(), None);
+
+        std::fs::write(project.join("Gemfile"), "").unwrap();
+        assert_eq!(interpreter.bundle_dir(), Some(project));
+        if check_toolchain(&["bundle"]).is_err() {
+            assert!(matches!(
+                interpreter.run(),
+                Err(SniprunError::PrerequisiteError(message)) if message.contains("bundle")
+            ));
+        }
+    }
+
+    #[test]
+    fn a_snippet_runs() {
+        if check_toolchain(&["ruby"]).is_err() {
+            return;
+        }
+        assert_eq!(ruby("puts 1+1").run().unwrap().stdout, "2\n");
+        assert!(matches!(
+            ruby("raise 'boom'").run(),
+            Err(SniprunError::RuntimeError(message)) if message.contains("boom")
+        ));
+    }
+}
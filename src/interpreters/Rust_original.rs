@@ -9,21 +9,243 @@ pub struct Rust_original {
     rust_work_dir: String,
     bin_path: String,
     main_file_path: String,
+    ///the compiler command, eg: ["rustup", "run", "nightly", "rustc"]
+    compiler: Vec<String>,
+    ///extra flags given to the compiler
+    flags: Vec<String>,
+    ///dependencies annotated in the code ('//# dep: name = "version"' or
+    ///'//! sniprun-dep: name = "version"'), as (name, cargo spec)
+    dependencies: Vec<(String, String)>,
+    line_offsets: LineOffsets,
+}
+
+impl Rust_original {
+    /// the compiler command and flags the user chose (interpreter options `compiler` and
+    /// `flags`), by default a plain non-optimized rustc build, to compile fast
+    fn compiler_and_flags(data: &DataHolder) -> (Vec<String>, Vec<String>) {
+        let compiler = data
+            .interpreter_option(&Self::get_name(), "compiler")
+            .and_then(|c| c.as_str())
+            .map(|c| c.split_whitespace().map(String::from).collect::<Vec<_>>())
+            .filter(|c| !c.is_empty())
+            .unwrap_or_else(|| vec![String::from("rustc")]);
+        let flags = data
+            .interpreter_option(&Self::get_name(), "flags")
+            .and_then(|f| f.as_array())
+            .map(|f| {
+                f.iter()
+                    .filter_map(|flag| flag.as_str().map(String::from))
+                    .collect()
+            })
+            .unwrap_or_default();
+        (compiler, flags)
+    }
+
+    /// build a CompilationError from rustc's stderr, located at the
+    /// line of the original buffer the first error points to
+    fn compilation_error(&self, stderr: &str) -> SniprunError {
+        let message = stderr
+            .lines()
+            .find(|l| l.starts_with("error"))
+            .map(|l| l.splitn(2, ": ").last().unwrap_or(l).to_string())
+            .unwrap_or_default();
+        let error = if message.contains("unresolved import") || message.contains("can't find crate")
+        {
+            SniprunError::InterpreterLimitationError(format!(
+                "Rust_original cannot resolve external crates ({}), declare them with a '//# dep: crate_name = \"version\"' line",
+                message
+            ))
+        } else {
+            SniprunError::CompilationError(message)
+        };
+
+        // rustc locates errors as ' --> path/to/main.rs:line:column' (the warnings before the
+        // first error are located the same way, eg: an unused import of the prelude)
+        let generated_line = stderr
+            .lines()
+            .skip_while(|l| !l.starts_with("error"))
+            .find_map(|l| l.trim_start().strip_prefix("--> "))
+            .and_then(|location| location.rsplit(':').nth(1))
+            .and_then(|line| line.parse::<i64>().ok());
+        match generated_line.and_then(|line| self.to_buffer_line(line)) {
+            Some(line) => error.at_line(line),
+            None => error,
+        }
+    }
+
+    /// the dependency declared by a line of code, if it is an annotation (not any comment
+    /// starting with '//#', eg: '//#region')
+    fn dependency_annotation(line: &str) -> Option<&str> {
+        let line = line.trim_start();
+        line.strip_prefix("//# dep:")
+            .or_else(|| line.strip_prefix("//! sniprun-dep:"))
+    }
+
+    /// dependencies annotated in the code, as lines like '//# dep: serde_json = "1"' or
+    /// '//! sniprun-dep: serde_json = "1"' (the right side is anything cargo accepts in a
+    /// Cargo.toml, eg: '{ version = "1", features = ["derive"] }')
+    fn parse_dependencies(code: &str) -> Result<Vec<(String, String)>, SniprunError> {
+        let mut dependencies = vec![];
+        for annotation in code.lines().filter_map(Self::dependency_annotation) {
+            let mut parts = annotation.splitn(2, '=');
+            let name = parts.next().unwrap_or("").trim();
+            let spec = parts.next().unwrap_or("").trim();
+            let valid_name = !name.is_empty()
+                && name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+            if !valid_name || spec.is_empty() {
+                return Err(SniprunError::CompilationError(format!(
+                    "invalid dependency annotation '{}', expected '//# dep: name = \"version\"'",
+                    annotation
+                )));
+            }
+            dependencies.push((name.to_string(), spec.to_string()));
+        }
+        Ok(dependencies)
+    }
+
+    /// directory of the throwaway cargo project used when the code has dependencies, one per
+    /// set of dependencies; they are kept between runs so that the dependencies are only fetched
+    /// and built once
+    fn cargo_project_dir(&self) -> String {
+        let mut dependencies = self.dependencies.clone();
+        dependencies.sort();
+        format!(
+            "{}/cargo_project_{}",
+            self.rust_work_dir,
+            crate::cache::key(&dependencies)
+        )
+    }
+
+    /// write the cargo project for the code and its dependencies, and build it
+    fn build_with_cargo(&mut self) -> Result<(), SniprunError> {
+        let project_dir = self.cargo_project_dir();
+        std::fs::create_dir_all(project_dir.clone() + "/src")?;
+        let manifest = String::from(
+            "[package]\nname = \"sniprun_snippet\"\nversion = \"0.1.0\"\nedition = \"2018\"\n\n[dependencies]\n",
+        ) + &self
+            .dependencies
+            .iter()
+            .map(|(name, spec)| format!("{} = {}\n", name, spec))
+            .collect::<String>();
+        write_code(&(project_dir.clone() + "/Cargo.toml"), &manifest)?;
+        write_code(&self.main_file_path, &self.code)?;
+
+        check_toolchain(&["cargo"])?;
+        info!("[RUST] building with cargo, dependencies: {:?}", self.dependencies);
+        let output = run_build_command(
+            sandboxed_command(&self.data, &project_dir, "cargo")
+                .arg("build")
+                .arg("--quiet"),
+            &self.data,
+        )?;
+        if !output.status.success() {
+            return Err(self.compilation_error(&String::from_utf8_lossy(&output.stderr)));
+        }
+        Ok(())
+    }
+
+    /// where the last statement of the code starts: after the last `;` or `}` that isn't
+    /// nested in brackets, a string or a comment
+    fn last_statement_start(code: &str) -> usize {
+        let mut start = 0;
+        let mut depth = 0i32;
+        let mut chars = code.char_indices().peekable();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '/' if chars.peek().map(|&(_, n)| n) == Some('/') => {
+                    while chars.peek().is_some_and(|&(_, n)| n != '\n') {
+                        chars.next();
+                    }
+                }
+                '"' => {
+                    while let Some((_, n)) = chars.next() {
+                        match n {
+                            '\\' => {
+                                chars.next();
+                            }
+                            '"' => break,
+                            _ => {}
+                        }
+                    }
+                }
+                // a char ('x', '\n'), not a lifetime ('a)
+                '\'' => {
+                    let literal: Vec<char> = chars.clone().take(12).map(|(_, n)| n).collect();
+                    let length = match literal.as_slice() {
+                        ['\\', _, rest @ ..] => rest.iter().position(|&n| n == '\'').map(|p| p + 3),
+                        [_, '\'', ..] => Some(2),
+                        _ => None,
+                    };
+                    for _ in 0..length.unwrap_or(0) {
+                        chars.next();
+                    }
+                }
+                '(' | '[' | '{' => depth += 1,
+                ')' | ']' => depth -= 1,
+                '}' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        start = i + 1;
+                    }
+                }
+                ';' if depth == 0 => start = i + 1,
+                _ => {}
+            }
+        }
+        start
+    }
+
+    /// wrap the last statement of the code (that may span several lines) in a println! if it
+    /// is an expression (not a statement, an item or a block), so that its value is displayed.
+    /// The lines don't move
+    fn print_last_expression(code: &str) -> String {
+        let start = Self::last_statement_start(code);
+        let (head, tail) = code.split_at(start);
+        // the comments before the expression belong to the head
+        let mut offset = 0;
+        for line in tail.split_inclusive('\n') {
+            let trimmed = line.trim();
+            if !trimmed.is_empty() && !trimmed.starts_with("//") {
+                break;
+            }
+            offset += line.len();
+        }
+        let (comments, expression) = tail.split_at(offset);
+        let trimmed = expression.trim();
+        let is_statement = trimmed.is_empty()
+            || trimmed.ends_with(';')
+            || trimmed.ends_with('}')
+            || trimmed.ends_with('{')
+            || trimmed.ends_with(',')
+            || trimmed.starts_with('#')
+            || ["let ", "use ", "fn ", "struct ", "enum ", "impl ", "mod ", "return"]
+                .iter()
+                .any(|keyword| trimmed.starts_with(keyword));
+        if is_statement {
+            return code.to_string();
+        }
+        let indent = &expression[..expression.len() - expression.trim_start().len()];
+        let trailing = &expression[expression.trim_end().len()..];
+        format!(
+            "{}{}{}println!(\"{{:?}}\", {{ {} }});{}",
+            head, comments, indent, trimmed, trailing
+        )
+    }
 }
 
 impl Interpreter for Rust_original {
+    declare_interpreter!(Rust_original);
+
     fn new_with_level(data: DataHolder, support_level: SupportLevel) -> Box<Rust_original> {
-        //create a subfolder in the cache folder
-        let rwd = data.work_dir.clone() + "/rust_original";
-        let mut builder = DirBuilder::new();
-        builder.recursive(true);
-        builder
-            .create(&rwd)
-            .expect("Could not create directory for rust-original");
+        //the subfolder of the cache folder, the launcher creates it
+        let rwd = work_dir_path(&data, &Self::get_work_dir_name());
 
         //pre-create string pointing to main file's and binary's path
-        let mfp = rwd.clone() + "/main.rs";
-        let bp = String::from(&mfp[..mfp.len() - 3]); // remove extension so binary is named 'main'
+        let mfp = Self::temp_source_path(&data);
+        let bp = rwd.clone() + "/main";
+        let (compiler, flags) = Self::compiler_and_flags(&data);
         Box::new(Rust_original {
             data,
             support_level,
@@ -31,6 +253,10 @@ impl Interpreter for Rust_original {
             rust_work_dir: rwd,
             bin_path: bp,
             main_file_path: mfp,
+            compiler,
+            flags,
+            dependencies: vec![],
+            line_offsets: LineOffsets::default(),
         })
     }
 
@@ -42,84 +268,212 @@ impl Interpreter for Rust_original {
         ]
     }
 
-    fn get_name() -> String {
-        String::from("Rust_original")
+    fn get_file_extension() -> String {
+        String::from("rs")
     }
 
-    fn get_current_level(&self) -> SupportLevel {
-        self.support_level
+    fn get_generated_file(&self) -> Option<String> {
+        Some(self.main_file_path.clone())
     }
-    fn set_current_level(&mut self, level: SupportLevel) {
-        self.support_level = level;
+
+    fn get_max_support_level() -> SupportLevel {
+        SupportLevel::Bloc
     }
 
-    fn get_data(&self) -> DataHolder {
-        self.data.clone()
+    fn get_comment_syntax(_filetype: &str) -> CommentSyntax {
+        // doc comments ('///', '//!') are line comments too
+        CommentSyntax::C_LIKE
     }
 
-    fn get_max_support_level() -> SupportLevel {
-        SupportLevel::Bloc
+    fn get_capabilities() -> Capabilities {
+        Capabilities::SEPARATE_BUILD | Capabilities::STREAMING
     }
 
     fn fetch_code(&mut self) -> Result<(), SniprunError> {
         //add code from data to self.code
-        if !self
-            .data
-            .current_bloc
-            .replace(&[' ', '\t', '\n', '\r'][..], "")
-            .is_empty()
-            && self.support_level >= SupportLevel::Bloc
-        {
-            self.code = self.data.current_bloc.clone();
-        } else if !self.data.current_line.replace(" ", "").is_empty()
-            && self.support_level >= SupportLevel::Line
-        {
-            self.code = self.data.current_line.clone();
-        } else {
-            self.code = String::from("");
-        }
+        self.code = selected_code(&self.data, self.get_current_level());
         Ok(())
     }
 
+    fn get_line_offsets(&self) -> LineOffsets {
+        self.line_offsets.clone()
+    }
+
     fn add_boilerplate(&mut self) -> Result<(), SniprunError> {
-        self.code = String::from("fn main() {") + &self.code + "}";
+        self.line_offsets = LineOffsets::default();
+        self.dependencies = Self::parse_dependencies(&self.code)?;
+        if !self.dependencies.is_empty() {
+            let project_dir = self.cargo_project_dir();
+            self.main_file_path = project_dir.clone() + "/src/main.rs";
+            self.bin_path = project_dir + "/target/debug/sniprun_snippet";
+            // the annotations are not code, they are blanked so that the lines don't move
+            self.code = self
+                .code
+                .lines()
+                .map(|l| match Self::dependency_annotation(l) {
+                    Some(_) => "",
+                    None => l,
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+        }
+
+        // the user's prelude (eg: the `use` always needed) is at the top, out of main()
+        let prelude = match self.data.prelude() {
+            Some(prelude) => prelude.trim_end().to_string() + "\n",
+            None => String::new(),
+        };
+        self.line_offsets.prepend(prelude.lines().count() as i64);
+
+        // a complete program (eg: a whole file) is run as-is
+        if self
+            .code
+            .lines()
+            .any(|l| l.trim_start().starts_with("fn main("))
+        {
+            self.code = prelude + &self.code;
+            return Ok(());
+        }
+        if self.data.print_last_expression {
+            self.code = Self::print_last_expression(&self.code);
+        }
+        self.code = prelude + "fn main() {\n" + &self.code + "\n}";
+        self.line_offsets.prepend(1);
         Ok(())
     }
 
     fn build(&mut self) -> Result<(), SniprunError> {
+        if !self.dependencies.is_empty() {
+            return self.build_with_cargo();
+        }
+
         //write code to file
-        let mut _file =
-            File::create(&self.main_file_path).expect("Failed to create file for rust-original");
-        write(&self.main_file_path, &self.code).expect("Unable to write to file for rust-original");
+        write_code(&self.main_file_path, &self.code)?;
 
         //compile it (to the bin_path that arleady points to the rigth path)
-        let output = Command::new("rustc")
-            .arg("-O")
+        check_toolchain(&[&self.compiler[0]])?;
+        info!("[RUST] compiling with {:?} {:?}", self.compiler, self.flags);
+        let output = sandboxed_command(&self.data, &self.rust_work_dir, &self.compiler[0])
+            .args(&self.compiler[1..])
+            .args(&self.flags)
             .arg("--out-dir")
             .arg(&self.rust_work_dir)
             .arg(&self.main_file_path)
-            .output()
-            .expect("Unable to start process");
+            .output()?;
 
-        //TODO if relevant, return the error number (parse it from stderr)
         if !output.status.success() {
-            return Err(SniprunError::CompilationError("".to_string()));
+            Err(self.compilation_error(&String::from_utf8_lossy(&output.stderr)))
         } else {
-            return Ok(());
+            Ok(())
         }
     }
 
-    fn execute(&mut self) -> Result<String, SniprunError> {
+    fn execute(&mut self) -> Result<ExecutionResult, SniprunError> {
         //run th binary and get the std output (or stderr)
-        let output = Command::new(&self.bin_path)
-            .output()
-            .expect("Unable to start process");
+        let output = run_command(
+            &mut sandboxed_command(&self.data, &self.rust_work_dir, &self.bin_path),
+            &self.data,
+        )?;
         if output.status.success() {
-            return Ok(String::from_utf8(output.stdout).unwrap());
+            Ok(ExecutionResult::from_output(&output))
         } else {
-            return Err(SniprunError::RuntimeError(
-                String::from_utf8(output.stderr).unwrap(),
-            ));
+            Err(SniprunError::RuntimeError(
+                String::from_utf8_lossy(&output.stderr).to_string(),
+            ))
         }
     }
 }
+
+#[cfg(test)]
+mod test_rust_original {
+    use super::*;
+
+    #[test]
+    fn expression_tails_are_printed() {
+        let printed = Rust_original::print_last_expression;
+        assert_eq!(printed("2 + 2"), "println!(\"{:?}\", { 2 + 2 });");
+        assert_eq!(
+            printed("let a = 1;\n  a + 1\n"),
+            "let a = 1;\n  println!(\"{:?}\", { a + 1 });\n"
+        );
+        // the whole expression, not only its last line, and the lines don't move
+        let code = "let v = vec![1, 2];\nv.iter()\n    .map(|x| x * 2)\n    .collect::<Vec<_>>()";
+        assert_eq!(
+            printed(code),
+            "let v = vec![1, 2];\nprintln!(\"{:?}\", { v.iter()\n    .map(|x| x * 2)\n    .collect::<Vec<_>>() });"
+        );
+        let code = "let s = \"a; b\";\n// the length\ns.len()";
+        assert_eq!(
+            printed(code),
+            "let s = \"a; b\";\n// the length\nprintln!(\"{:?}\", { s.len() });"
+        );
+        assert_eq!(
+            printed("let c = '}';\nc"),
+            "let c = '}';\nprintln!(\"{:?}\", { c });"
+        );
+    }
+
+    #[test]
+    fn statement_tails_are_left_alone() {
+        for code in [
+            "let a = 1;",
+            "println!(\"{}\", 1);",
+            "for i in 0..2 {\n    println!(\"{}\", i)\n}",
+            "fn f() -> i32 {\n    1\n}",
+            "let v = vec![\n    1,\n    2,\n];",
+            "// nothing\n",
+            "",
+        ] {
+            assert_eq!(Rust_original::print_last_expression(code), code);
+        }
+    }
+
+    #[test]
+    fn only_the_annotations_are_dependencies() {
+        let code = "//#region setup\n//# note: not a dependency\n//# dep: serde_json = \"1\"\n\
+                    //! sniprun-dep: rand = { version = \"0.8\" }\nlet a = 1; //# dep: x = \"1\"";
+        assert_eq!(
+            Rust_original::parse_dependencies(code).unwrap(),
+            vec![
+                (String::from("serde_json"), String::from("\"1\"")),
+                (String::from("rand"), String::from("{ version = \"0.8\" }")),
+            ]
+        );
+        let error = Rust_original::parse_dependencies("//# dep: serde json").unwrap_err();
+        assert!(error.to_string().contains("expected '//# dep: name = \"version\"'"));
+    }
+
+    #[test]
+    fn the_value_of_the_last_expression_is_shown() {
+        if check_toolchain(&["rustc"]).is_err() {
+            return;
+        }
+        let code = "let a = [1, 2, 3];\na.iter()\n    .sum::<i32>()";
+        let mut data = DataHolder::with_code_in_temp_dir("rust", code);
+        data.print_last_expression = true;
+        let mut interpreter: Box<Rust_original> = data.into_prepared(&[]);
+        let result = interpreter.run_at_level(SupportLevel::Bloc).unwrap();
+        assert_eq!(result.stdout.trim(), "6");
+    }
+
+    #[test]
+    fn the_prelude_is_used_and_the_errors_point_at_the_code() {
+        if check_toolchain(&["rustc"]).is_err() {
+            return;
+        }
+        let prelude = "use std::collections::HashMap;\nuse std::fmt::Write;";
+        let run = |code: &str| {
+            let mut data = DataHolder::with_code_in_temp_dir("rust", code);
+            data.preludes
+                .insert(String::from("rust"), String::from(prelude));
+            data.into_prepared::<Rust_original>(&[])
+                .run_at_level(SupportLevel::Bloc)
+        };
+        let result = run("let mut m = HashMap::new();\nm.insert(1, 2);\nprint!(\"{}\", m[&1]);");
+        assert_eq!(result.unwrap().stdout, "2");
+
+        let error = run("let m: HashMap<i32, i32> = HashMap::new();\nlet a: u8 = m;").unwrap_err();
+        assert!(matches!(error.inner(), SniprunError::CompilationError(_)));
+        assert_eq!(error.line(), Some(2));
+    }
+}
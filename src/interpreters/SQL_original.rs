@@ -0,0 +1,236 @@
+#[derive(Debug, Clone)]
+#[allow(non_camel_case_types)]
+pub struct SQL_original {
+    support_level: SupportLevel,
+    data: DataHolder,
+    code: String,
+
+    ///specific to sql
+    sql_work_dir: String,
+    main_file_path: String,
+    ///the database the statements run against: a file, or ":memory:" for a new empty one
+    database: String,
+    ///the client running the statements: "sqlite3", "psql" or "mysql"
+    backend: String,
+    ///how psql or mysql connect to the database (interpreter option `connection`)
+    connection: Vec<String>,
+}
+
+lazy_static! {
+    /// sqlite3 reports errors as 'Parse error near line N: message' or 'Runtime error near
+    /// line N: message', N being the line of the statement that failed
+    static ref SQLITE_ERROR: Regex =
+        Regex::new(r"(Parse|Runtime) error near line (\d+): ([^\n]*)").unwrap();
+    /// psql reports errors as 'psql:/path/to/main.sql:LINE: ERROR:  message'
+    static ref PSQL_ERROR: Regex = Regex::new(r"main\.sql:(\d+): ERROR:\s+([^\n]*)").unwrap();
+    /// mysql reports errors as 'ERROR 1064 (42000) at line LINE: message', 1064 being a
+    /// syntax error
+    static ref MYSQL_ERROR: Regex =
+        Regex::new(r"ERROR (\d+) \(\w+\) at line (\d+)(?: in file: '[^']*')?: ([^\n]*)").unwrap();
+}
+
+impl SQL_original {
+    /// the database file the user chose (interpreter option `database`), relative to the
+    /// directory of the edited file; an in-memory database by default
+    fn select_database(data: &DataHolder) -> String {
+        let database = match data
+            .interpreter_option(&Self::get_name(), "database")
+            .and_then(|d| d.as_str())
+        {
+            Some(database) if !database.is_empty() => database,
+            _ => return String::from(":memory:"),
+        };
+        if database == "scratch" {
+            return Self::scratch_database(data);
+        }
+        if database == ":memory:" || Path::new(database).is_absolute() {
+            return database.to_string();
+        }
+        match Path::new(&data.filepath).parent() {
+            Some(dir) if dir.is_absolute() => dir.join(database).to_string_lossy().to_string(),
+            _ => database.to_string(),
+        }
+    }
+
+    /// the database kept in the work directory (`database` set to 'scratch'): its tables
+    /// persist from one run to the next, until `:SnipReset`
+    fn scratch_database(data: &DataHolder) -> String {
+        work_dir_path(data, &Self::get_work_dir_name()) + "/scratch.db"
+    }
+
+    /// the connection the user gave (interpreter option `connection`): a connection string for
+    /// psql, the arguments of the mysql command (eg: '-u user -h host database') for mysql
+    fn connection(data: &DataHolder) -> Vec<String> {
+        let connection = data.interpreter_option(&Self::get_name(), "connection");
+        match (
+            connection.and_then(|c| c.as_array()),
+            connection.and_then(|c| c.as_str()),
+        ) {
+            (Some(arguments), _) => arguments
+                .iter()
+                .filter_map(|a| a.as_str().map(String::from))
+                .collect(),
+            (_, Some(connection)) => connection.split_whitespace().map(String::from).collect(),
+            _ => vec![],
+        }
+    }
+
+    /// run the statements of the generated file one after the other, stopping at the first
+    /// that fails; the rows of the queries are printed as tables, with their headers
+    fn run_statements(&self) -> Result<std::process::Output, SniprunError> {
+        let mut command = match self.backend.as_str() {
+            "psql" => {
+                check_toolchain(&["psql"])?;
+                let mut command = Command::new("psql");
+                // a whole connection string is a single argument, unlike mysql's
+                command
+                    .arg("--no-psqlrc")
+                    .arg("--set=ON_ERROR_STOP=1")
+                    .arg("--dbname")
+                    .arg(self.connection.join(" "))
+                    .arg("--file")
+                    .arg(&self.main_file_path);
+                command
+            }
+            "mysql" => {
+                check_toolchain(&["mysql"])?;
+                let mut command = Command::new("mysql");
+                command
+                    .arg("--table")
+                    .args(&self.connection)
+                    .arg("--execute")
+                    .arg(format!("source {}", self.main_file_path));
+                command
+            }
+            _ => {
+                check_toolchain(&["sqlite3"])?;
+                let mut command = Command::new("sqlite3");
+                command
+                    .arg("-bail")
+                    .arg("-header")
+                    .arg("-table")
+                    .arg(&self.database)
+                    .arg(format!(".read {}", self.main_file_path));
+                command
+            }
+        };
+        Ok(run_command(
+            command.current_dir(&self.sql_work_dir),
+            &self.data,
+        )?)
+    }
+
+    /// the error of the statement that failed: a syntax error doesn't compile, any other
+    /// (eg: a constraint that fails) is a runtime error
+    fn error(&self, stderr: &str) -> SniprunError {
+        let message = stderr.trim().to_string();
+        let (syntax_error, line) = if let Some(captures) = SQLITE_ERROR.captures(stderr) {
+            (
+                &captures[1] == "Parse" && captures[3].contains("syntax error"),
+                captures[2].parse::<i64>().ok(),
+            )
+        } else if let Some(captures) = PSQL_ERROR.captures(stderr) {
+            (
+                captures[2].starts_with("syntax error"),
+                captures[1].parse::<i64>().ok(),
+            )
+        } else if let Some(captures) = MYSQL_ERROR.captures(stderr) {
+            (&captures[1] == "1064", captures[2].parse::<i64>().ok())
+        } else {
+            return SniprunError::RuntimeError(message);
+        };
+        // the statement that failed, in case the client doesn't quote it
+        let statement = line
+            .filter(|line| *line > 0)
+            .and_then(|line| self.code.lines().nth(line as usize - 1))
+            .map(str::trim)
+            .filter(|statement| !statement.is_empty() && !message.contains(statement));
+        let message = match statement {
+            Some(statement) => format!("{}\nin: {}", message, statement),
+            None => message,
+        };
+        let error = if syntax_error {
+            SniprunError::CompilationError(message)
+        } else {
+            SniprunError::RuntimeError(message)
+        };
+        match line.and_then(|line| self.to_buffer_line(line)) {
+            Some(line) => error.at_line(line),
+            None => error,
+        }
+    }
+}
+
+impl Interpreter for SQL_original {
+    declare_interpreter!(SQL_original);
+
+    fn new_with_level(data: DataHolder, support_level: SupportLevel) -> Box<SQL_original> {
+        let swd = work_dir_path(&data, &Self::get_work_dir_name());
+        let mfp = swd.clone() + "/main.sql";
+        let database = Self::select_database(&data);
+        let backend = data
+            .interpreter_option(&Self::get_name(), "backend")
+            .and_then(|b| b.as_str())
+            .unwrap_or("sqlite3")
+            .to_string();
+        let connection = Self::connection(&data);
+        Box::new(SQL_original {
+            data,
+            support_level,
+            code: String::from(""),
+            sql_work_dir: swd,
+            main_file_path: mfp,
+            database,
+            backend,
+            connection,
+        })
+    }
+
+    fn get_supported_languages() -> Vec<String> {
+        vec![String::from("sql")]
+    }
+
+    fn get_generated_file(&self) -> Option<String> {
+        Some(self.main_file_path.clone())
+    }
+
+    fn get_max_support_level() -> SupportLevel {
+        SupportLevel::Bloc
+    }
+
+    fn get_capabilities() -> Capabilities {
+        Capabilities::REPL
+    }
+
+    fn clean_repl_memory(data: &DataHolder) -> Result<(), SniprunError> {
+        let scratch = Self::scratch_database(data);
+        if Path::new(&scratch).is_file() {
+            info!("[SQL] removing the scratch database");
+            std::fs::remove_file(scratch)?;
+        }
+        Ok(())
+    }
+
+    fn fetch_code(&mut self) -> Result<(), SniprunError> {
+        self.code = selected_code(&self.data, self.get_current_level());
+        Ok(())
+    }
+
+    fn add_boilerplate(&mut self) -> Result<(), SniprunError> {
+        Ok(())
+    }
+
+    fn build(&mut self) -> Result<(), SniprunError> {
+        write_code(&self.main_file_path, &self.code)
+    }
+
+    fn execute(&mut self) -> Result<ExecutionResult, SniprunError> {
+        info!("[SQL] running with {} against {}", self.backend, self.database);
+        let output = self.run_statements()?;
+        if output.status.success() {
+            Ok(ExecutionResult::from_output(&output))
+        } else {
+            Err(self.error(&String::from_utf8_lossy(&output.stderr)))
+        }
+    }
+}
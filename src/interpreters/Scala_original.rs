@@ -0,0 +1,338 @@
+#[derive(Debug, Clone)]
+#[allow(non_camel_case_types)]
+pub struct Scala_original {
+    support_level: SupportLevel,
+    data: DataHolder,
+    code: String,
+
+    ///specific to scala
+    scala_work_dir: String,
+    main_file_path: String,
+    ///"scala-cli", "scalac" (compiled, then run by scala) or "scala"
+    runner: String,
+    line_offsets: LineOffsets,
+}
+
+lazy_static! {
+    /// the code defines its own entry point
+    static ref SCALA_MAIN: Regex =
+        Regex::new(r"(?m)^\s*(@main\b|.*\bdef\s+main\s*\(|.*\bextends\s+App\b)").unwrap();
+    /// the compiler locates errors as 'main.scala:LINE:COLUMN', a stack trace as
+    /// '(main.scala:LINE)'
+    static ref SCALA_LOCATION: Regex = Regex::new(r"main\.scala(:|\(|:\()(\d+)").unwrap();
+    /// the code didn't compile (rather than failed when it ran)
+    static ref SCALA_COMPILATION_ERROR: Regex =
+        Regex::new(r"Compilation failed|\[E\d{3}\]|(?m)^\[error\]|: error:|(?m)^-- .*Error: ")
+            .unwrap();
+    /// the entry point of the code, that scala runs: a @main method or an object
+    static ref SCALA_ENTRY_POINT: Regex =
+        Regex::new(r"@main\s+def\s+(\w+)|\bobject\s+(\w+)").unwrap();
+}
+
+impl Scala_original {
+    /// the lines that must be at the top of the file: scala-cli's directives and the imports
+    fn is_header(line: &str) -> bool {
+        let line = line.trim_start();
+        line.starts_with("//> using ") || line.starts_with("import ")
+    }
+
+    /// if the user didn't choose (interpreter option `runner`), scala-cli if it is installed,
+    /// else scalac
+    fn select_runner(data: &DataHolder) -> String {
+        if let Some(runner) = data
+            .interpreter_option(&Self::get_name(), "runner")
+            .and_then(|r| r.as_str())
+        {
+            return runner.to_string();
+        }
+        if check_toolchain(&["scala-cli"]).is_ok() {
+            String::from("scala-cli")
+        } else if check_toolchain(&["scalac"]).is_ok() {
+            String::from("scalac")
+        } else {
+            String::from("scala")
+        }
+    }
+
+    /// where scalac puts the classes of the code, named by a hash of the code: the same code
+    /// is only compiled once
+    fn classes_dir(&self) -> String {
+        format!(
+            "{}/classes/{}",
+            self.scala_work_dir,
+            crate::cache::key(&self.code)
+        )
+    }
+
+    /// compile the code with scalac, unless its classes are there already
+    fn compile(&self) -> Result<(), SniprunError> {
+        let classes_dir = self.classes_dir();
+        if Path::new(&classes_dir).is_dir() {
+            info!("[SCALA] reusing {}", classes_dir);
+            return Ok(());
+        }
+        check_toolchain(&["scalac"])?;
+        // only the classes of the last code are kept
+        let _ = std::fs::remove_dir_all(self.scala_work_dir.clone() + "/classes");
+        std::fs::create_dir_all(&classes_dir)?;
+        let output = Command::new("scalac")
+            .arg("-d")
+            .arg(&classes_dir)
+            .arg(&self.main_file_path)
+            .current_dir(&self.scala_work_dir)
+            .output()?;
+        if output.status.success() {
+            return Ok(());
+        }
+        let _ = std::fs::remove_dir_all(&classes_dir);
+        let report = String::from_utf8_lossy(&output.stderr).to_string()
+            + &String::from_utf8_lossy(&output.stdout);
+        Err(self.error(&report))
+    }
+
+    /// the class scala runs: the one of the @main method, or of the first object
+    fn main_class(&self) -> String {
+        SCALA_ENTRY_POINT
+            .captures(&self.code)
+            .and_then(|captures| captures.get(1).or_else(|| captures.get(2)))
+            .map_or_else(|| String::from("run"), |name| name.as_str().to_string())
+    }
+
+    fn command(&self, action: &str) -> Command {
+        let mut command = Command::new(&self.runner);
+        command.arg(action);
+        // scala-cli keeps a compilation server running between runs, unless told not to
+        let server = self
+            .data
+            .interpreter_option(&Self::get_name(), "server")
+            .map(|v| config_bool(v, true))
+            .unwrap_or(true);
+        if self.runner == "scala-cli" && !server {
+            command.arg("--server=false");
+        }
+        command
+            .arg(&self.main_file_path)
+            .current_dir(&self.scala_work_dir);
+        command
+    }
+
+    /// the error of a failed run (or compilation), with the lines of the code rather than the
+    /// ones of the generated file, located in the buffer where the first error is
+    fn error(&self, report: &str) -> SniprunError {
+        let mut first_line = None;
+        let message = SCALA_LOCATION.replace_all(report, |captures: &regex::Captures| {
+            let code_line = captures[2]
+                .parse::<i64>()
+                .ok()
+                .and_then(|line| self.line_offsets.code_line(line));
+            if first_line.is_none() {
+                first_line = code_line.and_then(|line| self.data.buffer_line(line));
+            }
+            match code_line {
+                Some(line) => format!("main.scala{}{}", &captures[1], line),
+                None => captures[0].to_string(),
+            }
+        });
+        let error = if SCALA_COMPILATION_ERROR.is_match(&message) {
+            SniprunError::CompilationError(message.to_string())
+        } else {
+            SniprunError::RuntimeError(message.to_string())
+        };
+        match first_line {
+            Some(line) => error.at_line(line),
+            None => error,
+        }
+    }
+}
+
+impl Interpreter for Scala_original {
+    declare_interpreter!(Scala_original);
+
+    fn new_with_level(data: DataHolder, support_level: SupportLevel) -> Box<Scala_original> {
+        let swd = work_dir_path(&data, &Self::get_work_dir_name());
+        let mfp = swd.clone() + "/main.scala";
+        let runner = Self::select_runner(&data);
+        Box::new(Scala_original {
+            data,
+            support_level,
+            code: String::from(""),
+            scala_work_dir: swd,
+            main_file_path: mfp,
+            runner,
+            line_offsets: LineOffsets::default(),
+        })
+    }
+
+    fn get_supported_languages() -> Vec<String> {
+        vec![String::from("scala"), String::from("sc")]
+    }
+
+    fn get_generated_file(&self) -> Option<String> {
+        Some(self.main_file_path.clone())
+    }
+
+    fn get_max_support_level() -> SupportLevel {
+        SupportLevel::Bloc
+    }
+
+    fn get_capabilities() -> Capabilities {
+        Capabilities::STREAMING
+    }
+
+    fn get_line_offsets(&self) -> LineOffsets {
+        self.line_offsets.clone()
+    }
+
+    fn fetch_code(&mut self) -> Result<(), SniprunError> {
+        self.code = selected_code(&self.data, self.get_current_level());
+        Ok(())
+    }
+
+    fn add_boilerplate(&mut self) -> Result<(), SniprunError> {
+        self.line_offsets = LineOffsets::default();
+        if SCALA_MAIN.is_match(&self.code) {
+            return Ok(());
+        }
+        // the directives and imports are moved before the entry point, and blanked in place so
+        // that the lines of the code don't move
+        let headers: Vec<&str> = self
+            .code
+            .lines()
+            .filter(|l| Self::is_header(l))
+            .map(|l| l.trim())
+            .collect();
+        let body: Vec<&str> = self
+            .code
+            .lines()
+            .map(|l| if Self::is_header(l) { "" } else { l })
+            .collect();
+        let mut code = String::new();
+        for header in &headers {
+            code = code + header + "\n";
+        }
+        code = code + "@main def run() = {\n" + &body.join("\n") + "\n}\n";
+        self.line_offsets.prepend(headers.len() as i64 + 1);
+        self.code = code;
+        Ok(())
+    }
+
+    fn build(&mut self) -> Result<(), SniprunError> {
+        write_code(&self.main_file_path, &self.code)
+    }
+
+    fn check(&mut self) -> Result<ExecutionResult, SniprunError> {
+        self.build()?;
+        if self.runner == "scalac" {
+            self.compile()?;
+            return Ok(ExecutionResult::new(String::from("compiled OK\n")));
+        }
+        if self.runner != "scala-cli" {
+            return Ok(ExecutionResult::new(format!(
+                "no compile step with {}, nothing was checked\n",
+                self.runner
+            )));
+        }
+        check_toolchain(&["scala-cli"])?;
+        syntax_check(&mut self.command("compile"), |report| self.error(report))
+    }
+
+    fn execute(&mut self) -> Result<ExecutionResult, SniprunError> {
+        if self.runner != "scalac" {
+            check_toolchain(&[&self.runner])?;
+        }
+        let mut command = if self.runner == "scala-cli" {
+            self.command("run")
+        } else if self.runner == "scalac" {
+            self.compile()?;
+            check_toolchain(&["scala"])?;
+            let mut command = Command::new("scala");
+            command
+                .arg("-classpath")
+                .arg(self.classes_dir())
+                .arg(self.main_class())
+                .current_dir(&self.scala_work_dir);
+            command
+        } else {
+            let mut command = Command::new(&self.runner);
+            command
+                .arg(&self.main_file_path)
+                .current_dir(&self.scala_work_dir);
+            command
+        };
+        let output = run_command(&mut command, &self.data)?;
+        if output.status.success() {
+            return Ok(ExecutionResult::from_output(&output));
+        }
+        // scala-cli reports the compilation errors on stderr, along with the ones of the code
+        Err(self.error(&String::from_utf8_lossy(&output.stderr)))
+    }
+}
+
+#[cfg(test)]
+mod test_scala_original {
+    use super::*;
+
+    fn scala(code: &str, runner: Option<&str>) -> Box<Scala_original> {
+        let options: Vec<_> = runner
+            .map(|runner| ("runner", neovim_lib::Value::from(runner)))
+            .into_iter()
+            .collect();
+        DataHolder::prepared("scala", code, &options)
+    }
+
+    #[test]
+    fn bare_statements_are_wrapped_after_the_headers() {
+        let interpreter = scala("//> using scala 3\nval a = 1\nimport scala.util.Try\nprintln(a)", None);
+        assert_eq!(
+            interpreter.code,
+            "//> using scala 3\nimport scala.util.Try\n@main def run() = {\n\nval a = 1\n\nprintln(a)\n}\n"
+        );
+        assert_eq!(interpreter.to_buffer_line(6), Some(3));
+        assert_eq!(interpreter.main_class(), "run");
+
+        let code = "object Hello extends App {\n  println(1)\n}";
+        let interpreter = scala(code, None);
+        assert_eq!(interpreter.code, code);
+        assert_eq!(interpreter.main_class(), "Hello");
+        assert_eq!(scala("@main def hi() = println(1)", None).main_class(), "hi");
+    }
+
+    #[test]
+    fn the_compiled_classes_of_the_same_code_are_reused() {
+        let interpreter = scala("println(1)", Some("scalac"));
+        assert_eq!(interpreter.runner, "scalac");
+        let classes_dir = interpreter.classes_dir();
+        let name = |dir: &str| Path::new(dir).file_name().unwrap().to_os_string();
+        let same = scala("println(1)", None).classes_dir();
+        assert_eq!(name(&classes_dir), name(&same));
+        assert_ne!(name(&classes_dir), name(&scala("println(2)", None).classes_dir()));
+        // there, no scalac is needed
+        std::fs::create_dir_all(&classes_dir).unwrap();
+        interpreter.compile().unwrap();
+    }
+
+    #[test]
+    fn errors_point_at_the_code() {
+        let interpreter = scala("val a = 1\nval b: String = a", None);
+        let error = interpreter.error(
+            "[error] ./main.scala:3:17\n[error] Found:    (a : Int)\n[error] Required: String",
+        );
+        assert!(matches!(error.inner(), SniprunError::CompilationError(_)));
+        assert_eq!(error.line(), Some(2));
+        assert!(error.inner().to_string().contains("main.scala:2:17"));
+
+        let error = interpreter.error(
+            "Exception in thread \"main\" java.lang.ArithmeticException: / by zero\n\tat main$package$.run(main.scala:2)",
+        );
+        assert!(matches!(error.inner(), SniprunError::RuntimeError(_)));
+        assert_eq!(error.line(), Some(1));
+    }
+
+    #[test]
+    fn a_snippet_runs() {
+        if check_toolchain(&["scala-cli"]).is_err() && check_toolchain(&["scalac"]).is_err() {
+            return;
+        }
+        assert_eq!(scala("println(1+1)", None).run().unwrap().stdout, "2\n");
+    }
+}
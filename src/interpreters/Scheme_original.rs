@@ -0,0 +1,260 @@
+#[derive(Debug, Clone)]
+#[allow(non_camel_case_types)]
+pub struct Scheme_original {
+    support_level: SupportLevel,
+    data: DataHolder,
+    code: String,
+
+    ///specific to scheme
+    scheme_work_dir: String,
+    main_file_path: String,
+    ///"racket", "guile" or "chicken"
+    dialect: String,
+    line_offsets: LineOffsets,
+}
+
+lazy_static! {
+    /// the implementations locate errors as 'main.rkt:LINE:COLUMN' or 'main.scm:LINE:COLUMN'
+    static ref SCHEME_LOCATION: Regex = Regex::new(r"(main\.(?:rkt|scm):)(\d+)").unwrap();
+    /// the code couldn't be read or expanded, rather than failed when it ran
+    static ref SCHEME_COMPILATION_ERROR: Regex = Regex::new(
+        r"read-syntax|read error|unbound identifier|bad syntax|[Ss]yntax error|unexpected end of input"
+    )
+    .unwrap();
+}
+
+impl Scheme_original {
+    /// racket for its filetypes or for code that starts with a #lang line, else the scheme the
+    /// user chose (interpreter option `implementation`), guile by default
+    fn select_dialect(data: &DataHolder) -> String {
+        if ["racket", "rkt"].contains(&data.filetype.as_str())
+            || data.current_bloc.trim_start().starts_with("#lang")
+        {
+            return String::from("racket");
+        }
+        data.interpreter_option(&Self::get_name(), "implementation")
+            .and_then(|i| i.as_str())
+            .unwrap_or("guile")
+            .to_string()
+    }
+
+    /// the byte range of the last top-level form of the code, if any
+    fn last_form(code: &str) -> Option<(usize, usize)> {
+        let mut last = None;
+        let mut depth = 0;
+        let mut start = None;
+        let mut chars = code.char_indices().peekable();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                ';' => {
+                    // a comment, up to the end of the line
+                    while chars.next_if(|(_, c)| *c != '\n').is_some() {}
+                    continue;
+                }
+                '"' => {
+                    start = start.or(Some(i));
+                    while let Some((_, c)) = chars.next() {
+                        match c {
+                            '\\' => {
+                                chars.next();
+                            }
+                            '"' => break,
+                            _ => (),
+                        }
+                    }
+                }
+                '#' if chars.peek().is_some_and(|(_, c)| *c == '\\') => {
+                    // a character, eg: #\( or #\space
+                    start = start.or(Some(i));
+                    chars.next();
+                    chars.next();
+                }
+                '(' | '[' => {
+                    start = start.or(Some(i));
+                    depth += 1;
+                }
+                ')' | ']' => depth -= 1,
+                c if c.is_whitespace() => {
+                    if depth == 0 {
+                        if let Some(s) = start.take() {
+                            last = Some((s, i));
+                        }
+                    }
+                    continue;
+                }
+                _ => start = start.or(Some(i)),
+            }
+            let end = chars.peek().map_or(code.len(), |(i, _)| *i);
+            if depth == 0 && matches!(c, ')' | ']' | '"') {
+                if let Some(s) = start.take() {
+                    last = Some((s, end));
+                }
+            }
+        }
+        if let Some(s) = start {
+            last = Some((s, code.len()));
+        }
+        last
+    }
+
+    /// the value of the last form of the code, if it is an expression, is written like a
+    /// REPL would, in place so that the lines of the code don't move
+    fn print_last_form(&self, code: &str) -> String {
+        let (start, end) = match Self::last_form(code) {
+            Some(range) => range,
+            None => return code.to_string(),
+        };
+        let form = &code[start..end];
+        let definitions = ["(define", "(import", "(use-modules", "(require", "(set!"];
+        if definitions.iter().any(|d| form.starts_with(d)) {
+            return code.to_string();
+        }
+        let unspecified = match self.dialect.as_str() {
+            "chicken" => "(eq? value (void))",
+            _ => "(unspecified? value)",
+        };
+        format!(
+            "{}(let ((value {})) (if (not {}) (begin (write value) (newline)))){}",
+            &code[..start],
+            form,
+            unspecified,
+            &code[end..]
+        )
+    }
+
+    /// the error, with the lines of the code rather than the ones of the generated file,
+    /// located in the buffer where the first error is
+    fn error(&self, stderr: &str) -> SniprunError {
+        let mut first_line = None;
+        let message = SCHEME_LOCATION.replace_all(stderr, |captures: &regex::Captures| {
+            let line = captures[2]
+                .parse::<i64>()
+                .ok()
+                .and_then(|line| self.line_offsets.code_line(line));
+            if first_line.is_none() {
+                first_line = line.and_then(|line| self.data.buffer_line(line));
+            }
+            match line {
+                Some(line) => format!("{}{}", &captures[1], line),
+                None => captures[0].to_string(),
+            }
+        });
+        let error = if SCHEME_COMPILATION_ERROR.is_match(&message) {
+            SniprunError::CompilationError(message.to_string())
+        } else {
+            SniprunError::RuntimeError(message.to_string())
+        };
+        match first_line {
+            Some(line) => error.at_line(line),
+            None => error,
+        }
+    }
+}
+
+impl Interpreter for Scheme_original {
+    declare_interpreter!(Scheme_original);
+
+    fn new_with_level(data: DataHolder, support_level: SupportLevel) -> Box<Scheme_original> {
+        let swd = work_dir_path(&data, &Self::get_work_dir_name());
+        let dialect = Self::select_dialect(&data);
+        // racket wants its own extension to read the #lang line
+        let mfp = if dialect == "racket" {
+            swd.clone() + "/main.rkt"
+        } else {
+            Self::temp_source_path(&data)
+        };
+        Box::new(Scheme_original {
+            data,
+            support_level,
+            code: String::from(""),
+            scheme_work_dir: swd,
+            main_file_path: mfp,
+            dialect,
+            line_offsets: LineOffsets::default(),
+        })
+    }
+
+    fn get_supported_languages() -> Vec<String> {
+        vec![
+            String::from("scheme"),
+            String::from("scm"),
+            String::from("racket"),
+            String::from("rkt"),
+        ]
+    }
+
+    fn get_file_extension() -> String {
+        String::from("scm")
+    }
+
+    fn get_generated_file(&self) -> Option<String> {
+        Some(self.main_file_path.clone())
+    }
+
+    fn get_max_support_level() -> SupportLevel {
+        SupportLevel::Bloc
+    }
+
+    fn get_capabilities() -> Capabilities {
+        Capabilities::STREAMING
+    }
+
+    fn get_line_offsets(&self) -> LineOffsets {
+        self.line_offsets.clone()
+    }
+
+    fn fetch_code(&mut self) -> Result<(), SniprunError> {
+        self.code = selected_code(&self.data, self.get_current_level());
+        Ok(())
+    }
+
+    fn add_boilerplate(&mut self) -> Result<(), SniprunError> {
+        self.line_offsets = LineOffsets::default();
+        if self.dialect != "racket" {
+            self.code = self.print_last_form(&self.code);
+            return Ok(());
+        }
+        // a racket module prints the values of its expressions itself
+        if !self.code.trim_start().starts_with("#lang") {
+            self.code = String::from("#lang racket\n") + &self.code;
+            self.line_offsets.prepend(1);
+        }
+        Ok(())
+    }
+
+    fn build(&mut self) -> Result<(), SniprunError> {
+        write_code(&self.main_file_path, &self.code)
+    }
+
+    fn execute(&mut self) -> Result<ExecutionResult, SniprunError> {
+        let mut command = match self.dialect.as_str() {
+            "racket" => {
+                check_toolchain(&["racket"])?;
+                let mut command = Command::new("racket");
+                command.arg(&self.main_file_path);
+                command
+            }
+            "chicken" => {
+                check_toolchain(&["csi"])?;
+                let mut command = Command::new("csi");
+                command.arg("-s").arg(&self.main_file_path);
+                command
+            }
+            _ => {
+                check_toolchain(&["guile"])?;
+                let mut command = Command::new("guile");
+                command
+                    .arg("--no-auto-compile")
+                    .arg("-s")
+                    .arg(&self.main_file_path);
+                command
+            }
+        };
+        let output = run_command(command.current_dir(&self.scheme_work_dir), &self.data)?;
+        if output.status.success() {
+            Ok(ExecutionResult::from_output(&output))
+        } else {
+            Err(self.error(&String::from_utf8_lossy(&output.stderr)))
+        }
+    }
+}
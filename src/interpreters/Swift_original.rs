@@ -0,0 +1,159 @@
+#[derive(Debug, Clone)]
+#[allow(non_camel_case_types)]
+pub struct Swift_original {
+    support_level: SupportLevel,
+    data: DataHolder,
+    code: String,
+
+    ///specific to swift
+    swift_work_dir: String,
+    main_file_path: String,
+    ///compile the code with swiftc (the binary of the same code is reused), rather than
+    ///running it with the interpreter
+    compiled: bool,
+    ///the binary of the code, named by a hash of the code, when compiled
+    bin_path: String,
+}
+
+/// how many compiled binaries are kept, the oldest ones are removed first
+const KEPT_SWIFT_BINARIES: usize = 20;
+
+lazy_static! {
+    /// the compiler locates errors as 'main.swift:LINE:COLUMN: error: message'
+    static ref SWIFT_COMPILATION_ERROR: Regex =
+        Regex::new(r"main\.swift:(\d+):\d+: error: ").unwrap();
+    /// a fatal error is located as 'main.swift:LINE: Fatal error: message' or as 'file
+    /// main.swift, line LINE'
+    static ref SWIFT_FATAL_ERROR: Regex = Regex::new(r"main\.swift(?::|, line )(\d+)").unwrap();
+}
+
+impl Swift_original {
+    /// the error, located at the first line of the generated file the regex captures
+    fn located(&self, error: SniprunError, regex: &Regex, output: &str) -> SniprunError {
+        let line = regex
+            .captures(output)
+            .and_then(|captures| captures[1].parse::<i64>().ok())
+            .and_then(|line| self.to_buffer_line(line));
+        match line {
+            Some(line) => error.at_line(line),
+            None => error,
+        }
+    }
+
+    /// the error of a failed compilation or run: the interpreter compiles the code before
+    /// running it, and reports the compilation errors the same way swiftc does
+    fn error(&self, stderr: &str) -> SniprunError {
+        if SWIFT_COMPILATION_ERROR.is_match(stderr) {
+            self.located(
+                SniprunError::CompilationError(stderr.to_string()),
+                &SWIFT_COMPILATION_ERROR,
+                stderr,
+            )
+        } else {
+            self.located(
+                SniprunError::RuntimeError(stderr.to_string()),
+                &SWIFT_FATAL_ERROR,
+                stderr,
+            )
+        }
+    }
+}
+
+impl Interpreter for Swift_original {
+    declare_interpreter!(Swift_original);
+
+    fn new_with_level(data: DataHolder, support_level: SupportLevel) -> Box<Swift_original> {
+        let swd = work_dir_path(&data, &Self::get_work_dir_name());
+        let mfp = Self::temp_source_path(&data);
+        let compiled = data
+            .interpreter_option(&Self::get_name(), "compiled")
+            .map(|c| config_bool(c, false))
+            .unwrap_or(false);
+        Box::new(Swift_original {
+            data,
+            support_level,
+            code: String::from(""),
+            swift_work_dir: swd,
+            main_file_path: mfp,
+            compiled,
+            bin_path: String::new(),
+        })
+    }
+
+    fn get_supported_languages() -> Vec<String> {
+        vec![String::from("swift")]
+    }
+
+    fn get_file_extension() -> String {
+        String::from("swift")
+    }
+
+    fn get_generated_file(&self) -> Option<String> {
+        Some(self.main_file_path.clone())
+    }
+
+    fn get_max_support_level() -> SupportLevel {
+        SupportLevel::Bloc
+    }
+
+    fn get_capabilities() -> Capabilities {
+        Capabilities::STREAMING
+    }
+
+    fn fetch_code(&mut self) -> Result<(), SniprunError> {
+        self.code = selected_code(&self.data, self.get_current_level());
+        Ok(())
+    }
+
+    fn add_boilerplate(&mut self) -> Result<(), SniprunError> {
+        // main.swift runs its top-level code, nothing to add
+        self.bin_path = format!(
+            "{}/{}.bin",
+            self.swift_work_dir,
+            crate::cache::key(&self.code)
+        );
+        Ok(())
+    }
+
+    fn build(&mut self) -> Result<(), SniprunError> {
+        write_code(&self.main_file_path, &self.code)?;
+        if !self.compiled {
+            return Ok(());
+        }
+        if Path::new(&self.bin_path).is_file() {
+            info!("[SWIFT] reusing {}", self.bin_path);
+            return Ok(());
+        }
+
+        check_toolchain(&["swiftc"])?;
+        let output = Command::new("swiftc")
+            .arg("-Onone")
+            .arg("-o")
+            .arg(&self.bin_path)
+            .arg(&self.main_file_path)
+            .current_dir(&self.swift_work_dir)
+            .output()?;
+        if !output.status.success() {
+            return Err(self.error(&String::from_utf8_lossy(&output.stderr)));
+        }
+        remove_oldest_files(&self.swift_work_dir, "bin", KEPT_SWIFT_BINARIES);
+        Ok(())
+    }
+
+    fn execute(&mut self) -> Result<ExecutionResult, SniprunError> {
+        let mut command = if self.compiled {
+            Command::new(&self.bin_path)
+        } else {
+            check_toolchain(&["swift"])?;
+            let mut command = Command::new("swift");
+            command.arg(&self.main_file_path);
+            command
+        };
+        let output = run_command(command.current_dir(&self.swift_work_dir), &self.data)?;
+        if output.status.success() {
+            Ok(ExecutionResult::from_output(&output))
+        } else {
+            Err(self.error(&String::from_utf8_lossy(&output.stderr)))
+        }
+    }
+}
@@ -0,0 +1,265 @@
+#[derive(Debug, Clone)]
+#[allow(non_camel_case_types)]
+pub struct TS_original {
+    support_level: SupportLevel,
+    data: DataHolder,
+    code: String,
+
+    ///specific to typescript
+    ts_work_dir: String,
+    main_file_path: String,
+    ///"deno" or "ts-node" to run the code in one step, or "tsc" to transpile it and run it
+    ///with node
+    runner: String,
+    ///the permissions given to deno, eg: ["--allow-read", "--allow-net"]
+    permissions: Vec<String>,
+    line_offsets: LineOffsets,
+}
+
+lazy_static! {
+    /// deno locates errors as 'main.ts:LINE:COLUMN', tsc and ts-node as 'main.ts(LINE,COLUMN)'
+    static ref TS_LOCATION: Regex =
+        Regex::new(r"main\.ts(?::(\d+):(\d+)|\((\d+),(\d+)\))").unwrap();
+    /// type errors are reported with their code (eg: 'TS2322'), syntax errors by deno as
+    /// 'The module's source code could not be parsed'
+    static ref TS_COMPILATION_ERROR: Regex =
+        Regex::new(r"\bTS\d{4}\b|could not be parsed|TSError").unwrap();
+    /// a relative module specifier in an import, export or require
+    static ref RELATIVE_IMPORT: Regex =
+        Regex::new(r#"\b((?:from|import|require)\s*\(?\s*)(['"])(\.{1,2}/[^'"]+)['"]"#).unwrap();
+}
+
+impl TS_original {
+    /// runner asked by the user, or the first one installed of deno, ts-node and tsc
+    fn select_runner(data: &DataHolder) -> String {
+        if let Some(runner) = data
+            .interpreter_option(&Self::get_name(), "runner")
+            .and_then(|r| r.as_str())
+        {
+            return runner.to_string();
+        }
+        ["deno", "ts-node"]
+            .iter()
+            .find(|runner| check_toolchain(&[runner]).is_ok())
+            .unwrap_or(&"tsc")
+            .to_string()
+    }
+
+    /// the permission flags the user gives to deno (interpreter option `permissions`)
+    fn permissions(data: &DataHolder) -> Vec<String> {
+        data.interpreter_option(&Self::get_name(), "permissions")
+            .and_then(|p| p.as_array())
+            .map(|p| {
+                p.iter()
+                    .filter_map(|flag| flag.as_str().map(String::from))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn is_import(line: &str) -> bool {
+        let line = line.trim_start();
+        line.starts_with("import ") && !line.starts_with("import(")
+    }
+
+    /// the code runs from the work directory: its relative imports are made absolute, from the
+    /// directory of the edited file
+    fn resolve_imports(&self, code: &str) -> String {
+        let dir = match Path::new(&self.data.filepath).parent() {
+            Some(dir) if dir.is_absolute() => dir,
+            _ => return code.to_string(),
+        };
+        RELATIVE_IMPORT
+            .replace_all(code, |captures: &regex::Captures| {
+                format!(
+                    "{}{quote}{}{quote}",
+                    &captures[1],
+                    dir.join(captures[3].trim_start_matches("./"))
+                        .to_string_lossy(),
+                    quote = &captures[2]
+                )
+            })
+            .to_string()
+    }
+
+    /// the error of a failed run (or type check), with the lines of the code rather than the
+    /// ones of the generated file, located in the buffer where the first error is
+    fn error(&self, report: &str) -> SniprunError {
+        let mut first_line = None;
+        let message = TS_LOCATION.replace_all(report, |captures: &regex::Captures| {
+            // deno's format, or tsc's
+            let (line, column, tsc) = match captures.get(1) {
+                Some(line) => (line.as_str(), &captures[2], false),
+                None => (&captures[3], &captures[4], true),
+            };
+            let code_line = line
+                .parse::<i64>()
+                .ok()
+                .and_then(|line| self.line_offsets.code_line(line));
+            if first_line.is_none() {
+                first_line = code_line.and_then(|line| self.data.buffer_line(line));
+            }
+            match (code_line, tsc) {
+                (Some(line), false) => format!("main.ts:{}:{}", line, column),
+                (Some(line), true) => format!("main.ts({},{})", line, column),
+                (None, _) => captures[0].to_string(),
+            }
+        });
+        let error = if TS_COMPILATION_ERROR.is_match(&message) {
+            SniprunError::CompilationError(message.to_string())
+        } else {
+            SniprunError::RuntimeError(message.to_string())
+        };
+        match first_line {
+            Some(line) => error.at_line(line),
+            None => error,
+        }
+    }
+}
+
+impl Interpreter for TS_original {
+    declare_interpreter!(TS_original);
+
+    fn new_with_level(data: DataHolder, support_level: SupportLevel) -> Box<TS_original> {
+        let twd = work_dir_path(&data, &Self::get_work_dir_name());
+        let mfp = twd.clone() + "/main.ts";
+        let runner = Self::select_runner(&data);
+        let permissions = Self::permissions(&data);
+        Box::new(TS_original {
+            data,
+            support_level,
+            code: String::from(""),
+            ts_work_dir: twd,
+            main_file_path: mfp,
+            runner,
+            permissions,
+            line_offsets: LineOffsets::default(),
+        })
+    }
+
+    fn get_supported_languages() -> Vec<String> {
+        vec![String::from("typescript"), String::from("ts")]
+    }
+
+    fn get_generated_file(&self) -> Option<String> {
+        Some(self.main_file_path.clone())
+    }
+
+    fn get_max_support_level() -> SupportLevel {
+        SupportLevel::Import
+    }
+
+    fn get_capabilities() -> Capabilities {
+        Capabilities::STREAMING | Capabilities::PROJECT_FILES
+    }
+
+    fn get_line_offsets(&self) -> LineOffsets {
+        self.line_offsets.clone()
+    }
+
+    fn fetch_code(&mut self) -> Result<(), SniprunError> {
+        self.code = selected_code(&self.data, self.get_current_level());
+        Ok(())
+    }
+
+    fn add_boilerplate(&mut self) -> Result<(), SniprunError> {
+        self.line_offsets = LineOffsets::default();
+        if self.support_level >= SupportLevel::Import {
+            // the imports of the file the code doesn't have already
+            let imports: Vec<String> = std::fs::read_to_string(&self.data.filepath)
+                .unwrap_or_default()
+                .lines()
+                .filter(|l| Self::is_import(l))
+                .filter(|l| !self.code.lines().any(|c| c.trim() == l.trim()))
+                .map(|l| l.trim().to_string())
+                .collect();
+            if !imports.is_empty() {
+                self.line_offsets.prepend(imports.len() as i64);
+                self.code = imports.join("\n") + "\n" + &self.code;
+            }
+        }
+        self.code = self.resolve_imports(&self.code);
+        Ok(())
+    }
+
+    fn build(&mut self) -> Result<(), SniprunError> {
+        write_code(&self.main_file_path, &self.code)?;
+        if self.runner != "tsc" {
+            return Ok(());
+        }
+
+        check_toolchain(&["tsc"])?;
+        let output = Command::new("tsc")
+            .arg("--target")
+            .arg("es2018")
+            .arg("--outDir")
+            .arg(&self.ts_work_dir)
+            .arg(&self.main_file_path)
+            .output()?;
+        if output.status.success() {
+            return Ok(());
+        }
+        // tsc reports type errors on stdout
+        let report = String::from_utf8_lossy(&output.stdout).to_string()
+            + &String::from_utf8_lossy(&output.stderr);
+        Err(self.error(&report))
+    }
+
+    fn check(&mut self) -> Result<ExecutionResult, SniprunError> {
+        self.build()?;
+        match self.runner.as_str() {
+            "tsc" => Ok(ExecutionResult::new(String::from("compiled OK\n"))),
+            "deno" => {
+                check_toolchain(&["deno"])?;
+                syntax_check(
+                    Command::new("deno")
+                        .arg("check")
+                        .arg(&self.main_file_path)
+                        .env("NO_COLOR", "1"),
+                    |report| self.error(report),
+                )
+            }
+            _ => Ok(ExecutionResult::new(format!(
+                "no type check with {}, nothing was checked\n",
+                self.runner
+            ))),
+        }
+    }
+
+    fn execute(&mut self) -> Result<ExecutionResult, SniprunError> {
+        info!("[TS] running with {}", self.runner);
+        let mut command = match self.runner.as_str() {
+            "tsc" => {
+                check_toolchain(&["node"])?;
+                let js_file = self.ts_work_dir.clone() + "/main.js";
+                let mut command = Command::new("node");
+                command.arg(js_file);
+                command
+            }
+            "deno" => {
+                check_toolchain(&["deno"])?;
+                // deno doesn't type check what it runs by default
+                let mut command = Command::new("deno");
+                command
+                    .arg("run")
+                    .arg("--check")
+                    .args(&self.permissions)
+                    .arg(&self.main_file_path)
+                    .env("NO_COLOR", "1");
+                command
+            }
+            runner => {
+                check_toolchain(&[runner])?;
+                let mut command = Command::new(runner);
+                command.arg(&self.main_file_path);
+                command
+            }
+        };
+        let output = run_command(command.current_dir(&self.ts_work_dir), &self.data)?;
+        if output.status.success() {
+            Ok(ExecutionResult::from_output(&output))
+        } else {
+            Err(self.error(&String::from_utf8_lossy(&output.stderr)))
+        }
+    }
+}
@@ -1,12 +1,25 @@
 use crate::error::SniprunError;
-use crate::interpreter::{Interpreter, SupportLevel};
+use crate::interpreter::{
+    check_toolchain, create_work_dir, find_ancestor, kill_process_group, python_environment,
+    remove_oldest_files, run_build_command, run_command, sandboxed_command, selected_code,
+    syntax_check, work_dir_path, write_code, Capabilities, CommentSyntax, ExecutionResult,
+    Interpreter, LineOffsets, SupportLevel,
+};
+use crate::{config_bool, lock};
 use crate::DataHolder;
+use lazy_static::lazy_static;
 use log::info;
+use neovim_lib::NeovimApi;
+use regex::Regex;
 use serde_json::Value;
 
-use std::fs::{write, DirBuilder, File};
+use std::fs::File;
 use std::io::prelude::*;
-use std::process::Command;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
 //python-specific
 use pyo3::types::PyDict;
@@ -0,0 +1,188 @@
+use crate::interpreter::CancelToken;
+use log::info;
+use std::sync::{Condvar, Mutex};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// a run in progress, in its own thread
+struct Job {
+    id: u64,
+    handle: JoinHandle<()>,
+    cancel: CancelToken,
+    /// the number of the buffer the code comes from, if known
+    buffer: Option<i64>,
+}
+
+/// The runs in progress. The threads of finished runs are joined (reaped), so that a panic
+/// is logged instead of silently lost
+#[derive(Default)]
+pub struct Jobs {
+    /// the id of the last run started
+    last_id: u64,
+    running: Vec<Job>,
+}
+
+impl Jobs {
+    /// track the thread of a new run of the code of a buffer, returns its id
+    pub fn add(&mut self, handle: JoinHandle<()>, cancel: CancelToken, buffer: Option<i64>) -> u64 {
+        self.reap();
+        self.last_id += 1;
+        self.running.push(Job {
+            id: self.last_id,
+            handle,
+            cancel,
+            buffer,
+        });
+        self.last_id
+    }
+
+    /// join the threads of the runs that finished
+    pub fn reap(&mut self) {
+        let (finished, running): (Vec<Job>, Vec<Job>) = self
+            .running
+            .drain(..)
+            .partition(|job| job.handle.is_finished());
+        self.running = running;
+        for job in finished {
+            match job.handle.join() {
+                Ok(()) => info!("[JOBS] Run {} finished", job.id),
+                Err(panic) => info!(
+                    "[JOBS] Run {} panicked: {}",
+                    job.id,
+                    crate::panic_message(&*panic)
+                ),
+            }
+        }
+    }
+
+    /// the ids of the runs in progress
+    pub fn ids(&mut self) -> Vec<u64> {
+        self.reap();
+        self.running.iter().map(|job| job.id).collect()
+    }
+
+    /// number of runs in progress
+    pub fn count(&mut self) -> usize {
+        self.reap();
+        self.running.len()
+    }
+
+    /// number of runs in progress of the code of that buffer
+    pub fn count_in(&mut self, buffer: Option<i64>) -> usize {
+        self.reap();
+        self.running
+            .iter()
+            .filter(|job| job.buffer == buffer && !job.cancel.is_cancelled())
+            .count()
+    }
+
+    /// cancel the runs of the code of that buffer, the others go on; returns the number of runs
+    /// cancelled
+    pub fn cancel_in(&mut self, buffer: Option<i64>) -> usize {
+        self.reap();
+        let mut cancelled = 0;
+        for job in &self.running {
+            if job.buffer == buffer && !job.cancel.is_cancelled() {
+                job.cancel.cancel();
+                cancelled += 1;
+            }
+        }
+        cancelled
+    }
+
+    /// cancel the run with that id, or every run if None; returns the number of runs cancelled.
+    /// Their threads are reaped once they have stopped
+    pub fn cancel(&mut self, id: Option<u64>) -> usize {
+        self.reap();
+        let mut cancelled = 0;
+        for job in &self.running {
+            if id.is_none_or(|id| id == job.id) && !job.cancel.is_cancelled() {
+                job.cancel.cancel();
+                cancelled += 1;
+            }
+        }
+        cancelled
+    }
+}
+
+/// A limit on the number of runs executing at once (a counting semaphore): the runs over the
+/// limit wait for one to finish
+#[derive(Default)]
+pub struct RunSlots {
+    used: Mutex<usize>,
+    freed: Condvar,
+}
+
+/// a slot taken by a run, given back when dropped
+pub struct RunSlot<'a> {
+    slots: &'a RunSlots,
+}
+
+impl RunSlots {
+    /// wait for one of the `max` slots to be free, and take it. None if the run was cancelled
+    /// while it waited
+    pub fn acquire(&self, max: usize, cancel: &CancelToken) -> Option<RunSlot<'_>> {
+        let mut used = crate::lock(&self.used);
+        if *used >= max {
+            info!(
+                "[JOBS] {} runs in progress already, waiting for one to finish",
+                used
+            );
+        }
+        while *used >= max {
+            if cancel.is_cancelled() {
+                return None;
+            }
+            used = self
+                .freed
+                .wait_timeout(used, Duration::from_millis(100))
+                .map(|(used, _)| used)
+                .unwrap_or_else(|e| e.into_inner().0);
+        }
+        *used += 1;
+        Some(RunSlot { slots: self })
+    }
+}
+
+impl Drop for RunSlot<'_> {
+    fn drop(&mut self) {
+        *crate::lock(&self.slots.used) -= 1;
+        self.slots.freed.notify_one();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    /// a run that lasts until it is cancelled
+    fn add_run(jobs: &mut Jobs, buffer: Option<i64>) -> CancelToken {
+        let cancel = CancelToken::default();
+        let token = cancel.clone();
+        let handle = thread::spawn(move || {
+            while !token.is_cancelled() {
+                thread::sleep(Duration::from_millis(5));
+            }
+        });
+        jobs.add(handle, cancel.clone(), buffer);
+        cancel
+    }
+
+    #[test]
+    fn cancelling_a_buffer_leaves_the_others_running() {
+        let mut jobs = Jobs::default();
+        let first = add_run(&mut jobs, Some(1));
+        let second = add_run(&mut jobs, Some(2));
+        assert_eq!(jobs.count_in(Some(1)), 1);
+
+        assert_eq!(jobs.cancel_in(Some(1)), 1);
+        assert!(first.is_cancelled());
+        assert!(!second.is_cancelled());
+        assert_eq!(jobs.count_in(Some(1)), 0);
+        assert_eq!(jobs.count_in(Some(2)), 1);
+
+        assert_eq!(jobs.cancel(None), 1);
+        assert!(second.is_cancelled());
+    }
+}
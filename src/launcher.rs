@@ -1,40 +1,1323 @@
 use crate::*;
 use error::SniprunError;
-use interpreter::{Interpreter, SupportLevel};
+use interpreter::{
+    create_work_dir, selected_code, take_step_durations, Capabilities, ExecutionResult,
+    Interpreter, SupportLevel,
+};
+use lazy_static::lazy_static;
+use neovim_lib::Value;
+use serde_json::json;
+use std::any::Any;
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// releases the resources of an interpreter instance, see Interpreter::cleanup()
+type Cleanup = Box<dyn Fn() + Send>;
+
+lazy_static! {
+    /// the last instance of each interpreter with the CLEANUP capability that ran, by name
+    static ref CLEANUPS: Mutex<Vec<(String, Cleanup)>> = Mutex::new(vec![]);
+}
+
+/// how many candidates are prepared at once, see Launcher::prepare_all()
+const PREPARING_THREADS: usize = 4;
+
+/// a candidate made ready to build (see Launcher::prepare())
+struct Preparation {
+    /// the interpreter, as its concrete type, or why it couldn't be created
+    interpreter: Result<Box<dyn Any + Send>, SniprunError>,
+    /// whether it fetched the code and added its boilerplate
+    prepared: Result<(), SniprunError>,
+    /// the steps it took, see interpreter::timed()
+    steps: Vec<(String, Duration)>,
+    duration: Duration,
+}
+
+/// the interpreters that run the code in a process the user started themselves (eg: a jupyter
+/// kernel): most of the time there is none, they are only tried first when the user chose them
+const ATTACHED_INTERPRETERS: &[&str] = &["Jupyter_original"];
+
+/// sort the interpreters, best one first: the ones that run the code themselves before the
+/// attached ones, then highest support level first, then the one that has the most of the wanted
+/// capabilities, then one with a REPL. The sort is stable: on a tie, the first interpreter found
+/// stays first
+fn rank(candidates: &mut [(String, SupportLevel, Capabilities)], wanted: Capabilities) {
+    candidates.sort_by_key(|(name, level, capabilities)| {
+        std::cmp::Reverse((
+            !ATTACHED_INTERPRETERS.contains(&name.as_str()),
+            *level as u8,
+            capabilities.matching(wanted),
+            capabilities.contains(Capabilities::REPL),
+        ))
+    });
+}
+
+/// the usual other names of the filetypes the interpreters support, eg: file extensions
+const FILETYPE_ALIASES: &[(&str, &str)] = &[
+    ("py", "python"),
+    ("python3", "python"),
+    ("js", "javascript"),
+    ("node", "javascript"),
+    ("ts", "typescript"),
+    ("rb", "ruby"),
+    ("hs", "haskell"),
+    ("jl", "julia"),
+    ("ex", "elixir"),
+    ("exs", "elixir"),
+    ("pl", "perl"),
+    ("shell", "sh"),
+    ("golang", "go"),
+    ("c++", "cpp"),
+    ("csharp", "cs"),
+    ("f#", "fsharp"),
+    ("ml", "ocaml"),
+    ("kts", "kotlin"),
+];
+
+/// the files of a directory, with their last modification time: the artifacts of a run are
+/// the files that differ from the ones before it
+type Snapshot = HashMap<PathBuf, SystemTime>;
+
+/// how deep in the subdirectories, and how many files, a snapshot looks at: the code may run
+/// in a big project
+const SNAPSHOT_MAX_DEPTH: usize = 4;
+const SNAPSHOT_MAX_FILES: usize = 10_000;
+
+/// the files of the directory and of its subdirectories, but the hidden ones (eg: .git)
+fn snapshot(dir: &Path) -> Snapshot {
+    let mut files = Snapshot::new();
+    let mut dirs = vec![(dir.to_path_buf(), 0)];
+    while let Some((dir, depth)) = dirs.pop() {
+        for entry in fs::read_dir(&dir).into_iter().flatten().flatten() {
+            if files.len() >= SNAPSHOT_MAX_FILES {
+                info!("[LAUNCHER] Too many files to look for artifacts in");
+                return files;
+            }
+            if entry.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
+            match entry.metadata() {
+                Ok(metadata) if metadata.is_dir() && depth < SNAPSHOT_MAX_DEPTH => {
+                    dirs.push((entry.path(), depth + 1))
+                }
+                Ok(metadata) if metadata.is_file() => {
+                    files.insert(entry.path(), metadata.modified().unwrap_or(UNIX_EPOCH));
+                }
+                _ => {}
+            }
+        }
+    }
+    files
+}
+
+/// the files of the directory created or modified since the snapshot, sorted
+fn artifacts(dir: &Path, before: &Snapshot) -> Vec<String> {
+    let mut artifacts: Vec<String> = snapshot(dir)
+        .into_iter()
+        .filter(|(path, modified)| before.get(path) != Some(modified))
+        .map(|(path, _)| path.to_string_lossy().to_string())
+        .collect();
+    artifacts.sort();
+    artifacts
+}
+
+/// run a step of an interpreter: a panic in it (eg: an unwrap on something unexpected) is an
+/// internal error of the interpreter, the other interpreters and the next runs are not affected
+fn guarded<T>(step: impl FnOnce() -> Result<T, SniprunError>) -> Result<T, SniprunError> {
+    panic::catch_unwind(AssertUnwindSafe(step)).unwrap_or_else(|panic| {
+        let message = crate::panic_message(&*panic);
+        info!("[LAUNCHER] The interpreter panicked: {}", message);
+        Err(SniprunError::InternalError(message))
+    })
+}
 
 pub struct Launcher {
     pub data: DataHolder,
+    /// source file generated by the last interpreter that ran, if any
+    pub generated_file: Option<String>,
+    /// name of the last interpreter that ran, if any
+    pub interpreter: Option<String>,
+    /// support level the last interpreter that ran ended at, if any
+    pub support_level: Option<SupportLevel>,
+    /// the steps of the interpreters that ran, with their duration
+    pub steps: Vec<(String, Duration)>,
+}
+
+/// summary of a run, sent to the user's `result_function` so that other plugins can use it
+#[derive(Debug, Clone)]
+pub struct RunResult {
+    pub interpreter: String,
+    /// one of ok, compile_error, runtime_error, timeout, error
+    pub status: String,
+    pub stdout: String,
+    pub stderr: String,
+    /// exit code of the user's code, when known
+    pub exit_code: Option<i64>,
+    pub duration: Duration,
+    /// the range of lines that was run
+    pub range: [i64; 2],
+    /// the name of the error's variant, if the run failed
+    pub error_variant: Option<String>,
+    /// the support level the code ran at, if an interpreter ran
+    pub support_level: Option<String>,
+    /// a hash of the code that was run
+    pub code_hash: String,
+    /// the steps of the interpreters that ran ("Interpreter: step"), with their duration
+    pub steps: Vec<(String, Duration)>,
+    /// the files the run created or modified (config: `capture_artifacts`)
+    pub artifacts: Vec<String>,
+}
+
+/// what an interpreter can do, known without creating it
+#[derive(Debug, Clone)]
+pub struct InterpreterInfo {
+    pub name: String,
+    pub languages: Vec<String>,
+    pub max_support_level: SupportLevel,
+    pub capabilities: Capabilities,
+}
+
+impl InterpreterInfo {
+    /// a vim dictionary with the same fields
+    pub fn to_value(&self) -> Value {
+        Value::Map(vec![
+            (Value::from("name"), Value::from(self.name.as_str())),
+            (
+                Value::from("languages"),
+                Value::Array(
+                    self.languages
+                        .iter()
+                        .map(|language| Value::from(language.as_str()))
+                        .collect(),
+                ),
+            ),
+            (
+                Value::from("max_support_level"),
+                Value::from(self.max_support_level.to_string()),
+            ),
+            (
+                Value::from("capabilities"),
+                Value::Array(
+                    self.capabilities
+                        .names()
+                        .into_iter()
+                        .map(Value::from)
+                        .collect(),
+                ),
+            ),
+        ])
+    }
+}
+
+impl RunResult {
+    /// a vim dictionary with the same fields (the duration in milliseconds)
+    pub fn to_value(&self) -> Value {
+        let exit_code = match self.exit_code {
+            Some(code) => Value::from(code),
+            None => Value::Nil,
+        };
+        Value::Map(vec![
+            (
+                Value::from("interpreter"),
+                Value::from(self.interpreter.as_str()),
+            ),
+            (Value::from("status"), Value::from(self.status.as_str())),
+            (Value::from("stdout"), Value::from(self.stdout.as_str())),
+            (Value::from("stderr"), Value::from(self.stderr.as_str())),
+            (Value::from("exit_code"), exit_code),
+            (
+                Value::from("duration"),
+                Value::from(self.duration.as_millis() as u64),
+            ),
+            (
+                Value::from("range"),
+                Value::Array(vec![Value::from(self.range[0]), Value::from(self.range[1])]),
+            ),
+            (
+                Value::from("artifacts"),
+                Value::Array(
+                    self.artifacts
+                        .iter()
+                        .map(|artifact| Value::from(artifact.as_str()))
+                        .collect(),
+                ),
+            ),
+        ])
+    }
+
+    /// a JSON line with everything known about the run, for debugging: stdout and stderr are
+    /// cut after `max_output` characters
+    pub fn to_record(&self, max_output: usize) -> String {
+        let truncated = |text: &str| text.chars().take(max_output).collect::<String>();
+        let steps: Vec<serde_json::Value> = self
+            .steps
+            .iter()
+            .map(
+                |(step, duration)| json!({ "step": step, "duration": duration.as_millis() as u64 }),
+            )
+            .collect();
+        json!({
+            "timestamp": std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+            "interpreter": self.interpreter,
+            "support_level": self.support_level,
+            "code_hash": self.code_hash,
+            "status": self.status,
+            "exit_code": self.exit_code,
+            "duration": self.duration.as_millis() as u64,
+            "steps": steps,
+            "stdout": truncated(&self.stdout),
+            "stderr": truncated(&self.stderr),
+            "error": self.error_variant,
+        })
+        .to_string()
+    }
+
+    /// the same fields as a JSON object, plus `success` and the error (variant name and
+    /// message) if the run failed
+    pub fn to_json(&self) -> String {
+        let error = match &self.error_variant {
+            Some(variant) => json!({ "variant": variant, "message": self.stderr }),
+            None => serde_json::Value::Null,
+        };
+        json!({
+            "interpreter": self.interpreter,
+            "success": self.status == "ok",
+            "status": self.status,
+            "stdout": self.stdout,
+            "stderr": self.stderr,
+            "exit_code": self.exit_code,
+            "duration": self.duration.as_millis() as u64,
+            "range": self.range,
+            "artifacts": self.artifacts,
+            "error": error,
+        })
+        .to_string()
+    }
 }
 
 impl Launcher {
     pub fn new(data: DataHolder) -> Self {
-        Launcher { data }
+        Launcher {
+            data,
+            generated_file: None,
+            interpreter: None,
+            support_level: None,
+            steps: vec![],
+        }
+    }
+
+    /// capabilities that would be nice to have from the interpreter, given what the user asked
+    /// for; used to break ties between interpreters with the same support level. A REPL is
+    /// always welcome: the next runs can build on the state the previous ones left
+    fn wanted_capabilities(&self) -> Capabilities {
+        if self.data.output_stream.is_enabled() {
+            Capabilities::STREAMING | Capabilities::REPL
+        } else {
+            Capabilities::REPL
+        }
+    }
+
+    /// the filetype the interpreters are looked for with: the filetype, or for a compound one
+    /// (eg: 'javascript.jsx') the first of its parts an interpreter supports; the filetypes
+    /// are lowercased, and their aliases (the user's `filetype_aliases`, then the usual ones,
+    /// eg: 'python' for 'py') tried before them, so that the filetype is the usual name (the
+    /// comments are guessed from it)
+    fn lookup_filetype(&self) -> String {
+        let filetype = self.data.filetype.to_lowercase();
+        let supported = |filetype: &str| {
+            let mut supported = false;
+            iter_types! {
+                supported |= Current::get_supported_languages().iter().any(|l| l == filetype);
+            }
+            supported
+        };
+        let alias = |filetype: &str| {
+            self.data
+                .filetype_aliases
+                .get(filetype)
+                .map(String::as_str)
+                .or_else(|| {
+                    FILETYPE_ALIASES
+                        .iter()
+                        .find(|(alias, _)| *alias == filetype)
+                        .map(|(_, target)| *target)
+                })
+                .map(str::to_lowercase)
+        };
+        std::iter::once(filetype.as_str())
+            .chain(filetype.split('.').filter(|part| !part.is_empty()))
+            .flat_map(|name| {
+                alias(name)
+                    .into_iter()
+                    .chain(std::iter::once(name.to_string()))
+            })
+            .find(|name| supported(name))
+            .unwrap_or(filetype)
+    }
+
+    /// the interpreter the user chose: for this run (`b:sniprun_interpreter`), else for the
+    /// filetype (config: `interpreters`). A choice that names no interpreter is ignored
+    fn chosen_interpreter(&self, filetype: &str) -> Option<(String, SupportLevel)> {
+        let choices = self
+            .data
+            .selected_interpreter
+            .iter()
+            .chain(self.data.preferred_interpreters.get(filetype));
+        for chosen in choices {
+            iter_types! {
+                if Current::get_name().eq_ignore_ascii_case(chosen) {
+                    return Some((Current::get_name(), Current::get_max_support_level()));
+                }
+            }
+            info!("[LAUNCHER] No interpreter named {}, ignored", chosen);
+        }
+        None
     }
 
-    pub fn select_and_run<'a>(&self) -> Result<String, SniprunError> {
-        let mut max_level_support = SupportLevel::Unsupported;
-        let mut name_best_interpreter = String::from("Generic");
-        //select the best interpreter for the language
+    /// all the interpreters that support the filetype, best one first: the one the user chose,
+    /// then the others in the order of rank()
+    fn candidates(&self) -> Vec<(String, SupportLevel)> {
+        let wanted = self.wanted_capabilities();
+        let filetype = self.lookup_filetype();
+        let mut candidates: Vec<(String, SupportLevel, Capabilities)> = vec![];
         iter_types! {
-            if Current::get_supported_languages().contains(&self.data.filetype){
-                if Current::get_max_support_level() > max_level_support {
-                    max_level_support = Current::get_max_support_level();
-                    name_best_interpreter = Current::get_name();
+            if Current::get_supported_languages().contains(&filetype)
+                && Current::get_max_support_level() > SupportLevel::Unsupported {
+                candidates.push((
+                    Current::get_name(),
+                    Current::get_max_support_level(),
+                    Current::negotiate_capabilities(&self.data),
+                ));
+            }
+        }
+        rank(&mut candidates, wanted);
+        let mut candidates: Vec<(String, SupportLevel)> = candidates
+            .into_iter()
+            .map(|(name, level, _)| (name, level))
+            .collect();
+
+        if let Some((name, level)) = self.chosen_interpreter(&filetype) {
+            candidates.retain(|(candidate, _)| *candidate != name);
+            candidates.insert(0, (name, level));
+        }
+        // even the one the user chose for the filetype, if they also disabled it
+        candidates.retain(|(name, _)| self.data.is_enabled(name));
+        candidates
+    }
+
+    /// every interpreter sniprun has, by name
+    pub fn interpreters() -> Vec<InterpreterInfo> {
+        let mut interpreters = vec![];
+        iter_types! {
+            interpreters.push(InterpreterInfo {
+                name: Current::get_name(),
+                languages: Current::get_supported_languages(),
+                max_support_level: Current::get_max_support_level(),
+                capabilities: Current::get_capabilities(),
+            });
+        }
+        interpreters.sort_by(|a, b| a.name.cmp(&b.name));
+        interpreters
+    }
+
+    /// work directory names of the interpreters that either have the given name or support the
+    /// given filetype
+    pub fn work_dir_names_of(target: &str) -> Vec<String> {
+        let mut names = vec![];
+        iter_types! {
+            if Current::get_name().eq_ignore_ascii_case(target)
+                || Current::get_supported_languages().iter().any(|l| l == target) {
+                names.push(Current::get_work_dir_name());
+            }
+        }
+        names
+    }
+
+    /// release the resources of the interpreters that ran and have the given name or support
+    /// the given filetype (all of them if the target is empty), see Interpreter::cleanup()
+    pub fn cleanup(target: &str) {
+        let mut names = vec![];
+        iter_types! {
+            if target.is_empty()
+                || Current::get_name().eq_ignore_ascii_case(target)
+                || Current::get_supported_languages().iter().any(|l| l == target) {
+                names.push(Current::get_name());
+            }
+        }
+        // the registry isn't locked while the interpreters clean up, a run can register again
+        let cleanups: Vec<(String, Cleanup)> = {
+            let mut registered = crate::lock(&CLEANUPS);
+            let (cleanups, kept) = registered
+                .drain(..)
+                .partition(|(name, _)| names.contains(name));
+            *registered = kept;
+            cleanups
+        };
+        for (name, cleanup) in cleanups {
+            info!("[LAUNCHER] Cleaning up {}", name);
+            let _ = guarded(|| {
+                cleanup();
+                Ok(())
+            });
+        }
+    }
+
+    /// keep the instance of an interpreter that has resources to release, replacing the previous
+    /// one: its cleanup() is called by Launcher::cleanup()
+    fn register_cleanup(name: &str, cleanup: Cleanup) {
+        let mut registered = crate::lock(&CLEANUPS);
+        registered.retain(|(registered_name, _)| registered_name != name);
+        registered.push((name.to_string(), cleanup));
+    }
+
+    /// forget the state kept between runs by the interpreters that have the given name or
+    /// support the given filetype (all of them if the target is empty)
+    pub fn clean_repl_memory(&self, target: &str) -> Result<(), SniprunError> {
+        iter_types! {
+            if target.is_empty()
+                || Current::get_name().eq_ignore_ascii_case(target)
+                || Current::get_supported_languages().iter().any(|l| l == target) {
+                Current::clean_repl_memory(&self.data)
+                    .map_err(|e| e.in_interpreter(&Current::get_name()))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// if the user asked for it, keep a copy of the generated source file of a failed run
+    /// (the next run would overwrite it) and point to it in the error
+    fn keep_failed_file(&self, error: SniprunError) -> SniprunError {
+        let generated_file = match &self.generated_file {
+            Some(generated_file) if self.data.keep_temp => generated_file,
+            _ => return error,
+        };
+        match self.data.keep_copy(generated_file, "failed") {
+            Ok(kept_file) => error.with_kept_file(kept_file),
+            Err(e) => {
+                info!(
+                    "[LAUNCHER] Could not keep failed file {}: {}",
+                    generated_file, e
+                );
+                error
+            }
+        }
+    }
+
+    /// key of the result of the code with the given interpreter, in the result cache
+    fn cache_key(&self, name: &str) -> String {
+        let mut options: Vec<String> = self
+            .data
+            .interpreter_options
+            .get(name)
+            .into_iter()
+            .flatten()
+            .map(|(key, value)| format!("{}={:?}", key, value))
+            .collect();
+        options.sort();
+        // the code may use what the rest of the file declares
+        let file = std::fs::read(&self.data.filepath).ok();
+        cache::key(&(
+            name,
+            &self.data.filetype,
+            (&self.data.filepath, file),
+            &self.data.current_bloc,
+            &self.data.current_line,
+            options,
+            self.data.print_last_expression,
+            self.data.prelude(),
+            self.data.execution_dir(),
+            &self.data.sandbox,
+            &self.data.sandbox_image,
+            self.data.support_level.map(|level| level.to_string()),
+        ))
+    }
+
+    /// create the work directory and an instance of the interpreter that has the given name,
+    /// then fetch the code and add the boilerplate: what doesn't depend on the other candidates
+    fn prepare(data: &DataHolder, name: &str) -> Preparation {
+        let start = Instant::now();
+        iter_types! {
+            if Current::get_name() == name {
+                // the limits of this interpreter (or of the filetype) override the global ones
+                let data = data.with_limits(name);
+                let interpreter = create_work_dir(&data, &Current::get_work_dir_name())
+                    .and_then(|_| guarded(|| Ok(Current::new(data.clone()))));
+                let (interpreter, prepared) = match interpreter {
+                    Ok(mut inter) => {
+                        let level = inter.get_current_level();
+                        let prepared = guarded(|| inter.prepare_at_level(level));
+                        (Ok(inter as Box<dyn Any + Send>), prepared)
+                    }
+                    Err(e) => (Err(e), Ok(())),
+                };
+                return Preparation {
+                    interpreter,
+                    prepared,
+                    steps: take_step_durations(),
+                    duration: start.elapsed(),
+                };
+            }
+        }
+        Preparation {
+            interpreter: Err(SniprunError::InternalError(format!(
+                "no interpreter named {}",
+                name
+            ))),
+            prepared: Ok(()),
+            steps: vec![],
+            duration: start.elapsed(),
+        }
+    }
+
+    /// prepare the candidates in the background, at most PREPARING_THREADS at once, first
+    /// candidate first: the next ones are ready by the time the first one built and ran, if it
+    /// failed. Each candidate gets its preparation from its receiver
+    fn prepare_all(
+        &self,
+        candidates: &[(String, SupportLevel)],
+    ) -> Vec<mpsc::Receiver<Preparation>> {
+        let (jobs, receivers): (VecDeque<_>, Vec<_>) = candidates
+            .iter()
+            .map(|(name, _)| {
+                let (sender, receiver) = mpsc::channel();
+                ((name.clone(), sender), receiver)
+            })
+            .unzip();
+        let jobs = Arc::new(Mutex::new(jobs));
+        for _ in 0..PREPARING_THREADS.min(candidates.len()) {
+            let jobs = jobs.clone();
+            let data = self.data.clone();
+            thread::spawn(move || loop {
+                let job = jobs.lock().unwrap().pop_front();
+                let Some((name, sender)) = job else { break };
+                // the run may not need it anymore
+                let _ = sender.send(Self::prepare(&data, &name));
+            });
+        }
+        receivers
+    }
+
+    /// run the interpreter that has the given name, the one `preparation` receives if any (see
+    /// prepare_all()), else one prepared here. If the code does not compile and the user asked
+    /// for it, retry at the lower support levels: the errors of the attempts that were retried
+    /// are added to `retried`
+    fn run_interpreter(
+        &mut self,
+        name: &str,
+        preparation: Option<mpsc::Receiver<Preparation>>,
+        retried: &mut Vec<SniprunError>,
+    ) -> Result<ExecutionResult, SniprunError> {
+        iter_types! {
+            if Current::get_name() == name {
+                self.interpreter = Some(Current::get_name());
+                // a selection of comments only would compile an empty program
+                let code = selected_code(&self.data, SupportLevel::Bloc);
+                if Current::get_comment_syntax(&self.lookup_filetype()).only_comments(&code) {
+                    return Err(SniprunError::NoCodeFound);
+                }
+                // a check isn't worth caching, nor should it return the output of a run; the
+                // result of a REPL depends on the previous runs, the one of code that imports the
+                // file or reads the project around it on files that aren't in the key
+                let capabilities = Current::negotiate_capabilities(&self.data);
+                let level = self
+                    .data
+                    .support_level_for(Current::get_max_support_level());
+                let cache_key = if self.data.cache_results
+                    && !self.data.compile_only
+                    && !capabilities.contains(Capabilities::REPL)
+                    && !capabilities.contains(Capabilities::PROJECT_FILES)
+                    && Current::negotiate_level(&self.data, level) < SupportLevel::Import
+                {
+                    Some(self.cache_key(name))
+                } else {
+                    None
+                };
+                if let Some((mut cached, level)) = cache_key
+                    .as_ref()
+                    .and_then(|key| cache::get(&self.data.work_dir, key))
+                {
+                    info!("[LAUNCHER] Returning the cached result of {}", name);
+                    self.support_level = level;
+                    if !cached.stdout.is_empty() && !cached.stdout.ends_with('\n') {
+                        cached.stdout.push('\n');
+                    }
+                    cached.stdout.push_str("(cached)");
+                    return Ok(cached);
                 }
+                let start = Instant::now();
+                let preparation = preparation
+                    .and_then(|receiver| receiver.recv().ok())
+                    .unwrap_or_else(|| Self::prepare(&self.data, name));
+                let waited = start.elapsed();
+                info!(
+                    "[LAUNCHER] {} prepared in {:?}, waited {:?} for it",
+                    name, preparation.duration, waited
+                );
+                self.steps.extend(
+                    preparation
+                        .steps
+                        .into_iter()
+                        .map(|(step, duration)| (format!("{}: {}", name, step), duration)),
+                );
+                self.steps.push((format!("{}: prepare", name), preparation.duration));
+                self.steps.push((format!("{}: wait for the preparation", name), waited));
+                let mut inter = preparation
+                    .interpreter
+                    .map_err(|e| e.in_interpreter(name))?
+                    .downcast::<Current>()
+                    .map_err(|_| {
+                        SniprunError::InternalError(format!("{} prepared something else", name))
+                    })?;
+                if Current::get_capabilities().contains(Capabilities::CLEANUP) {
+                    let instance = inter.clone();
+                    Self::register_cleanup(name, Box::new(move || instance.cleanup()));
+                }
+                // the files the code creates can only be told apart in the directory it runs in
+                let before = match self.data.execution_dir() {
+                    Some(dir) if self.data.capture_artifacts && !self.data.compile_only => {
+                        let files = snapshot(&dir);
+                        Some((dir, files))
+                    }
+                    _ => None,
+                };
+                let mut result = match preparation.prepared {
+                    Err(e) => Err(e),
+                    Ok(()) if self.data.compile_only => guarded(|| inter.build_and_check()),
+                    Ok(()) => guarded(|| inter.build_and_execute()),
+                };
+                let mut retries = 0;
+                let result = loop {
+                    let level = inter.get_current_level();
+                    match (result, level.lower()) {
+                        (Err(e), Some(lower))
+                            if self.data.retry_lower_level
+                                && matches!(e.inner(), SniprunError::CompilationError(_)) =>
+                        {
+                            info!(
+                                "[LAUNCHER] {} failed at level {}, retrying at level {}",
+                                name, level, lower
+                            );
+                            retried.push(e.in_interpreter(&format!("{}, {} level", name, level)));
+                            retries += 1;
+                            result = if self.data.compile_only {
+                                guarded(|| inter.check_at_level(lower))
+                            } else {
+                                guarded(|| inter.run_at_level(lower))
+                            };
+                        }
+                        (result, _) => break result,
+                    }
+                };
+                self.support_level = Some(inter.get_current_level());
+                // the user asked for more than what ran
+                let result = match result {
+                    Ok(mut result) if retries > 0 => {
+                        if !result.stdout.is_empty() && !result.stdout.ends_with('\n') {
+                            result.stdout.push('\n');
+                        }
+                        result
+                            .stdout
+                            .push_str(&format!("(ran at {} level)", inter.get_current_level()));
+                        Ok(result)
+                    }
+                    result => result,
+                };
+                let result = match (result, before) {
+                    (Ok(mut result), Some((dir, before))) => {
+                        result.artifacts = artifacts(&dir, &before);
+                        Ok(result)
+                    }
+                    (result, _) => result,
+                };
+                self.steps.extend(
+                    take_step_durations()
+                        .into_iter()
+                        .map(|(step, duration)| (format!("{}: {}", name, step), duration)),
+                );
+                self.generated_file = inter
+                    .get_generated_file()
+                    .filter(|path| std::path::Path::new(path).is_file());
+                //when several levels were tried, say which one each error comes from
+                let attempt_name = if retries > 0 {
+                    format!("{}, {} level", name, inter.get_current_level())
+                } else {
+                    Current::get_name()
+                };
+                if let (Some(key), Ok(result)) = (cache_key, &result) {
+                    cache::insert(&self.data.work_dir, key, result, self.support_level);
+                }
+                return result.map_err(|e| self.keep_failed_file(e.in_interpreter(&attempt_name)));
+            }
+        }
+        Err(SniprunError::InternalError(format!(
+            "no interpreter named {}",
+            name
+        )))
+    }
+
+    /// summary of the last run, that returned `result` and took `duration`
+    pub fn run_result(
+        &self,
+        result: &Result<ExecutionResult, SniprunError>,
+        duration: Duration,
+    ) -> RunResult {
+        let (status, stdout, stderr, exit_code) = match result {
+            Ok(execution) => (
+                "ok",
+                execution.stdout.clone(),
+                execution.stderr.clone(),
+                execution.exit_code,
+            ),
+            Err(e) => {
+                let status = match e.inner() {
+                    SniprunError::CompilationError(_) => "compile_error",
+                    SniprunError::RuntimeError(_) => "runtime_error",
+                    SniprunError::TimeoutError(_) => "timeout",
+                    _ => "error",
+                };
+                (status, String::new(), e.to_string(), None)
             }
+        };
+        RunResult {
+            interpreter: self.interpreter.clone().unwrap_or_default(),
+            status: status.to_string(),
+            stdout,
+            stderr,
+            exit_code: exit_code.map(i64::from),
+            duration,
+            range: self.data.range,
+            error_variant: result.as_ref().err().map(|e| e.variant_name().to_string()),
+            support_level: self.support_level.map(|level| level.to_string()),
+            code_hash: cache::key(&(&self.data.current_bloc, &self.data.current_line)),
+            steps: self.steps.clone(),
+            artifacts: result
+                .as_ref()
+                .map(|execution| execution.artifacts.clone())
+                .unwrap_or_default(),
         }
+    }
+
+    /// the interpreters that will be tried to run the code, in order, with their maximum
+    /// support level
+    pub fn select(&self) -> Vec<(String, SupportLevel)> {
+        let mut candidates = self.candidates();
+        if candidates.is_empty() && self.data.is_enabled("Generic") {
+            candidates.push((String::from("Generic"), SupportLevel::Unsupported));
+        }
+        if !self.data.fallback {
+            candidates.truncate(1);
+        }
+        candidates
+    }
+
+    /// the support level the interpreter that has the given name would run the code at,
+    /// without creating, building or running anything
+    pub fn negotiated_level(&self, name: &str) -> Option<SupportLevel> {
+        iter_types! {
+            if Current::get_name() == name {
+                let level = self.data.support_level_for(Current::get_max_support_level());
+                return Some(Current::negotiate_level(&self.data, level));
+            }
+        }
+        None
+    }
+
+    /// the pipeline of a run: the candidates are prepared in the background (see prepare_all()),
+    /// then each compiles and runs the code, in order, until one succeeds. The duration of each
+    /// phase is logged and kept in `steps`, with how long the run waited for the preparation
+    pub fn select_and_run(&mut self) -> Result<ExecutionResult, SniprunError> {
+        // a blank line (or selection) would otherwise compile an empty program, and show
+        // nothing at all
+        if self.data.current_bloc.trim().is_empty() && self.data.current_line.trim().is_empty() {
+            return Err(SniprunError::NoCodeFound);
+        }
+        let start = Instant::now();
+        let candidates = self.select();
         info!(
-            "[LAUNCHER] Selected interpreter : {} ; with support level {:?}",
-            name_best_interpreter, max_level_support
+            "[LAUNCHER] Selected [{}] in {:?}",
+            candidates
+                .iter()
+                .map(|(name, level)| format!("{} ({})", name, level))
+                .collect::<Vec<_>>()
+                .join(", "),
+            start.elapsed()
         );
+        self.steps.push((String::from("select"), start.elapsed()));
+        if candidates.is_empty() {
+            return Err(SniprunError::InterpreterLimitationError(format!(
+                "no enabled interpreter for the filetype {} (see enabled_interpreters and \
+                 disabled_interpreters)",
+                self.data.filetype
+            )));
+        }
+        //forget the steps of the previous runs of this thread
+        take_step_durations();
 
-        //launch !
-        iter_types! {
-            if Current::get_name() == name_best_interpreter {
-                let mut inter = Current::new(self.data.clone());
-                return inter.run();
+        //launch ! if an interpreter fails to build the code, try the next one, that was prepared
+        //meanwhile
+        let preparations = self.prepare_all(&candidates);
+        let mut errors = vec![];
+        for ((name, level), preparation) in candidates.into_iter().zip(preparations) {
+            info!(
+                "[LAUNCHER] Selected interpreter : {} ; with support level {}",
+                name, level
+            );
+            let mut retried = vec![];
+            let result = self.run_interpreter(&name, Some(preparation), &mut retried);
+            errors.append(&mut retried);
+            match result {
+                Ok(result) => return Ok(result),
+                Err(e) if e.may_fallback() => {
+                    info!("[LAUNCHER] {} failed, trying the next interpreter", name);
+                    errors.push(e);
+                }
+                Err(e) if errors.is_empty() => return Err(e),
+                Err(e) => {
+                    errors.push(e);
+                    break;
+                }
+            }
+        }
+
+        if errors.len() == 1 {
+            Err(errors.remove(0))
+        } else {
+            Err(SniprunError::AllAttemptsFailed(errors))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    fn names(candidates: &[(String, SupportLevel, Capabilities)]) -> Vec<&str> {
+        candidates
+            .iter()
+            .map(|(name, _, _)| name.as_str())
+            .collect()
+    }
+
+    fn tied() -> Vec<(String, SupportLevel, Capabilities)> {
+        vec![
+            (
+                String::from("plain"),
+                SupportLevel::Bloc,
+                Capabilities::NONE,
+            ),
+            (
+                String::from("streaming"),
+                SupportLevel::Bloc,
+                Capabilities::STREAMING,
+            ),
+            (String::from("repl"), SupportLevel::Bloc, Capabilities::REPL),
+        ]
+    }
+
+    #[test]
+    fn higher_level_wins_over_capabilities() {
+        let mut candidates = tied();
+        candidates.push((
+            String::from("import"),
+            SupportLevel::Import,
+            Capabilities::NONE,
+        ));
+        rank(
+            &mut candidates,
+            Capabilities::STREAMING | Capabilities::REPL,
+        );
+        assert_eq!(names(&candidates)[0], "import");
+    }
+
+    #[test]
+    fn streaming_wanted_breaks_the_tie() {
+        let mut candidates = tied();
+        rank(&mut candidates, Capabilities::STREAMING);
+        assert_eq!(names(&candidates), vec!["streaming", "repl", "plain"]);
+    }
+
+    #[test]
+    fn repl_breaks_the_remaining_tie() {
+        let mut candidates = tied();
+        rank(
+            &mut candidates,
+            Capabilities::STREAMING | Capabilities::REPL,
+        );
+        assert_eq!(names(&candidates), vec!["repl", "streaming", "plain"]);
+
+        let mut candidates = tied();
+        rank(&mut candidates, Capabilities::NONE);
+        assert_eq!(names(&candidates), vec!["repl", "plain", "streaming"]);
+    }
+
+    #[test]
+    fn equal_candidates_keep_their_order() {
+        let mut candidates = vec![
+            (
+                String::from("first"),
+                SupportLevel::Line,
+                Capabilities::STREAMING,
+            ),
+            (
+                String::from("second"),
+                SupportLevel::Line,
+                Capabilities::STREAMING,
+            ),
+        ];
+        rank(&mut candidates, Capabilities::REPL);
+        assert_eq!(names(&candidates), vec!["first", "second"]);
+    }
+
+    #[test]
+    fn attached_interpreters_come_last() {
+        let mut candidates = vec![
+            (
+                String::from("Jupyter_original"),
+                SupportLevel::Import,
+                Capabilities::REPL,
+            ),
+            (
+                String::from("R_original"),
+                SupportLevel::Bloc,
+                Capabilities::STREAMING,
+            ),
+        ];
+        rank(&mut candidates, Capabilities::REPL);
+        assert_eq!(names(&candidates), vec!["R_original", "Jupyter_original"]);
+    }
+
+    #[test]
+    fn jupyter_is_first_only_when_chosen() {
+        let mut data = DataHolder::with_code_in_temp_dir("r", "1 + 1");
+        let first = |data: &DataHolder| Launcher::new(data.clone()).candidates()[0].0.clone();
+        assert_eq!(first(&data), "R_original");
+
+        data.preferred_interpreters
+            .insert(String::from("r"), String::from("Jupyter_original"));
+        assert_eq!(first(&data), "Jupyter_original");
+
+        data.preferred_interpreters.clear();
+        data.selected_interpreter = Some(String::from("jupyter_original"));
+        assert_eq!(first(&data), "Jupyter_original");
+    }
+
+    #[test]
+    fn streaming_is_wanted_only_when_the_output_is_streamed() {
+        let mut data = DataHolder::with_code_in_temp_dir("python", "print(1)");
+        let launcher = Launcher::new(data.clone());
+        assert_eq!(launcher.wanted_capabilities(), Capabilities::REPL);
+
+        data.output_stream = OutputStream::new(mpsc::channel().0);
+        let launcher = Launcher::new(data);
+        assert!(launcher
+            .wanted_capabilities()
+            .contains(Capabilities::STREAMING));
+        assert!(launcher.wanted_capabilities().contains(Capabilities::REPL));
+    }
+
+    #[test]
+    fn the_level_is_negotiated_without_creating_anything() {
+        let mut data = DataHolder::with_code_in_temp_dir("fsharp", "1 + 1");
+        let launcher = Launcher::new(data.clone());
+        // no project around the file: only the selected code can run
+        assert_eq!(
+            launcher.negotiated_level("FSharp_original"),
+            Some(SupportLevel::Bloc)
+        );
+        assert_eq!(launcher.negotiated_level("Nonexistent_original"), None);
+        let fsharp_dir = Path::new(&data.work_dir)
+            .join(crate::interpreters::FSharp_original::get_work_dir_name());
+        assert!(!fsharp_dir.exists());
+
+        data.support_level = Some(SupportLevel::Line);
+        assert_eq!(
+            Launcher::new(data).negotiated_level("FSharp_original"),
+            Some(SupportLevel::Line)
+        );
+    }
+
+    #[test]
+    fn a_work_dir_that_cant_be_created_is_an_error() {
+        let data = DataHolder::with_code_in_temp_dir("python", "");
+        let file = Path::new(&data.work_dir).join("file");
+        fs::write(&file, "").unwrap();
+        let data = DataHolder {
+            work_dir: file.to_string_lossy().to_string(),
+            ..data
+        };
+        assert!(create_work_dir(&data, "sub").is_err());
+        let other = DataHolder::with_code_in_temp_dir("python", "");
+        let created = create_work_dir(&other, "sub");
+        assert!(Path::new(&created.unwrap()).is_dir());
+    }
+
+    #[test]
+    fn the_cache_key_covers_what_changes_the_result() {
+        let mut data = DataHolder::with_code_in_temp_dir("python", "print(1)");
+        data.filepath = data.work_dir.clone() + "/main.py";
+        std::fs::write(&data.filepath, "a = 1\nprint(1)").unwrap();
+        let key = |data: &DataHolder| Launcher::new(data.clone()).cache_key("Python3_original");
+        let base = key(&data);
+        assert_eq!(base, key(&data));
+
+        let changes: Vec<fn(&mut DataHolder)> = vec![
+            |data| data.run_dir = data.work_dir.clone(),
+            |data| data.sandbox = String::from("docker"),
+            |data| data.sandbox_image = String::from("python:3"),
+            |data| data.support_level = Some(SupportLevel::Line),
+            |data| {
+                data.preludes
+                    .insert(String::from("python"), String::from("import os"));
+            },
+            |data| data.current_bloc = String::from("print(2)"),
+            |data| data.filepath = data.work_dir.clone() + "/other.py",
+        ];
+        for change in changes {
+            let mut changed = data.clone();
+            change(&mut changed);
+            assert_ne!(base, key(&changed));
+        }
+
+        // the rest of the file
+        std::fs::write(&data.filepath, "a = 2\nprint(1)").unwrap();
+        assert_ne!(base, key(&data));
+    }
+
+    #[test]
+    fn a_cached_result_keeps_its_level() {
+        if interpreter::check_toolchain(&["perl"]).is_err() {
+            return;
+        }
+        let mut data = DataHolder::with_code_in_temp_dir("perl", "print 42;");
+        data.cache_results = true;
+        let mut first = Launcher::new(data.clone());
+        assert_eq!(first.select_and_run().unwrap().stdout, "42");
+        let mut second = Launcher::new(data);
+        assert_eq!(second.select_and_run().unwrap().stdout, "42\n(cached)");
+        assert_eq!(second.support_level, Some(SupportLevel::Bloc));
+        assert_eq!(second.support_level, first.support_level);
+    }
+
+    #[test]
+    fn import_level_results_are_not_cached() {
+        if interpreter::check_toolchain(&["gcc"]).is_err() {
+            return;
+        }
+        let code = "printf(\"%d\", 42);";
+        let mut data = DataHolder::with_code_in_temp_dir("c", code);
+        data.filepath = data.work_dir.clone() + "/main.c";
+        std::fs::write(&data.filepath, format!("#include <stdio.h>\n{}", code)).unwrap();
+        data.cache_results = true;
+        let run = |data: &DataHolder| Launcher::new(data.clone()).select_and_run().unwrap().stdout;
+        // at Import level, the includes of the file are used: they may have changed
+        assert_eq!(run(&data), "42");
+        assert_eq!(run(&data), "42");
+        data.support_level = Some(SupportLevel::Bloc);
+        assert_eq!(run(&data), "42");
+        assert_eq!(run(&data), "42\n(cached)");
+    }
+
+    #[test]
+    fn the_results_of_a_repl_are_not_cached() {
+        if interpreter::check_toolchain(&["sqlite3"]).is_err() {
+            return;
+        }
+        let mut data = DataHolder::with_code_in_temp_dir("sql", "select 40 + 2;");
+        data.cache_results = true;
+        for _ in 0..2 {
+            let result = Launcher::new(data.clone()).select_and_run().unwrap();
+            assert!(result.stdout.contains("42"));
+            assert!(!result.stdout.contains("(cached)"));
+        }
+    }
+
+    #[test]
+    fn odd_filetypes_are_resolved() {
+        let resolved = |filetype: &str| {
+            let data = DataHolder::with_code_in_temp_dir(filetype, "1");
+            Launcher::new(data).lookup_filetype()
+        };
+        assert_eq!(resolved("Python3"), "python");
+        assert_eq!(resolved("rust.cargo"), "rust");
+        assert_eq!(resolved("sh.bash"), "sh");
+        assert_eq!(resolved("javascript.jsx"), "javascript");
+        assert_eq!(resolved("py"), "python");
+        assert_eq!(resolved("nonexistent"), "nonexistent");
+
+        let mut data = DataHolder::with_code_in_temp_dir("mypython", "1");
+        data.filetype_aliases
+            .insert(String::from("mypython"), String::from("python"));
+        assert_eq!(Launcher::new(data.clone()).lookup_filetype(), "python");
+        assert_eq!(Launcher::new(data).select()[0].0, "Python3_original");
+    }
+
+    #[test]
+    fn an_unsupported_filetype_falls_back_to_generic() {
+        let mut data = DataHolder::with_code_in_temp_dir("nonexistent", "1");
+        assert_eq!(
+            Launcher::new(data.clone()).select(),
+            vec![(String::from("Generic"), SupportLevel::Unsupported)]
+        );
+        data.disabled_interpreters = vec![String::from("Generic")];
+        assert!(matches!(
+            Launcher::new(data).select_and_run(),
+            Err(SniprunError::InterpreterLimitationError(_))
+        ));
+    }
+
+    #[test]
+    fn comments_are_recognized_through_an_alias() {
+        let data = DataHolder::with_code_in_temp_dir("py", "# only a comment\n");
+        assert!(matches!(
+            Launcher::new(data).run_interpreter("Python3_original", None, &mut vec![]),
+            Err(SniprunError::NoCodeFound)
+        ));
+    }
+
+    #[test]
+    fn the_choice_for_the_run_wins_over_the_one_for_the_filetype() {
+        let mut data = DataHolder::with_code_in_temp_dir("python", "1");
+        let selected = |data: &DataHolder| -> Vec<String> {
+            let selected = Launcher::new(data.clone()).select();
+            selected.into_iter().map(|(name, _)| name).collect()
+        };
+        data.fallback = true;
+        // built-in order
+        assert_eq!(
+            selected(&data),
+            vec!["Python3_original", "Jupyter_original"]
+        );
+
+        // the preference for the filetype
+        data.preferred_interpreters
+            .insert(String::from("python"), String::from("jupyter_original"));
+        assert_eq!(
+            selected(&data),
+            vec!["Jupyter_original", "Python3_original"]
+        );
+
+        // the choice for this run
+        data.selected_interpreter = Some(String::from("Python3_original"));
+        assert_eq!(
+            selected(&data),
+            vec!["Python3_original", "Jupyter_original"]
+        );
+
+        // an unknown choice is ignored
+        data.selected_interpreter = Some(String::from("Nonexistent_original"));
+        assert_eq!(
+            selected(&data),
+            vec!["Jupyter_original", "Python3_original"]
+        );
+    }
+
+    #[test]
+    fn an_empty_selection_is_not_run() {
+        for (line, bloc) in [("", ""), ("   ", " \n\t\n")] {
+            let mut data = DataHolder::with_code_in_temp_dir("rust", "");
+            data.current_line = String::from(line);
+            data.current_bloc = String::from(bloc);
+            assert!(matches!(
+                Launcher::new(data.clone()).select_and_run(),
+                Err(SniprunError::NoCodeFound)
+            ));
+            // before anything was compiled
+            let rust_dir = Path::new(&data.work_dir)
+                .join(crate::interpreters::Rust_original::get_work_dir_name());
+            assert!(!rust_dir.exists());
+        }
+    }
+
+    #[test]
+    fn a_selection_of_comments_is_not_run() {
+        let mut data = DataHolder::with_code_in_temp_dir("rust", "");
+        data.current_bloc = String::from("// a\n/* b\n   c */");
+        assert!(matches!(
+            Launcher::new(data.clone()).run_interpreter("Rust_original", None, &mut vec![]),
+            Err(SniprunError::NoCodeFound)
+        ));
+        if interpreter::check_toolchain(&["rustc"]).is_err() {
+            return;
+        }
+        data.current_bloc = String::from("// a\nprint!(\"1\");");
+        let result = Launcher::new(data).run_interpreter("Rust_original", None, &mut vec![]);
+        assert_eq!(result.unwrap().stdout, "1");
+    }
+
+    #[test]
+    fn the_fallback_is_prepared_while_the_first_candidate_runs() {
+        if interpreter::check_toolchain(&["python3"]).is_err() {
+            return;
+        }
+        let mut data = DataHolder::with_code_in_temp_dir("python", "print(1 +)");
+        data.fallback = true;
+        let mut launcher = Launcher::new(data.clone());
+        assert!(launcher.select_and_run().is_err());
+        let duration = |step: &str| {
+            let found = launcher.steps.iter().find(|(name, _)| name == step);
+            found.map(|(_, duration)| *duration).unwrap()
+        };
+        let position = |step: &str| launcher.steps.iter().position(|(name, _)| name == step);
+        // each one prepared, then built, then executed
+        for name in ["Python3_original", "Jupyter_original"] {
+            let steps: Vec<_> = ["prepare", "wait for the preparation", "build", "execute"]
+                .iter()
+                .map(|step| position(&format!("{}: {}", name, step)).unwrap())
+                .collect();
+            assert!(steps.windows(2).all(|pair| pair[0] < pair[1]));
+        }
+        // the fallback was ready before python3 failed to run the code
+        assert!(
+            duration("Jupyter_original: wait for the preparation")
+                < duration("Python3_original: execute")
+        );
+        let jupyter_dir = Path::new(&data.work_dir)
+            .join(crate::interpreters::Jupyter_original::get_work_dir_name());
+        assert!(jupyter_dir.is_dir());
+    }
+
+    #[test]
+    fn disabled_interpreters_are_never_selected() {
+        let mut data = DataHolder::with_code_in_temp_dir("python", "1");
+        data.fallback = true;
+        let selected = |data: &DataHolder| -> Vec<String> {
+            let selected = Launcher::new(data.clone()).select();
+            selected.into_iter().map(|(name, _)| name).collect()
+        };
+
+        // only the allowed ones
+        data.enabled_interpreters = Some(vec![String::from("Jupyter_original")]);
+        assert_eq!(selected(&data), vec!["Jupyter_original"]);
+
+        // a disabled one, even if allowed, preferred for the filetype and chosen for the run
+        data.enabled_interpreters = None;
+        data.disabled_interpreters = vec![String::from("jupyter_original")];
+        data.preferred_interpreters
+            .insert(String::from("python"), String::from("Jupyter_original"));
+        data.selected_interpreter = Some(String::from("Jupyter_original"));
+        assert_eq!(selected(&data), vec!["Python3_original"]);
+
+        // Generic stands in when no interpreter of the filetype is enabled, unless it is not
+        // enabled either
+        data.disabled_interpreters
+            .push(String::from("Python3_original"));
+        assert_eq!(selected(&data), vec!["Generic"]);
+        data.enabled_interpreters = Some(vec![String::from("Rust_original")]);
+        assert!(selected(&data).is_empty());
+        match Launcher::new(data).select_and_run() {
+            Err(SniprunError::InterpreterLimitationError(message)) => {
+                assert!(message.contains("no enabled interpreter for the filetype python"))
             }
+            other => panic!("unexpected result {:?}", other),
         }
-        panic!()
     }
 }
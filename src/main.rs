@@ -3,15 +3,30 @@
 //! Sniprun is a neovim plugin that run parts of code.
 
 use dirs::cache_dir;
-use log::{info, LevelFilter};
-use neovim_lib::{Neovim, NeovimApi, Session, Value};
+use error::SniprunError;
+use history::{History, HistoryEntry};
+use interpreter::{
+    CancelToken, ExecutionResult, NeovimInstance, OutputStream, Progress, SupportLevel,
+};
+use jobs::{Jobs, RunSlots};
+use log::{debug, info, LevelFilter};
+use neovim_lib::{neovim_api::Buffer, CallError, Neovim, NeovimApi, Session, Value};
 use simple_logging::log_to_file;
-use std::sync::{mpsc, Arc, Mutex};
+use std::collections::HashMap;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex, MutexGuard};
 use std::thread;
+use std::time::{Duration, Instant};
 
+mod cache;
 mod error;
+mod history;
+#[macro_use]
 mod interpreter;
 mod interpreters;
+mod jobs;
 mod launcher;
 
 ///This struct holds (with ownership) the data Sniprun and neovim
@@ -27,58 +42,881 @@ pub struct DataHolder {
     ///This contains the current block of text, if the user selected a bloc of code and ran snirpun
     ///on it
     current_bloc: String,
-    ///The inclusive limits of the selected block (line numbers)
+    ///The inclusive limits of the selected block (line numbers): the start of the first range and
+    ///the end of the last one
     range: [i64; 2],
+    ///All the (inclusive, sorted and disjoint) ranges of lines the code comes from, usually just
+    ///one
+    ranges: Vec<[i64; 2]>,
     /// path of the current file that's being edited
     filepath: String,
     /// Field is left blank as of v0.3
     projectroot: String,
     /// field is left blank as of v0.3
     dependencies_path: Vec<String>,
-    /// path to the cache directory that sniprun create
+    /// path to the cache directory that sniprun create, or the one the user chose
+    /// (config: `work_dir`)
     work_dir: String,
+    /// the directory the code runs in: empty for the one the interpreter chooses (usually its
+    /// work directory), 'file' for the directory of the edited file, or a path
+    /// (config: `run_dir`, default empty)
+    run_dir: String,
     /// path to sniprun root, eg in case you need ressoruces from the ressources folder
     sniprun_root_dir: String,
+
+    /// try the next interpreter available for the filetype when one fails to build the code
+    /// (config: `fallback`, default true)
+    fallback: bool,
+    /// when the code does not compile, retry with the same interpreter at the lower support
+    /// levels (eg: the current line only) (config: `retry_lower_level`, or its alias
+    /// `auto_downgrade`, default false)
+    retry_lower_level: bool,
+    /// stream the output of the code to a scratch buffer while it runs
+    /// (config: `stream_output`, default false)
+    stream_output: bool,
+    /// where interpreters able to stream their output should send it, line by line
+    output_stream: OutputStream,
+    /// tell the user when a compilation starts and when the code starts running
+    /// (config: `show_progress`, default true)
+    show_progress: bool,
+    /// where the interpreters report the phases of a run
+    progress: Progress,
+    /// keep a copy of every generated source file in the work directory's 'generated'
+    /// subfolder, instead of overwriting it at each run (config: `keep_generated_files`)
+    keep_generated_files: bool,
+    /// when a run fails, keep a copy of the generated source file in the work directory's
+    /// 'failed' subfolder and point to it in the error message (config: `keep_temp`)
+    keep_temp: bool,
+    /// when the code ends with an expression, print its value like a REPL would
+    /// (config: `print_last_expression`, default false, only some interpreters support it)
+    print_last_expression: bool,
+    /// maximum memory the code may use, in MB (config: `max_memory_mb`, Unix only)
+    max_memory_mb: Option<u64>,
+    /// maximum CPU time the code may use, in seconds (config: `max_cpu_seconds`, Unix only)
+    max_cpu_seconds: Option<u64>,
+    /// run the code in a sandbox: "docker", or "" to run it natively (config: `sandbox`)
+    sandbox: String,
+    /// the image the sandbox runs the code in (config: `sandbox_image`)
+    sandbox_image: String,
+    /// kill the code if it runs for longer than this (config: `timeout`, in seconds)
+    timeout: Option<Duration>,
+    /// the `timeout`, `max_memory_mb` and `max_cpu_seconds` of an interpreter, by interpreter
+    /// name or filetype, overriding the global ones
+    /// (config: `limits`, eg: `{'rust': {'timeout': 60}, 'Python3_original': {'timeout': 5}}`)
+    limits: HashMap<String, HashMap<String, Value>>,
+    /// echo the result of the runs (config: `display`, default true)
+    display: bool,
+    /// vim function called with a structured result (a dictionary) after each run, so that
+    /// other plugins can use it (config: `result_function`)
+    result_function: Option<String>,
+    /// options specific to an interpreter, by interpreter name
+    /// (config: `interpreter_options`, eg: `{'Lua_original': {'backend': 'nvim'}}`)
+    interpreter_options: HashMap<String, HashMap<String, Value>>,
+    /// the only interpreters that may run code, by name, all of them if None
+    /// (config: `enabled_interpreters`, eg: `['Python3_original', 'Rust_original']`)
+    enabled_interpreters: Option<Vec<String>>,
+    /// the interpreters that never run code, by name, even if they are enabled
+    /// (config: `disabled_interpreters`, eg: `['Generic', 'Jupyter_original']`)
+    disabled_interpreters: Vec<String>,
+    /// the filetype the interpreters are looked for with, by filetype, eg: for custom ones
+    /// (config: `filetype_aliases`, eg: `{'mypython': 'python'}`)
+    filetype_aliases: HashMap<String, String>,
+    /// the interpreter to use for a filetype, by filetype, when several support it
+    /// (config: `interpreters`, eg: `{'python': 'Jupyter_original'}`)
+    preferred_interpreters: HashMap<String, String>,
+    /// code added before the code of a filetype, by filetype, eg: the imports always needed
+    /// (config: `preludes`, eg: `{'rust': 'use std::collections::HashMap;'}`)
+    preludes: HashMap<String, String>,
+    /// the interpreter to use for this run only, whatever the filetype and the preferences
+    /// (`b:sniprun_interpreter`)
+    selected_interpreter: Option<String>,
+    /// the support level the code runs at, if it is lower than the maximum of the interpreter
+    /// (config: `support_level`, or `b:sniprun_support_level`, eg: 'bloc')
+    support_level: Option<SupportLevel>,
+    /// what to do when a run starts while another one is still running (config: `run_policy`,
+    /// 'cancel' (default), 'queue' or 'drop')
+    run_policy: RunPolicy,
+    /// how many runs can execute at once, the others wait for one to finish
+    /// (config: `max_concurrent_runs`, default 4)
+    max_concurrent_runs: usize,
+    /// instead of echoing the result of the runs, store it as JSON in `g:sniprun_json` and
+    /// trigger the `User SniprunResult` autocommand (config: `json_output`, default false)
+    json_output: bool,
+    /// return the stored result when the same code is run again, instead of running it
+    /// (config: `cache_results`, default false)
+    cache_results: bool,
+    /// list the files the run created or modified in the directory the code runs in
+    /// (`run_dir`), eg: a plot (config: `capture_artifacts`, default false)
+    capture_artifacts: bool,
+    /// write a JSON line describing each run to the work directory's runs.jsonl
+    /// (config: `run_log`, default false)
+    run_log: bool,
+    /// size (in KB) above which runs.jsonl is moved to runs.jsonl.1, replacing it
+    /// (config: `run_log_max_kb`, default 1024)
+    run_log_max_kb: u64,
+    /// the output of a run is cut after that many bytes, the whole of it is written to a file
+    /// (config: `max_output_bytes`, default 1 MiB)
+    max_output_bytes: u64,
+    /// only build (or syntax check) the code, without running it
+    /// (config: `compile_only`, default false)
+    compile_only: bool,
+    /// how many runs `:SnipHistory` remembers (config: `history_size`, default 20)
+    history_size: usize,
+    /// save the history to the work directory, so that it survives restarts
+    /// (config: `persist_history`, default true)
+    persist_history: bool,
+    /// in live mode, how long to wait after the last change of the buffer before running the
+    /// code again (config: `live_delay`, in milliseconds, default 500)
+    live_delay: Duration,
+    /// the run was started by live mode, its result is shown as virtual text
+    live: bool,
+    /// cancelled when the result of the run is not wanted anymore
+    cancel: CancelToken,
+    /// the neovim instance sniprun is attached to, for interpreters that need it
+    nvim_instance: NeovimInstance,
+    /// the work directory made for a test, removed along with the last clone of the data
+    #[cfg(test)]
+    temp_dir: Option<Arc<TempDir>>,
+}
+
+/// a directory that is removed when dropped
+#[cfg(test)]
+#[derive(Debug, PartialEq)]
+struct TempDir(PathBuf);
+
+#[cfg(test)]
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
 }
 
 impl DataHolder {
     ///create a new but almost empty DataHolder
     fn new() -> Self {
-        std::fs::create_dir_all(format!(
-            "{}/{}",
-            cache_dir().unwrap().to_str().unwrap(),
-            "sniprun"
-        ))
-        .unwrap();
+        //systems without XDG dirs have no cache directory, use the temp one instead
+        let work_dir = cache_dir()
+            .unwrap_or_else(std::env::temp_dir)
+            .join("sniprun")
+            .to_string_lossy()
+            .to_string();
+        if let Err(e) = std::fs::create_dir_all(&work_dir) {
+            info!("[DATA] Could not create work directory {}: {}", work_dir, e);
+        }
 
         DataHolder {
             filetype: String::from(""),
             current_line: String::from(""),
             current_bloc: String::from(""),
             range: [-1, -1],
+            ranges: vec![],
             filepath: String::from(""),
             projectroot: String::from(""),
             dependencies_path: vec![],
-            work_dir: format!("{}/{}", cache_dir().unwrap().to_str().unwrap(), "sniprun"),
+            work_dir,
+            run_dir: String::new(),
             sniprun_root_dir: String::from(""),
+            fallback: true,
+            retry_lower_level: false,
+            stream_output: false,
+            output_stream: OutputStream::default(),
+            show_progress: true,
+            progress: Progress::default(),
+            keep_generated_files: false,
+            keep_temp: false,
+            print_last_expression: false,
+            max_memory_mb: None,
+            max_cpu_seconds: None,
+            sandbox: String::new(),
+            sandbox_image: String::new(),
+            timeout: None,
+            limits: HashMap::new(),
+            display: true,
+            result_function: None,
+            interpreter_options: HashMap::new(),
+            enabled_interpreters: None,
+            support_level: None,
+            filetype_aliases: HashMap::new(),
+            disabled_interpreters: vec![],
+            // lua snippets are mostly neovim configuration
+            preferred_interpreters: vec![
+                (String::from("julia"), String::from("Julia_original")),
+                (String::from("lua"), String::from("Lua_nvim")),
+            ]
+            .into_iter()
+            .collect(),
+            preludes: HashMap::new(),
+            selected_interpreter: None,
+            run_policy: RunPolicy::Cancel,
+            max_concurrent_runs: 4,
+            json_output: false,
+            cache_results: false,
+            capture_artifacts: false,
+            run_log: false,
+            run_log_max_kb: 1024,
+            max_output_bytes: 1024 * 1024,
+            compile_only: false,
+            history_size: 20,
+            persist_history: true,
+            live_delay: Duration::from_millis(500),
+            live: false,
+            cancel: CancelToken::default(),
+            nvim_instance: NeovimInstance::default(),
+            #[cfg(test)]
+            temp_dir: None,
+        }
+    }
+
+    ///create a DataHolder as if `code` was selected in a buffer of the given filetype, with its
+    ///own work directory: lets interpreters and the launcher run without a neovim session
+    #[cfg(test)]
+    fn with_code(filetype: &str, code: &str, work_dir: &str) -> Self {
+        if let Err(e) = std::fs::create_dir_all(work_dir) {
+            info!("[DATA] Could not create work directory {}: {}", work_dir, e);
+        }
+        let line_count = code.lines().count().max(1) as i64;
+        DataHolder {
+            filetype: filetype.to_string(),
+            current_line: code.lines().next().unwrap_or("").to_string(),
+            current_bloc: code.to_string(),
+            range: [1, line_count],
+            ranges: vec![[1, line_count]],
+            work_dir: work_dir.to_string(),
+            ..DataHolder::new()
+        }
+    }
+
+    ///same as `with_code`, in a new work directory of the system's temp directory: each call
+    ///gets its own, so that runs in parallel (eg: of several interpreters) don't share files.
+    ///The directory is removed once the data and all its clones are dropped
+    #[cfg(test)]
+    fn with_code_in_temp_dir(filetype: &str, code: &str) -> Self {
+        static COUNT: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+        let work_dir = std::env::temp_dir().join(format!(
+            "sniprun-{}-{}",
+            std::process::id(),
+            COUNT.fetch_add(1, std::sync::atomic::Ordering::SeqCst)
+        ));
+        DataHolder {
+            temp_dir: Some(Arc::new(TempDir(work_dir.clone()))),
+            ..Self::with_code(filetype, code, &work_dir.to_string_lossy())
+        }
+    }
+
+    ///an interpreter of type I for `code` selected in a buffer of the filetype (as
+    ///`with_code_in_temp_dir` does), with the given interpreter options: it fetched the code
+    ///and added its boilerplate, it is ready to build
+    #[cfg(test)]
+    fn prepared<I: interpreter::Interpreter>(
+        filetype: &str,
+        code: &str,
+        options: &[(&str, Value)],
+    ) -> Box<I> {
+        Self::with_code_in_temp_dir(filetype, code).into_prepared(options)
+    }
+
+    ///same as `prepared`, for this data
+    #[cfg(test)]
+    fn into_prepared<I: interpreter::Interpreter>(mut self, options: &[(&str, Value)]) -> Box<I> {
+        if !options.is_empty() {
+            let options = options
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.clone()))
+                .collect();
+            self.interpreter_options.insert(I::get_name(), options);
+        }
+        interpreter::create_work_dir(&self, &I::get_work_dir_name()).unwrap();
+        let mut interpreter = I::new(self);
+        interpreter.fetch_code().unwrap();
+        interpreter.add_boilerplate().unwrap();
+        interpreter
+    }
+
+    ///apply the user's configuration (the `g:sniprun_config` dictionary), unknown keys are ignored
+    fn load_config(&mut self, config: &Value) {
+        let entries = match config.as_map() {
+            Some(entries) => entries,
+            None => return,
+        };
+        for (key, value) in entries {
+            match key.as_str().unwrap_or("") {
+                "fallback" => self.fallback = config_bool(value, self.fallback),
+                "retry_lower_level" | "auto_downgrade" => {
+                    self.retry_lower_level = config_bool(value, self.retry_lower_level)
+                }
+                "stream_output" => self.stream_output = config_bool(value, self.stream_output),
+                "show_progress" => self.show_progress = config_bool(value, self.show_progress),
+                "keep_generated_files" => {
+                    self.keep_generated_files = config_bool(value, self.keep_generated_files)
+                }
+                "keep_temp" => self.keep_temp = config_bool(value, self.keep_temp),
+                "print_last_expression" => {
+                    self.print_last_expression = config_bool(value, self.print_last_expression)
+                }
+                "max_memory_mb" => self.max_memory_mb = config_limit(value),
+                "max_cpu_seconds" => self.max_cpu_seconds = config_limit(value),
+                "sandbox" => self.sandbox = value.as_str().unwrap_or("").to_string(),
+                "sandbox_image" => self.sandbox_image = value.as_str().unwrap_or("").to_string(),
+                "timeout" => self.timeout = config_duration(value),
+                "limits" => self.limits = config_options(value),
+                "display" => self.display = config_bool(value, self.display),
+                "cache_results" => self.cache_results = config_bool(value, self.cache_results),
+                "support_level" => match value.as_str().filter(|level| !level.is_empty()) {
+                    Some(level) => self.ask_support_level(level, "support_level"),
+                    None => self.support_level = None,
+                },
+                "capture_artifacts" => {
+                    self.capture_artifacts = config_bool(value, self.capture_artifacts)
+                }
+                "run_log" => self.run_log = config_bool(value, self.run_log),
+                "compile_only" => self.compile_only = config_bool(value, self.compile_only),
+                "max_output_bytes" => {
+                    if let Some(size) = config_limit(value) {
+                        self.max_output_bytes = size;
+                    }
+                }
+                "run_log_max_kb" => {
+                    if let Some(size) = config_limit(value) {
+                        self.run_log_max_kb = size;
+                    }
+                }
+                "json_output" => self.json_output = config_bool(value, self.json_output),
+                "result_function" => {
+                    self.result_function = value
+                        .as_str()
+                        .filter(|name| !name.is_empty())
+                        .map(String::from)
+                }
+                "interpreter_options" => self.interpreter_options.extend(config_options(value)),
+                "enabled_interpreters" => self.enabled_interpreters = Some(config_list(value)),
+                "disabled_interpreters" => self.disabled_interpreters = config_list(value),
+                "filetype_aliases" => {
+                    for (filetype, alias) in value.as_map().into_iter().flatten() {
+                        if let (Some(filetype), Some(alias)) = (filetype.as_str(), alias.as_str()) {
+                            self.filetype_aliases
+                                .insert(filetype.to_lowercase(), alias.to_string());
+                        }
+                    }
+                }
+                "interpreters" => {
+                    for (filetype, interpreter) in value.as_map().into_iter().flatten() {
+                        if let (Some(filetype), Some(interpreter)) =
+                            (filetype.as_str(), interpreter.as_str())
+                        {
+                            self.preferred_interpreters
+                                .insert(filetype.to_string(), interpreter.to_string());
+                        }
+                    }
+                }
+                "preludes" => {
+                    for (filetype, prelude) in value.as_map().into_iter().flatten() {
+                        if let (Some(filetype), Some(prelude)) =
+                            (filetype.as_str(), prelude.as_str())
+                        {
+                            self.preludes
+                                .insert(filetype.to_string(), prelude.to_string());
+                        }
+                    }
+                }
+                "run_policy" => match value.as_str().unwrap_or("") {
+                    "queue" => self.run_policy = RunPolicy::Queue,
+                    "cancel" => self.run_policy = RunPolicy::Cancel,
+                    "drop" => self.run_policy = RunPolicy::Drop,
+                    other => info!("[DATA] Unknown run policy: {}", other),
+                },
+                "max_concurrent_runs" => {
+                    if let Some(max) = config_limit(value) {
+                        self.max_concurrent_runs = max as usize;
+                    }
+                }
+                "history_size" => {
+                    if let Some(size) = config_limit(value) {
+                        self.history_size = size as usize;
+                    }
+                }
+                "persist_history" => {
+                    self.persist_history = config_bool(value, self.persist_history)
+                }
+                "live_delay" => {
+                    if let Some(delay) = config_limit(value) {
+                        self.live_delay = Duration::from_millis(delay);
+                    }
+                }
+                "run_dir" => self.run_dir = value.as_str().unwrap_or("").to_string(),
+                "work_dir" => match usable_work_dir(value.as_str().unwrap_or("")) {
+                    Ok(work_dir) => self.work_dir = work_dir,
+                    Err(e) => info!(
+                        "[DATA] Cannot use work directory {:?} ({}), using {} instead",
+                        value, e, self.work_dir
+                    ),
+                },
+                "log_level" => {
+                    if let Err(e) = set_log_level(value.as_str().unwrap_or("")) {
+                        info!("[DATA] {}", e);
+                    }
+                }
+                other => info!("[DATA] Unknown configuration key: {}", other),
+            }
+        }
+    }
+
+    ///the option `key` the user set for the interpreter named `interpreter`, if any
+    fn interpreter_option(&self, interpreter: &str, key: &str) -> Option<&Value> {
+        self.interpreter_options.get(interpreter)?.get(key)
+    }
+
+    ///copy of self with the limits set for the interpreter that has the given name, or for
+    ///the filetype of the code: the ones of the interpreter come first, then the ones of the
+    ///filetype, then the global ones. A limit set to 0 lifts the global one
+    fn with_limits(&self, interpreter: &str) -> DataHolder {
+        let mut data = self.clone();
+        for key in [&self.filetype, interpreter].iter() {
+            for (limit, value) in self.limits.get(*key).into_iter().flatten() {
+                match limit.as_str() {
+                    "timeout" => data.timeout = config_duration(value),
+                    "max_memory_mb" => data.max_memory_mb = config_limit(value),
+                    "max_cpu_seconds" => data.max_cpu_seconds = config_limit(value),
+                    _ => info!("[DATA] unknown limit {} for {}", limit, key),
+                }
+            }
+        }
+        data
+    }
+
+    ///whether the user lets the interpreter that has the given name run code: a disabled one
+    ///never does, even if it is also enabled
+    fn is_enabled(&self, interpreter: &str) -> bool {
+        let listed =
+            |names: &Vec<String>| names.iter().any(|n| n.eq_ignore_ascii_case(interpreter));
+        !listed(&self.disabled_interpreters)
+            && self.enabled_interpreters.as_ref().is_none_or(listed)
+    }
+
+    ///cap the support level to the one the user asked for (in `origin`, eg: the config); an
+    ///invalid one is ignored, like any invalid config value
+    fn ask_support_level(&mut self, level: &str, origin: &str) {
+        match level.parse() {
+            Ok(level) => self.support_level = Some(level),
+            Err(e) => info!("[DATA] {} ignored: {}", origin, e),
+        }
+    }
+
+    ///the support level an interpreter runs the code at: its maximum one, or the one the user
+    ///asked for if it is lower
+    fn support_level_for(&self, max: SupportLevel) -> SupportLevel {
+        self.support_level.map_or(max, |level| level.capped(max))
+    }
+
+    ///the prelude the user set for the filetype of the code, if any
+    fn prelude(&self) -> Option<&str> {
+        self.preludes
+            .get(&self.filetype)
+            .map(String::as_str)
+            .filter(|prelude| !prelude.trim().is_empty())
+    }
+
+    ///copy of self fit for logging: huge blocs are cut
+    fn summary(&self) -> DataHolder {
+        let mut summary = self.clone();
+        if summary.current_bloc.len() > 500 {
+            let mut end = 500;
+            while !summary.current_bloc.is_char_boundary(end) {
+                end -= 1;
+            }
+            summary.current_bloc = format!("{}[...]", &summary.current_bloc[..end]);
+        }
+        summary
+    }
+
+    ///the line of the buffer the n-th line (1-based) of the code comes from, following the
+    ///ranges; None if the code has no range or less lines
+    fn buffer_line(&self, line: i64) -> Option<i64> {
+        if line < 1 {
+            return None;
+        }
+        let mut remaining = line;
+        for [start, end] in &self.ranges {
+            let length = end - start + 1;
+            if remaining <= length {
+                return Some(start + remaining - 1);
+            }
+            remaining -= length;
+        }
+        None
+    }
+
+    ///the directory the user wants the code to run in (config: `run_dir`), if any
+    fn execution_dir(&self) -> Option<PathBuf> {
+        match self.run_dir.as_str() {
+            "" => None,
+            "file" => Path::new(&self.filepath)
+                .parent()
+                .filter(|dir| dir.is_dir())
+                .map(Path::to_path_buf),
+            dir => Some(PathBuf::from(dir)).filter(|dir| dir.is_dir()),
+        }
+    }
+
+    ///path to sniprun's log file
+    fn log_file(&self) -> String {
+        format!("{}/{}", self.work_dir, LOG_FILE)
+    }
+
+    ///path to the file the history of the runs is saved in
+    fn history_file(&self) -> String {
+        format!("{}/history.jsonl", self.work_dir)
+    }
+
+    ///copy a file to the given subfolder of the work directory, under a timestamped name so
+    ///that the next runs don't overwrite it; returns the path of the copy
+    fn keep_copy(&self, file: &str, subfolder: &str) -> std::io::Result<String> {
+        let keep_dir = format!("{}/{}", self.work_dir, subfolder);
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or(0);
+        let file_name = std::path::Path::new(file)
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let kept_file = format!("{}/{}_{}", keep_dir, timestamp, file_name);
+        std::fs::create_dir_all(&keep_dir)?;
+        std::fs::copy(file, &kept_file)?;
+        Ok(kept_file)
+    }
+
+    ///append a line to the work directory's runs.jsonl, rotating it first if it is too big
+    fn log_run(&self, record: &str) -> std::io::Result<()> {
+        use std::io::Write;
+        let file = format!("{}/runs.jsonl", self.work_dir);
+        let size = std::fs::metadata(&file).map(|m| m.len()).unwrap_or(0);
+        if size > self.run_log_max_kb * 1024 {
+            std::fs::rename(&file, format!("{}.1", file))?;
+        }
+        let mut log = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&file)?;
+        writeln!(log, "{}", record)
+    }
+
+    ///the result, with its output cut (on a character boundary) if it is longer than
+    ///max_output_bytes; the whole output is then written to the work directory's
+    ///full_output.txt, and the cut output ends with a marker saying so
+    fn truncate_output(&self, mut result: ExecutionResult) -> ExecutionResult {
+        let max = self.max_output_bytes as usize;
+        if result.stdout.len() <= max {
+            return result;
+        }
+        let mut end = max;
+        while !result.stdout.is_char_boundary(end) {
+            end -= 1;
+        }
+        let file = format!("{}/full_output.txt", self.work_dir);
+        let saved = match std::fs::write(&file, &result.stdout) {
+            Ok(()) => format!(", the whole output is in {}", file),
+            Err(e) => {
+                info!("[DATA] Could not save the whole output to {}: {}", file, e);
+                String::new()
+            }
+        };
+        let marker = format!(
+            "\n… [output truncated, {} more bytes{}]\n",
+            result.stdout.len() - end,
+            saved
+        );
+        result.stdout.truncate(end);
+        result.stdout.push_str(&marker);
+        result
+    }
+
+    ///empty the cache directory, except the log that sniprun writes in (is invoked by
+    ///`:SnipReset`)
+    fn clean_dir(&mut self) -> Result<(), SniprunError> {
+        let to_error = |e: std::io::Error| {
+            SniprunError::InternalError(format!(
+                "could not clean directory {}: {}",
+                self.work_dir, e
+            ))
+        };
+        std::fs::create_dir_all(&self.work_dir).map_err(to_error)?;
+        for entry in std::fs::read_dir(&self.work_dir).map_err(to_error)? {
+            let path = entry.map_err(to_error)?.path();
+            if path.file_name().is_some_and(|name| name == LOG_FILE) {
+                continue;
+            }
+            if path.is_dir() {
+                std::fs::remove_dir_all(&path).map_err(to_error)?;
+            } else {
+                std::fs::remove_file(&path).map_err(to_error)?;
+            }
+        }
+        Ok(())
+    }
+
+    ///remove and recreate the given interpreters' subfolders of the cache directory
+    ///(is invoked by `:SnipReset <interpreter or filetype>`)
+    fn clean_interpreter_dirs(&mut self, dir_names: &[String]) -> Result<(), SniprunError> {
+        for name in dir_names {
+            recreate_dir(&format!("{}/{}", self.work_dir, name))?;
+        }
+        Ok(())
+    }
+}
+
+/// the ranges of lines to run: either the first two arguments (start and end lines), or a
+/// list of [start, end] pairs as first argument (the second one is then ignored)
+fn parse_ranges(values: &[Value]) -> Result<Vec<[i64; 2]>, SniprunError> {
+    let invalid = || {
+        SniprunError::InternalError(format!(
+            "invalid arguments, expected a range but got {:?}",
+            values
+        ))
+    };
+    let ranges = match values.first().and_then(Value::as_array) {
+        Some(pairs) => pairs
+            .iter()
+            .map(|pair| match pair.as_array().map(Vec::as_slice) {
+                Some([start, end]) => Some([start.as_i64()?, end.as_i64()?]),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(invalid)?,
+        None => match (
+            values.first().and_then(Value::as_i64),
+            values.get(1).and_then(Value::as_i64),
+        ) {
+            (Some(start), Some(end)) => vec![[start, end]],
+            _ => return Err(invalid()),
+        },
+    };
+    if ranges.is_empty() {
+        return Err(invalid());
+    }
+    for [start, end] in &ranges {
+        if *start < 1 || end < start {
+            return Err(SniprunError::InternalError(format!(
+                "invalid range {}..{}",
+                start, end
+            )));
         }
     }
-    ///remove and recreate the cache directory (is invoked by `:SnipReset`)
-    fn clean_dir(&mut self) {
-        let work_dir_path = self.work_dir.clone();
-        std::fs::remove_dir_all(&work_dir_path).unwrap();
-        std::fs::create_dir_all(&work_dir_path).unwrap();
+    Ok(ranges)
+}
+
+/// sort the ranges in buffer order and merge the ones that overlap or touch; they are
+/// clamped to the buffer, that may have shrunk since they were computed
+fn normalize_ranges(mut ranges: Vec<[i64; 2]>, line_count: i64) -> Vec<[i64; 2]> {
+    ranges.sort_unstable();
+    let mut normalized: Vec<[i64; 2]> = vec![];
+    for [start, end] in ranges {
+        let (start, end) = (start.min(line_count), end.min(line_count));
+        match normalized.last_mut() {
+            Some(last) if start <= last[1] + 1 => last[1] = last[1].max(end),
+            _ => normalized.push([start, end]),
+        }
+    }
+    normalized
+}
+
+/// remove (if it exists) and recreate a directory
+fn recreate_dir(path: &str) -> Result<(), SniprunError> {
+    let to_error = |e: std::io::Error| {
+        SniprunError::InternalError(format!("could not clean directory {}: {}", path, e))
+    };
+    match std::fs::remove_dir_all(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(to_error(e)),
+        _ => (),
+    }
+    std::fs::create_dir_all(path).map_err(to_error)
+}
+
+/// the absolute path of the directory, created if needed, if sniprun can write in it
+fn usable_work_dir(path: &str) -> Result<String, String> {
+    if path.trim().is_empty() {
+        return Err(String::from("empty path"));
+    }
+    let path = match path.strip_prefix("~/") {
+        Some(relative) => dirs::home_dir()
+            .ok_or_else(|| String::from("no home directory"))?
+            .join(relative),
+        None => PathBuf::from(path),
+    };
+    let path = std::env::current_dir()
+        .map(|cwd| cwd.join(&path))
+        .unwrap_or(path);
+    std::fs::create_dir_all(&path).map_err(|e| e.to_string())?;
+    let probe = path.join(".sniprun_probe");
+    std::fs::write(&probe, "").map_err(|e| e.to_string())?;
+    let _ = std::fs::remove_file(probe);
+    Ok(path.to_string_lossy().to_string())
+}
+
+/// change the log level (off/error/warn/info/debug/trace) of the whole program
+fn set_log_level(level: &str) -> Result<(), SniprunError> {
+    let filter: LevelFilter = level.trim().parse().map_err(|_| {
+        SniprunError::CustomError(format!(
+            "invalid log level '{}', expected one of off/error/warn/info/debug/trace",
+            level
+        ))
+    })?;
+    log::set_max_level(filter);
+    info!("[MAIN] Log level set to {}", filter);
+    Ok(())
+}
+
+/// the filetype in the output of `:set ft?` (eg: '  filetype=rust'), empty if there is none
+fn parse_filetype(set_ft_output: &str) -> String {
+    match set_ft_output.trim().rsplit_once('=') {
+        Some((option, filetype)) if option.trim().ends_with("filetype") => {
+            filetype.trim().to_string()
+        }
+        _ => String::new(),
     }
 }
 
+/// a positive number of something, anything else means no limit
+fn config_limit(value: &Value) -> Option<u64> {
+    value.as_u64().filter(|limit| *limit > 0)
+}
+
+/// a duration in seconds, fractional or not, that must be positive
+fn config_duration(value: &Value) -> Option<Duration> {
+    value
+        .as_f64()
+        .or_else(|| value.as_i64().map(|t| t as f64))
+        .filter(|t| *t > 0.0)
+        .map(Duration::from_secs_f64)
+}
+
+/// a list of strings, the other items are ignored
+fn config_list(value: &Value) -> Vec<String> {
+    value
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|item| item.as_str().map(String::from))
+        .collect()
+}
+
+/// a dictionary of dictionaries, eg: the options of the interpreters, by interpreter name
+fn config_options(value: &Value) -> HashMap<String, HashMap<String, Value>> {
+    value
+        .as_map()
+        .into_iter()
+        .flatten()
+        .filter_map(|(name, options)| {
+            let options = options
+                .as_map()
+                .into_iter()
+                .flatten()
+                .filter_map(|(k, v)| Some((k.as_str()?.to_string(), v.clone())))
+                .collect();
+            Some((name.as_str()?.to_string(), options))
+        })
+        .collect()
+}
+
+/// vimscript has no real booleans: accept v:true/v:false as well as 0/1
+fn config_bool(value: &Value, default: bool) -> bool {
+    value
+        .as_bool()
+        .or_else(|| value.as_i64().map(|i| i != 0))
+        .unwrap_or(default)
+}
+
 struct EventHandler {
-    nvim: Neovim,
+    nvim: Arc<Mutex<Neovim>>,
     data: DataHolder,
+    /// source file generated by the last run, if any
+    last_generated_file: Option<String>,
+    /// the data of the last run started by a run command, run again by `:SnipReplay`
+    last_run: Option<DataHolder>,
+    /// held by the run in progress, when runs are queued
+    run_queue: Arc<Mutex<()>>,
+    /// taken by the runs while they execute, so that at most `max_concurrent_runs` do
+    run_slots: Arc<RunSlots>,
+    /// the runs in progress
+    jobs: Jobs,
+    /// the last completed runs
+    history: History,
+    /// the code re-run on each change of the buffer, if live mode is enabled
+    live: Option<LiveSession>,
+    /// neovim namespaces of sniprun's virtual text and highlights, by name, created when
+    /// first needed
+    namespaces: HashMap<&'static str, i64>,
+}
+
+/// name of sniprun's log file, in the work directory
+const LOG_FILE: &str = "sniprun.log";
+/// namespace of the virtual text showing the results of live mode
+const LIVE_NAMESPACE: &str = "sniprun_live";
+/// namespace of the highlights of the lines errors come from
+const ERRORS_NAMESPACE: &str = "sniprun_errors";
+
+/// what live mode re-runs, and where it shows the results
+struct LiveSession {
+    /// the arguments of the `enable_live` message, given again to fill_data at each run
+    values: Vec<Value>,
+    buffer: Buffer,
+    delay: Duration,
+    /// incremented on each change of the buffer, so that only the last one of a burst of
+    /// changes triggers a run
+    generation: usize,
+}
+
+/// what to do with a run that starts while another one is still running
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum RunPolicy {
+    /// cancel the previous one, its result is never shown
+    Cancel,
+    /// wait for the previous one to finish
+    Queue,
+    /// ignore the new one
+    Drop,
+}
+
+/// what part of the buffer a run is about
+#[derive(Clone, Copy, PartialEq)]
+enum RunScope {
+    /// the lines (or ranges) given by the run command
+    Selection,
+    /// from the first line given to the end of the buffer
+    ToEnd,
+    /// the whole buffer
+    Buffer,
+}
+
+impl RunScope {
+    /// the lines of a buffer of `line_count` lines to run, given the ones of the run command
+    /// (there is at least one range)
+    fn ranges(self, ranges: Vec<[i64; 2]>, line_count: i64) -> Vec<[i64; 2]> {
+        //an empty buffer still has one (empty) line
+        let line_count = line_count.max(1);
+        match self {
+            RunScope::Selection => normalize_ranges(ranges, line_count),
+            RunScope::ToEnd => vec![[ranges[0][0].min(line_count), line_count]],
+            RunScope::Buffer => vec![[1, line_count]],
+        }
+    }
 }
 
 enum Messages {
     Run,
+    RunToEnd,
+    RunFile,
     Clean,
+    SetLogLevel,
+    ShowLog,
+    ShowCode,
+    ShowHistory,
+    Info,
+    EnableLive,
+    DisableLive,
+    BufferChanged,
+    Stop,
+    Replay,
+    Capabilities,
     Unknown(String),
 }
 
@@ -86,80 +924,671 @@ impl From<String> for Messages {
     fn from(event: String) -> Self {
         match &event[..] {
             "run" => Messages::Run,
+            "run_to_eof" => Messages::RunToEnd,
+            "run_file" | "run_buffer" => Messages::RunFile,
             "clean" => Messages::Clean,
+            "set_log_level" => Messages::SetLogLevel,
+            "show_log" => Messages::ShowLog,
+            "show_code" => Messages::ShowCode,
+            "show_history" => Messages::ShowHistory,
+            "info" => Messages::Info,
+            "enable_live" => Messages::EnableLive,
+            "disable_live" => Messages::DisableLive,
+            "buffer_changed" => Messages::BufferChanged,
+            "stop" => Messages::Stop,
+            "replay" => Messages::Replay,
+            "capabilities" => Messages::Capabilities,
             _ => Messages::Unknown(event),
         }
     }
 }
 
 impl EventHandler {
-    fn new() -> EventHandler {
-        let session = Session::new_parent().unwrap();
-        let nvim = Neovim::new(session);
-        let data = DataHolder::new();
-        EventHandler { nvim, data }
+    fn new(session: Session) -> EventHandler {
+        let nvim = Arc::new(Mutex::new(Neovim::new(session)));
+        let mut data = DataHolder::new();
+        //the history and the log are in the work directory the user may have set
+        if let Ok(config) = lock(&nvim).get_var("sniprun_config") {
+            data.load_config(&config);
+        }
+        let history = History::load(&data.history_file());
+        EventHandler {
+            nvim,
+            data,
+            last_generated_file: None,
+            last_run: None,
+            run_queue: Arc::new(Mutex::new(())),
+            run_slots: Arc::new(RunSlots::default()),
+            jobs: Jobs::default(),
+            history,
+            live: None,
+            namespaces: HashMap::new(),
+        }
     }
 
-    /// fill the DataHolder with data from sniprun and Neovim
-    fn fill_data(&mut self, values: Vec<Value>) {
-        self.data.range = [values[0].as_i64().unwrap(), values[1].as_i64().unwrap()];
-        self.data.sniprun_root_dir = String::from(values[2].as_str().unwrap());
+    /// gather the data for a run from sniprun and Neovim: the code selected now, the config...
+    ///the scope tells whether the range received is used as-is, extended to the end of the buffer,
+    ///or ignored for the whole buffer
+    fn fill_data(&self, values: Vec<Value>, scope: RunScope) -> Result<DataHolder, SniprunError> {
+        let mut data = DataHolder::new();
+        let ranges = parse_ranges(&values)?;
+        data.sniprun_root_dir = values
+            .get(2)
+            .and_then(Value::as_str)
+            .map(String::from)
+            .ok_or_else(|| {
+                SniprunError::InternalError(String::from("missing sniprun root directory"))
+            })?;
+        if let Some(config) = values.get(3) {
+            data.load_config(config);
+        }
+
+        data.nvim_instance = NeovimInstance::new(self.nvim.clone());
+        let nvim_instance = self.nvim.clone();
+        let mut nvim = lock(&nvim_instance);
 
         //get filetype
-        let ft = self.nvim.command_output("set ft?");
+        let ft = nvim.command_output("set ft?");
         if let Ok(real_ft) = ft {
-            self.data.filetype = String::from(real_ft.split("=").last().unwrap());
+            data.filetype = parse_filetype(&real_ft);
         }
 
         //get current line
-        let current_line = self.nvim.get_current_line();
+        let current_line = nvim.get_current_line();
         if let Ok(real_current_line) = current_line {
-            self.data.current_line = real_current_line;
+            data.current_line = real_current_line;
         }
 
-        //get current bloc
-        let current_bloc = self.nvim.get_current_buf().unwrap().get_lines(
-            &mut self.nvim,
-            self.data.range[0] - 1, //because the function is 0-based instead of 1 and end-exclusive
-            self.data.range[1],
-            false,
-        );
-        if let Ok(real_current_bloc) = current_bloc {
-            self.data.current_bloc = real_current_bloc.join("\n");
+        //get current bloc, the range may be out of date if the buffer shrank
+        let buffer = nvim
+            .get_current_buf()
+            .map_err(|e| SniprunError::InternalError(format!("could not read buffer: {}", e)))?;
+        let line_count = buffer
+            .line_count(&mut nvim)
+            .map_err(|e| SniprunError::InternalError(format!("could not read buffer: {}", e)))?;
+        data.ranges = scope.ranges(ranges, line_count);
+        data.range = [data.ranges[0][0], data.ranges[data.ranges.len() - 1][1]];
+        let mut current_bloc = vec![];
+        for range in &data.ranges {
+            let lines = buffer
+                .get_lines(
+                    &mut nvim,
+                    range[0] - 1, //because the function is 0-based instead of 1 and end-exclusive
+                    range[1],
+                    false,
+                )
+                .map_err(|e| {
+                    SniprunError::InternalError(format!("could not read buffer: {}", e))
+                })?;
+            current_bloc.extend(lines);
+        }
+        data.current_bloc = current_bloc.join("\n");
+
+        //the interpreter chosen for the buffer, if any
+        data.selected_interpreter = buffer
+            .get_var(&mut nvim, "sniprun_interpreter")
+            .ok()
+            .and_then(|name| name.as_str().map(String::from))
+            .filter(|name| !name.is_empty());
+
+        //the support level asked for the buffer, if any
+        if let Some(level) = buffer
+            .get_var(&mut nvim, "sniprun_support_level")
+            .ok()
+            .and_then(|level| level.as_str().map(String::from))
+            .filter(|level| !level.is_empty())
+        {
+            data.ask_support_level(&level, "b:sniprun_support_level");
         }
 
         //get full file path
-        let full_file_path = self.nvim.command_output("echo expand('%:p')");
+        let full_file_path = nvim.command_output("echo expand('%:p')");
         if let Ok(real_full_file_path) = full_file_path {
-            self.data.filepath = real_full_file_path;
+            data.filepath = real_full_file_path;
+        }
+        Ok(data)
+    }
+
+    /// remember the source file generated by the last run, copying it to the 'generated'
+    /// subfolder first if the user wants to keep them all
+    fn set_generated_file(&mut self, generated_file: Option<String>) {
+        let generated_file = match generated_file {
+            Some(generated_file) => generated_file,
+            None => {
+                self.last_generated_file = None;
+                return;
+            }
+        };
+        if !self.data.keep_generated_files {
+            self.last_generated_file = Some(generated_file);
+            return;
+        }
+
+        match self.data.keep_copy(&generated_file, "generated") {
+            Ok(kept_file) => self.last_generated_file = Some(kept_file),
+            Err(e) => {
+                info!(
+                    "[MAIN] Could not keep generated file {}: {}",
+                    generated_file, e
+                );
+                self.last_generated_file = Some(generated_file);
+            }
+        }
+    }
+
+    /// open the source file generated by the last run in a read-only split
+    fn show_generated_file(&mut self) -> Result<(), SniprunError> {
+        let file = self.last_generated_file.clone().ok_or_else(|| {
+            SniprunError::CustomError(String::from(
+                "no generated code to show: nothing has been run yet, or the interpreter does not generate files",
+            ))
+        })?;
+        lock(&self.nvim)
+            .command(&format!("sview {}", file.replace(' ', "\\ ")))
+            .map_err(|e| SniprunError::InternalError(format!("could not open {}: {}", file, e)))
+    }
+
+    /// display the output of a run in neovim's message area
+    fn display_output(&mut self, output: &str) {
+        echo_output(&self.nvim, output);
+    }
+
+    /// open a scratch buffer in a split (the cursor stays where it was), to stream output into
+    fn open_output_buffer(&mut self) -> Result<Buffer, SniprunError> {
+        let to_error =
+            |e| SniprunError::InternalError(format!("could not open output buffer: {}", e));
+        let mut nvim = lock(&self.nvim);
+        nvim.command(
+            "botright new | setlocal buftype=nofile bufhidden=wipe noswapfile nobuflisted",
+        )
+        .map_err(to_error)?;
+        let buffer = nvim.get_current_buf().map_err(to_error)?;
+        nvim.command("wincmd p").map_err(to_error)?;
+        Ok(buffer)
+    }
+
+    /// the history of the runs saved in that file (see DataHolder::history_file): the user may
+    /// have changed the work directory since the history was loaded
+    fn history_in(&mut self, file: &str) -> &mut History {
+        if self.history.file() != file {
+            self.history = History::load(file);
+        }
+        &mut self.history
+    }
+
+    /// show the last runs, newest first, in a scratch buffer
+    fn show_history(&mut self, count: Option<usize>) -> Result<(), SniprunError> {
+        let buffer = self.open_output_buffer()?;
+        let lines = self.history.render(count);
+        buffer
+            .set_lines(&mut lock(&self.nvim), 0, -1, false, lines)
+            .map_err(|e| SniprunError::InternalError(format!("could not show history: {}", e)))
+    }
+
+    /// describe the interpreters that would run the current selection, without running it
+    fn selection_report(data: DataHolder) -> String {
+        let mut report = format!("filetype: {}\n", data.filetype);
+        let launcher = launcher::Launcher::new(data);
+        let candidates = launcher.select();
+        if candidates.is_empty() {
+            report.push_str(
+                "no enabled interpreter (see enabled_interpreters and disabled_interpreters)\n",
+            );
+        }
+        for (i, (name, max_level)) in candidates.into_iter().enumerate() {
+            let role = if i == 0 { "selected" } else { "fallback" };
+            let level = launcher
+                .negotiated_level(&name)
+                .map(|level| level.to_string())
+                .unwrap_or_else(|| String::from("?"));
+            report.push_str(&format!(
+                "{}: {} (max support level: {}, current: {})\n",
+                role, name, max_level, level
+            ));
+        }
+        report
+    }
+
+    /// what sniprun can do, as a vim dictionary: every interpreter ('interpreters', see
+    /// launcher::InterpreterInfo) and the interpreters of every filetype ('filetypes'), by name
+    fn capabilities() -> Value {
+        let interpreters = launcher::Launcher::interpreters();
+        let mut filetypes: Vec<(String, Vec<Value>)> = vec![];
+        for interpreter in interpreters
+            .iter()
+            .filter(|i| i.max_support_level > SupportLevel::Unsupported)
+        {
+            for language in &interpreter.languages {
+                let name = Value::from(interpreter.name.as_str());
+                match filetypes
+                    .iter_mut()
+                    .find(|(filetype, _)| filetype == language)
+                {
+                    Some((_, names)) => names.push(name),
+                    None => filetypes.push((language.clone(), vec![name])),
+                }
+            }
+        }
+        filetypes.sort_by(|a, b| a.0.cmp(&b.0));
+        Value::Map(vec![
+            (
+                Value::from("interpreters"),
+                Value::Array(interpreters.iter().map(|i| i.to_value()).collect()),
+            ),
+            (
+                Value::from("filetypes"),
+                Value::Map(
+                    filetypes
+                        .into_iter()
+                        .map(|(filetype, names)| (Value::from(filetype), Value::Array(names)))
+                        .collect(),
+                ),
+            ),
+        ])
+    }
+
+    /// the id of the neovim namespace with that name, created if needed
+    fn namespace(&mut self, name: &'static str) -> Option<i64> {
+        if let Some(namespace) = self.namespaces.get(name) {
+            return Some(*namespace);
+        }
+        match lock(&self.nvim).create_namespace(name) {
+            Ok(namespace) => {
+                self.namespaces.insert(name, namespace);
+                Some(namespace)
+            }
+            Err(e) => {
+                info!("[MAIN] Could not create namespace {}: {}", name, e);
+                None
+            }
+        }
+    }
+
+    /// remove the error highlights of the previous run from the buffer
+    fn clear_error_lines(&mut self, buffer: &Buffer) {
+        if let Some(namespace) = self.namespaces.get(ERRORS_NAMESPACE).copied() {
+            let _ = buffer.clear_namespace(&mut lock(&self.nvim), namespace, 0, -1);
+        }
+    }
+
+    /// highlight the line (1-based) of the buffer an error comes from
+    fn highlight_error_line(&mut self, buffer: &Buffer, line: i64) {
+        let namespace = match self.namespace(ERRORS_NAMESPACE) {
+            Some(namespace) => namespace,
+            None => return,
+        };
+        let highlighted = buffer.add_highlight(
+            &mut lock(&self.nvim),
+            namespace,
+            "SniprunErrorLine",
+            line - 1,
+            0,
+            -1,
+        );
+        if let Err(e) = highlighted {
+            info!("[MAIN] Could not highlight line {}: {}", line, e);
         }
     }
+
+    /// show the result of a live run as virtual text at the end of the given line (1-based),
+    /// replacing the previous one; errors are highlighted as such
+    fn display_virtual_text(&mut self, line: i64, text: &str, error: bool) {
+        let buffer = match &self.live {
+            Some(live) => live.buffer.clone(),
+            None => return,
+        };
+        let namespace = match self.namespace(LIVE_NAMESPACE) {
+            Some(namespace) => namespace,
+            None => return,
+        };
+        let mut nvim = lock(&self.nvim);
+        let highlight = if error { "ErrorMsg" } else { "Comment" };
+        let text = text.trim().lines().collect::<Vec<_>>().join(" | ");
+        let _ = buffer.clear_namespace(&mut nvim, namespace, 0, -1);
+        let chunk = Value::Array(vec![
+            Value::from(format!("  {}", text)),
+            Value::from(highlight),
+        ]);
+        if let Err(e) =
+            buffer.set_virtual_text(&mut nvim, namespace, line.max(1) - 1, vec![chunk], vec![])
+        {
+            info!("[MAIN] Could not set virtual text: {}", e);
+        }
+    }
+
+    /// stop live mode, removing its virtual text
+    fn disable_live(&mut self) {
+        let namespace = self.namespaces.get(LIVE_NAMESPACE).copied();
+        if let (Some(live), Some(namespace)) = (self.live.take(), namespace) {
+            let _ = live
+                .buffer
+                .clear_namespace(&mut lock(&self.nvim), namespace, 0, -1);
+        }
+    }
+
+    /// same as display_output, but for errors
+    fn display_error(&mut self, error: &SniprunError) {
+        echo_error(&self.nvim, error);
+    }
+}
+
+/// the data of a live run: it always replaces the previous one, and shows its result as virtual
+/// text only
+fn live_data(mut data: DataHolder) -> DataHolder {
+    data.live = true;
+    data.run_policy = RunPolicy::Cancel;
+    data.stream_output = false;
+    data.result_function = None;
+    data
 }
-enum HandleAction {
-    New(thread::JoinHandle<()>),
+
+/// start a run in its own thread: the previous run of the same buffer is cancelled first, unless
+/// runs are queued, or the run is dropped if one of that buffer is in progress and the user asked
+/// for it. The runs of the other buffers go on.
+/// Returns the id of the run, None if it was dropped
+fn spawn_run(meh: Arc<Mutex<EventHandler>>, data: DataHolder) -> Option<u64> {
+    let (run_queue, run_slots, buffer, number) = {
+        let mut handler = lock(&meh);
+        let buffer = lock(&handler.nvim).get_current_buf().ok();
+        let number = buffer
+            .as_ref()
+            .and_then(|buffer| buffer.get_number(&mut lock(&handler.nvim)).ok());
+        match data.run_policy {
+            RunPolicy::Cancel => {
+                handler.jobs.cancel_in(number);
+            }
+            RunPolicy::Drop if handler.jobs.count_in(number) > 0 => {
+                info!("[MAINLOOP] A run of the buffer is in progress, the new one is dropped");
+                return None;
+            }
+            _ => (),
+        }
+        if let Some(buffer) = &buffer {
+            handler.clear_error_lines(buffer);
+        }
+        (
+            handler.run_queue.clone(),
+            handler.run_slots.clone(),
+            buffer,
+            number,
+        )
+    };
+
+    let cancel = data.cancel.clone();
+    let cloned_meh = meh.clone();
+    let handle = thread::spawn(move || {
+        let run = panic::catch_unwind(AssertUnwindSafe(|| {
+            run_and_deliver(&meh, data, &run_queue, &run_slots, buffer)
+        }));
+        if let Err(panic) = run {
+            let message = panic_message(&*panic);
+            info!("[MAINLOOP] The run panicked: {}", message);
+            let nvim = lock(&meh).nvim.clone();
+            echo_error(&nvim, &SniprunError::InternalError(message));
+        }
+    });
+    let id = lock(&cloned_meh).jobs.add(handle, cancel, number);
+    info!("[MAINLOOP] Run {} started", id);
+    Some(id)
+}
+
+/// run the code, then deliver its result. The handler is locked only briefly, never while
+/// the code runs
+fn run_and_deliver(
+    meh: &Mutex<EventHandler>,
+    mut data: DataHolder,
+    run_queue: &Mutex<()>,
+    run_slots: &RunSlots,
+    buffer: Option<Buffer>,
+) {
+    let _turn = if data.run_policy == RunPolicy::Queue {
+        Some(lock(run_queue))
+    } else {
+        None
+    };
+    let _slot = match run_slots.acquire(data.max_concurrent_runs, &data.cancel) {
+        Some(slot) => slot,
+        None => return,
+    };
+    if data.cancel.is_cancelled() {
+        return;
+    }
+    debug!("[MAINLOOP] Running with data: {:?}", data.summary());
+
+    //forward the streamed lines to a scratch buffer as they arrive
+    let mut forwarder = None;
+    if data.stream_output {
+        let buffer = lock(meh).open_output_buffer();
+        match buffer {
+            Ok(buffer) => {
+                let (sender, receiver) = mpsc::channel::<String>();
+                data.output_stream = OutputStream::new(sender);
+                let nvim = lock(meh).nvim.clone();
+                forwarder = Some(thread::spawn(move || {
+                    let mut count = 0;
+                    for (i, line) in receiver.iter().enumerate() {
+                        //the new buffer starts with an empty line, replace it
+                        let start = if i == 0 { 0 } else { -1 };
+                        let _ = buffer.set_lines(&mut lock(&nvim), start, -1, false, vec![line]);
+                        count += 1;
+                    }
+                    count
+                }));
+            }
+            Err(e) => info!("[MAINLOOP] Streaming disabled: {}", e),
+        }
+    }
+
+    //echo the phases of the run (eg: a long compilation), the result replaces them
+    let progress_shown = Arc::new(AtomicBool::new(false));
+    if data.show_progress && data.display && !data.live {
+        let nvim = lock(meh).nvim.clone();
+        let shown = progress_shown.clone();
+        data.progress = Progress::new(move |phase| {
+            shown.store(true, Ordering::SeqCst);
+            // the phases are sniprun's own words, there's nothing to escape
+            let _ = lock(&nvim).command(&format!("redraw | echo 'Sniprun: {}...'", phase));
+        });
+    }
+
+    //run the launcher (that selects, init and run an interpreter)
+    let mut launcher = launcher::Launcher::new(data);
+    let start = Instant::now();
+    let result = launcher
+        .select_and_run()
+        .map(|execution| launcher.data.truncate_output(execution));
+    if progress_shown.load(Ordering::SeqCst) {
+        let _ = lock(&lock(meh).nvim).command("echo ''");
+    }
+    info!("[MAINLOOP] Interpreter return a result");
+    let run_result = launcher.run_result(&result, start.elapsed());
+    if launcher.data.run_log {
+        if let Err(e) = launcher.data.log_run(&run_result.to_record(2000)) {
+            info!("[MAINLOOP] Could not log the run: {}", e);
+        }
+    }
+    let display = launcher.data.display && !launcher.data.json_output;
+    let json_output = launcher.data.json_output;
+    let history_size = launcher.data.history_size;
+    let persist_history = launcher.data.persist_history;
+    let history_file = launcher.data.history_file();
+    let filetype = launcher.data.filetype.clone();
+    let live = launcher.data.live;
+    let last_line = launcher.data.range[1];
+    let result_function = launcher.data.result_function.clone();
+    let cancel = launcher.data.cancel.clone();
+    let generated_file = launcher.generated_file.take();
+
+    //the launcher (and the stream sender it holds) is gone, wait for the
+    //last lines to be forwarded. The interpreter may not support streaming,
+    //in which case the output is displayed as usual
+    drop(launcher);
+    let streamed = forwarder
+        .map(|forwarder| forwarder.join().unwrap_or(0) > 0)
+        .unwrap_or(false);
+
+    //update sniprun's state in one short lock, then talk to neovim without holding it
+    let nvim = {
+        let mut handler = lock(meh);
+        //another run started meanwhile, this result is stale
+        if cancel.is_cancelled() {
+            info!("[MAINLOOP] Run cancelled, its result is dropped");
+            return;
+        }
+        handler.set_generated_file(generated_file);
+        if !live {
+            handler.history_in(&history_file).push(
+                HistoryEntry::from_run(&run_result, &filetype),
+                history_size,
+                persist_history,
+            );
+        }
+        if let (Some(buffer), Err(e)) = (&buffer, &result) {
+            if let Some(line) = e.line() {
+                handler.highlight_error_line(buffer, line);
+            }
+        }
+        if live {
+            match &result {
+                Ok(execution) => {
+                    handler.display_virtual_text(last_line, &execution.to_display_string(), false)
+                }
+                Err(e) => handler.display_virtual_text(last_line, &e.to_string(), true),
+            }
+        }
+        handler.nvim.clone()
+    };
+
+    if json_output {
+        send_json(&nvim, &run_result.to_json());
+    }
+
+    if let Some(function) = result_function {
+        let called = lock(&nvim).call_function(&function, vec![run_result.to_value()]);
+        if let Err(e) = called {
+            info!("[MAINLOOP] Could not call {}: {}", function, e);
+        }
+    }
+
+    // return Ok(result) or Err(sniprunerror)
+    match result {
+        Ok(execution) => {
+            info!(
+                "[MAINLOOP] Returning stdout of code run: {}",
+                execution.stdout
+            );
+            if !live && !streamed && display {
+                echo_output(&nvim, &execution.to_display_string());
+            }
+        }
+        Err(e) => {
+            info!("[MAINLOOP] Returning an error");
+            if !live && display {
+                echo_error(&nvim, &e);
+            }
+        }
+    };
+}
+
+/// lock the mutex, even if a thread panicked while holding it: sniprun's state stays usable
+/// (at worst, a run's result is half delivered)
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// the message a thread panicked with
+fn panic_message(panic: &(dyn std::any::Any + Send)) -> String {
+    panic
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| panic.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| String::from("unknown panic"))
+}
+
+/// display some output in neovim's message area, line by line.
+/// The lines are sent as-is through the API (no `:echo`), so no escaping is needed;
+/// only the final newline is removed
+fn echo_output(nvim: &Mutex<Neovim>, output: &str) {
+    let mut nvim = lock(nvim);
+    for line in output.lines() {
+        let _ = nvim.out_write(&format!("{}\n", line));
+    }
+}
+
+/// same as echo_output, but for errors
+fn echo_error(nvim: &Mutex<Neovim>, error: &SniprunError) {
+    let mut nvim = lock(nvim);
+    for line in error.to_string().lines() {
+        let _ = nvim.err_write(&format!("{}\n", line));
+    }
+}
+
+/// give the JSON result of a run to the plugins listening for it
+fn send_json(nvim: &Mutex<Neovim>, json: &str) {
+    let mut nvim = lock(nvim);
+    let sent = nvim
+        .set_var("sniprun_json", Value::from(json))
+        .and_then(|_| nvim.command("doautocmd <nomodeline> User SniprunResult"));
+    if let Err(e) = sent {
+        info!("[MAIN] Could not send the JSON result: {}", e);
+    }
+}
+
+/// the session with neovim: over stdio when neovim started sniprun (the default), else
+/// connected to a running neovim with `--tcp ADDRESS` or `--socket PATH` (eg: `nvim --headless
+/// --listen 127.0.0.1:6666`), so that a test or another tool can drive it. The boolean tells
+/// whether sniprun connected by itself
+fn connect(mut args: impl Iterator<Item = String>) -> Result<(Session, bool), String> {
+    let session = match (args.next().as_deref(), args.next()) {
+        (None, _) => return Ok((Session::new_parent().map_err(|e| e.to_string())?, false)),
+        (Some("--tcp"), Some(address)) => Session::new_tcp(&address),
+        #[cfg(unix)]
+        (Some("--socket"), Some(path)) => Session::new_unix_socket(&path),
+        (Some(arg), _) => {
+            return Err(format!(
+                "unexpected argument {}, usage: sniprun [--tcp ADDRESS | --socket PATH]",
+                arg
+            ))
+        }
+    };
+    Ok((session.map_err(|e| e.to_string())?, true))
+}
+
+/// tell neovim the channel sniprun is connected on, in g:sniprun_channel
+fn announce_channel(nvim: &mut Neovim) -> Result<(), CallError> {
+    let channel = nvim
+        .get_api_info()?
+        .into_iter()
+        .next()
+        .unwrap_or(Value::Nil);
+    nvim.set_var("sniprun_channel", channel)
 }
 
 fn main() {
-    let mut event_handler = EventHandler::new();
-    let _ = log_to_file(
-        &format!("{}/{}", event_handler.data.work_dir, "sniprun.log"),
-        LevelFilter::Info,
-    );
+    let (session, connected) = connect(std::env::args().skip(1)).unwrap_or_else(|e| {
+        eprintln!("sniprun: {}", e);
+        std::process::exit(1)
+    });
+    let event_handler = EventHandler::new(session);
+    let _ = log_to_file(event_handler.data.log_file(), LevelFilter::Info);
 
     info!("[MAIN] SnipRun launched successfully");
 
-    let receiver = event_handler.nvim.session.start_event_loop_channel();
+    let receiver = lock(&event_handler.nvim).session.start_event_loop_channel();
+    if connected {
+        // neovim didn't start sniprun, it has to be told the channel to send the events to
+        match announce_channel(&mut lock(&event_handler.nvim)) {
+            Ok(()) => info!("[MAIN] Connected to neovim, channel in g:sniprun_channel"),
+            Err(e) => info!("[MAIN] Could not tell neovim the channel: {}", e),
+        }
+    }
     let meh = Arc::new(Mutex::new(event_handler));
 
-    let (send, recv) = mpsc::channel();
-    thread::spawn(move || {
-        let mut _handle: Option<thread::JoinHandle<()>> = None;
-        loop {
-            match recv.recv() {
-                Err(_) => panic!("Broken connection"),
-                Ok(HandleAction::New(new)) => _handle = Some(new),
-            }
-        }
+    //join the threads of the finished runs, even when no new run starts
+    let cloned_meh = meh.clone();
+    thread::spawn(move || loop {
+        thread::sleep(Duration::from_secs(1));
+        lock(&cloned_meh).jobs.reap();
     });
 
     //main loop
@@ -167,57 +1596,242 @@ fn main() {
     for (event, values) in receiver {
         match Messages::from(event.clone()) {
             //Run command
-            Messages::Run => {
+            message @ Messages::Run
+            | message @ Messages::RunToEnd
+            | message @ Messages::RunFile => {
                 info!("[MAINLOOP] Run command received");
+                let scope = match message {
+                    Messages::RunToEnd => RunScope::ToEnd,
+                    Messages::RunFile => RunScope::Buffer,
+                    _ => RunScope::Selection,
+                };
+
+                // get up-to-date data: the code selected now, even if the run has to wait
+                let fill_result = lock(&meh).fill_data(values, scope);
+                let data = match fill_result {
+                    Ok(data) => data,
+                    Err(e) => {
+                        info!("[MAINLOOP] Could not gather data for the run: {}", e);
+                        lock(&meh).display_error(&e);
+                        continue;
+                    }
+                };
+                lock(&meh).last_run = Some(data.clone());
+                spawn_run(meh.clone(), data);
+            }
+            Messages::Replay => {
+                info!("[MAINLOOP] Replay command received");
+                let last_run = lock(&meh).last_run.clone();
+                let mut data = match last_run {
+                    Some(data) => data,
+                    None => {
+                        lock(&meh).display_output("nothing to replay yet, run some code first");
+                        continue;
+                    }
+                };
+                // the code is the one captured by the last run, even if the buffer changed
+                // since; the config is the current one (eg: to show the result differently)
+                if let Some(config) = values.first() {
+                    data.load_config(config);
+                }
+                data.cancel = CancelToken::default();
+                spawn_run(meh.clone(), data);
+            }
+            Messages::Clean => {
+                info!("[MAINLOOP] Clean command received");
+                let target = values.first().and_then(Value::as_str).unwrap_or("").trim();
+                let mut handler = lock(&meh);
+                //the work directory and the interpreters keeping a state (eg: a REPL) depend
+                //on the user's config
+                let mut data = DataHolder::new();
+                if let Some(config) = values.get(1) {
+                    data.load_config(config);
+                }
+                //history and cache only forget what they name
+                let forget_only = target == "history" || target == "cache";
+                if !forget_only {
+                    launcher::Launcher::cleanup(target);
+                }
+                let cleaned = if forget_only {
+                    Ok(())
+                } else {
+                    launcher::Launcher::new(data.clone()).clean_repl_memory(target)
+                };
+                if let Err(e) = cleaned {
+                    handler.display_error(&e);
+                }
+                let result = if target.is_empty() {
+                    cache::clear(&data.work_dir);
+                    handler.history_in(&data.history_file()).clear();
+                    data.clean_dir()
+                } else if target == "history" {
+                    handler.history_in(&data.history_file()).clear();
+                    Ok(())
+                } else if target == "cache" {
+                    cache::clear(&data.work_dir);
+                    Ok(())
+                } else {
+                    let dir_names = launcher::Launcher::work_dir_names_of(target);
+                    if dir_names.is_empty() {
+                        Err(SniprunError::CustomError(format!(
+                            "no interpreter named or supporting '{}'",
+                            target
+                        )))
+                    } else {
+                        data.clean_interpreter_dirs(&dir_names)
+                    }
+                };
+                if let Err(e) = result {
+                    handler.display_error(&e);
+                }
+            }
+
+            Messages::SetLogLevel => {
+                let level = values.first().and_then(Value::as_str).unwrap_or("");
+                if let Err(e) = set_log_level(level) {
+                    lock(&meh).display_error(&e);
+                }
+            }
+            Messages::ShowLog => {
+                info!("[MAINLOOP] Show log command received");
+                let handler = lock(&meh);
+                let log_file = handler.data.log_file().replace(' ', "\\ ");
+                let _ = lock(&handler.nvim).command(&format!("sview + {}", log_file));
+            }
+            Messages::ShowCode => {
+                info!("[MAINLOOP] Show code command received");
+                let mut handler = lock(&meh);
+                if let Err(e) = handler.show_generated_file() {
+                    handler.display_error(&e);
+                }
+            }
+            Messages::ShowHistory => {
+                info!("[MAINLOOP] Show history command received");
+                // how many runs to show, all of them if not given
+                let count = values.first().and_then(|v| {
+                    v.as_u64()
+                        .or_else(|| v.as_str().and_then(|count| count.trim().parse().ok()))
+                });
+                let mut handler = lock(&meh);
+                if let Err(e) = handler.show_history(count.map(|count| count as usize)) {
+                    handler.display_error(&e);
+                }
+            }
+            Messages::Info => {
+                info!("[MAINLOOP] Info command received");
+                let mut handler = lock(&meh);
+                match handler.fill_data(values, RunScope::Selection) {
+                    Ok(data) => {
+                        let mut report = EventHandler::selection_report(data);
+                        report.push_str(&format!("runs in progress: {}\n", handler.jobs.count()));
+                        handler.display_output(&report);
+                    }
+                    Err(e) => handler.display_error(&e),
+                }
+            }
 
+            Messages::EnableLive => {
+                info!("[MAINLOOP] Enable live command received");
+                let fill_result = lock(&meh).fill_data(values.clone(), RunScope::Selection);
+                let data = match fill_result {
+                    Ok(data) => data,
+                    Err(e) => {
+                        lock(&meh).display_error(&e);
+                        continue;
+                    }
+                };
+                let buffer = {
+                    let handler = lock(&meh);
+                    let buffer = lock(&handler.nvim).get_current_buf();
+                    buffer
+                };
+                match buffer {
+                    Ok(buffer) => {
+                        let mut handler = lock(&meh);
+                        handler.disable_live();
+                        handler.live = Some(LiveSession {
+                            values,
+                            buffer,
+                            delay: data.live_delay,
+                            generation: 0,
+                        });
+                    }
+                    Err(e) => {
+                        info!("[MAINLOOP] Could not enable live mode: {}", e);
+                        continue;
+                    }
+                }
+                spawn_run(meh.clone(), live_data(data));
+            }
+            Messages::DisableLive => {
+                info!("[MAINLOOP] Disable live command received");
+                lock(&meh).disable_live();
+            }
+            Messages::BufferChanged => {
+                // debounce: wait for the buffer to stay unchanged for a while before running
+                let (generation, delay) = match lock(&meh).live.as_mut() {
+                    Some(live) => {
+                        live.generation += 1;
+                        (live.generation, live.delay)
+                    }
+                    None => continue,
+                };
                 let cloned_meh = meh.clone();
-                let _res2 = send.send(HandleAction::New(thread::spawn(move || {
-                    // get up-to-date data
-                    //
-                    cloned_meh.lock().unwrap().fill_data(values);
-
-                    //run the launcher (that selects, init and run an interpreter)
-                    let launcher = launcher::Launcher::new(cloned_meh.lock().unwrap().data.clone());
-                    let result = launcher.select_and_run();
-                    info!("[MAINLOOP] Interpreter return a result");
-
-                    // return Ok(result) or Err(sniprunerror)
-                    match result {
-                        Ok(answer_str) => {
-                            let mut answer_str = answer_str.clone();
-                            answer_str = answer_str.replace("\\\"", "\"");
-                            answer_str = answer_str.replace("\"", "\\\"");
-                            //make sure there is no lone "
-                            let len_without_newline = answer_str.trim_end().len();
-                            answer_str.truncate(len_without_newline);
-
-                            info!("[MAINLOOP] Returning stdout of code run: {}", answer_str);
-
-                            let _ = cloned_meh
-                                .lock()
-                                .unwrap()
-                                .nvim
-                                .command(&format!("echo \"{}\"", answer_str));
-                        }
-                        Err(e) => {
-                            info!("[MAINLOOP] Returning an error");
-                            let _ = cloned_meh
-                                .lock()
-                                .unwrap()
-                                .nvim
-                                .err_writeln(&format!("{}", e));
+                thread::spawn(move || {
+                    thread::sleep(delay);
+                    let data = {
+                        let handler = lock(&cloned_meh);
+                        let live = match &handler.live {
+                            Some(live) if live.generation == generation => live,
+                            _ => return,
+                        };
+                        //the user went to another buffer meanwhile
+                        let current_buffer = lock(&handler.nvim).get_current_buf();
+                        if current_buffer.ok().as_ref() != Some(&live.buffer) {
+                            return;
                         }
+                        handler.fill_data(live.values.clone(), RunScope::Selection)
                     };
+                    match data {
+                        Ok(data) => {
+                            spawn_run(cloned_meh, live_data(data));
+                        }
+                        Err(e) => info!("[MAINLOOP] Live run skipped: {}", e),
+                    }
+                });
+            }
 
-                    //display ouput in nvim
-
-                    //clean data
-                    cloned_meh.lock().unwrap().data = DataHolder::new();
-                })));
+            Messages::Capabilities => {
+                info!("[MAINLOOP] Capabilities command received");
+                let nvim = lock(&meh).nvim.clone();
+                let mut nvim = lock(&nvim);
+                let sent = nvim
+                    .set_var("sniprun_capabilities", EventHandler::capabilities())
+                    .and_then(|_| nvim.command("doautocmd <nomodeline> User SniprunCapabilities"));
+                if let Err(e) = sent {
+                    info!("[MAINLOOP] Could not send the capabilities: {}", e);
+                }
             }
-            Messages::Clean => {
-                info!("[MAINLOOP] Clean command received");
-                meh.clone().lock().unwrap().data.clean_dir()
+
+            Messages::Stop => {
+                info!("[MAINLOOP] Stop command received");
+                // the id of the run to stop, every run if none is given
+                let id = values.first().and_then(|v| {
+                    v.as_u64()
+                        .or_else(|| v.as_str().and_then(|id| id.trim().parse().ok()))
+                });
+                let mut handler = lock(&meh);
+                let running = handler.jobs.ids();
+                if let Some(id) = id.filter(|id| !running.contains(id)) {
+                    let e = SniprunError::CustomError(format!(
+                        "no run {} in progress (running: {:?})",
+                        id, running
+                    ));
+                    handler.display_error(&e);
+                    continue;
+                }
+                let stopped = handler.jobs.cancel(id);
+                handler.display_output(&format!("{} run(s) stopped", stopped));
             }
 
             Messages::Unknown(event) => {
@@ -225,4 +1839,307 @@ fn main() {
             }
         }
     }
+    // neovim exited: the persistent processes of the interpreters must not outlive it
+    info!("[MAIN] End of main event loop, cleaning up");
+    launcher::Launcher::cleanup("");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cleaning_keeps_the_log() {
+        let mut data = DataHolder::with_code_in_temp_dir("python", "");
+        let work_dir = PathBuf::from(&data.work_dir);
+        std::fs::write(data.log_file(), "log").unwrap();
+        std::fs::write(data.history_file(), "{}").unwrap();
+        std::fs::create_dir_all(work_dir.join("python3_original")).unwrap();
+
+        data.clean_dir().unwrap();
+        let left: Vec<_> = std::fs::read_dir(&work_dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(left, vec![LOG_FILE]);
+        assert_eq!(std::fs::read_to_string(data.log_file()).unwrap(), "log");
+    }
+
+    #[test]
+    fn the_work_dir_can_be_set() {
+        let temp = DataHolder::with_code_in_temp_dir("python", "");
+        let dir = temp.work_dir.clone() + "/custom";
+        let mut data = DataHolder::new();
+        data.load_config(&Value::Map(vec![(
+            Value::from("work_dir"),
+            Value::from(dir.as_str()),
+        )]));
+        assert_eq!(data.work_dir, dir);
+        assert!(Path::new(&dir).is_dir());
+        assert_eq!(data.history_file(), dir.clone() + "/history.jsonl");
+        assert_eq!(data.log_file(), dir + "/sniprun.log");
+    }
+
+    #[test]
+    fn an_unusable_work_dir_is_ignored() {
+        let default = DataHolder::new().work_dir;
+        let temp = DataHolder::with_code_in_temp_dir("python", "");
+        let file = temp.work_dir.clone() + "/file";
+        std::fs::write(&file, "").unwrap();
+        for dir in ["", file.as_str()] {
+            let mut data = DataHolder::new();
+            data.load_config(&Value::Map(vec![(
+                Value::from("work_dir"),
+                Value::from(dir),
+            )]));
+            assert_eq!(data.work_dir, default);
+        }
+    }
+
+    #[test]
+    fn the_support_level_can_be_capped() {
+        let mut data = DataHolder::new();
+        data.load_config(&Value::Map(vec![(
+            Value::from("support_level"),
+            Value::from("Line"),
+        )]));
+        assert_eq!(data.support_level, Some(SupportLevel::Line));
+        assert_eq!(
+            data.support_level_for(SupportLevel::Bloc),
+            SupportLevel::Line
+        );
+
+        // the buffer asks for more, the interpreter can't do it
+        data.ask_support_level("import", "b:sniprun_support_level");
+        assert_eq!(
+            data.support_level_for(SupportLevel::Bloc),
+            SupportLevel::Bloc
+        );
+    }
+
+    #[test]
+    fn an_invalid_support_level_is_ignored() {
+        let mut data = DataHolder::new();
+        data.load_config(&Value::Map(vec![(
+            Value::from("support_level"),
+            Value::from("everything"),
+        )]));
+        assert_eq!(data.support_level, None);
+
+        data.ask_support_level("bloc", "support_level");
+        data.ask_support_level("blocs", "b:sniprun_support_level");
+        assert_eq!(data.support_level, Some(SupportLevel::Bloc));
+    }
+
+    #[test]
+    fn the_scope_gives_the_lines_to_run() {
+        let selection = vec![[4, 6], [1, 2]];
+        assert_eq!(
+            RunScope::Selection.ranges(selection.clone(), 10),
+            vec![[1, 2], [4, 6]]
+        );
+        assert_eq!(RunScope::ToEnd.ranges(vec![[4, 6]], 10), vec![[4, 10]]);
+        assert_eq!(RunScope::Buffer.ranges(selection, 10), vec![[1, 10]]);
+
+        // the cursor on the last line
+        assert_eq!(RunScope::ToEnd.ranges(vec![[10, 10]], 10), vec![[10, 10]]);
+        // the buffer shrank since
+        assert_eq!(RunScope::ToEnd.ranges(vec![[12, 12]], 10), vec![[10, 10]]);
+        // an empty buffer
+        for scope in [RunScope::Selection, RunScope::ToEnd, RunScope::Buffer] {
+            assert_eq!(scope.ranges(vec![[1, 1]], 0), vec![[1, 1]]);
+        }
+    }
+
+    #[test]
+    fn the_run_events_are_told_apart() {
+        let message = |event: &str| Messages::from(event.to_string());
+        assert!(matches!(message("run_to_eof"), Messages::RunToEnd));
+        assert!(matches!(message("run_buffer"), Messages::RunFile));
+        assert!(matches!(message("run_file"), Messages::RunFile));
+        assert!(matches!(message("run"), Messages::Run));
+    }
+
+    #[test]
+    fn the_filetype_is_parsed_from_set_ft() {
+        assert_eq!(parse_filetype("  filetype=rust"), "rust");
+        assert_eq!(
+            parse_filetype("\n  filetype=javascript.jsx \n"),
+            "javascript.jsx"
+        );
+        assert_eq!(parse_filetype("  filetype="), "");
+        assert_eq!(parse_filetype(""), "");
+        assert_eq!(parse_filetype("E518: Unknown option: ft"), "");
+        assert_eq!(parse_filetype("  syntax=c"), "");
+    }
+
+    #[test]
+    fn the_limits_of_an_interpreter_override_the_global_ones() {
+        let map = |entries: Vec<(&str, Value)>| {
+            Value::Map(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (Value::from(key), value))
+                    .collect(),
+            )
+        };
+        let mut data = DataHolder::with_code_in_temp_dir("rust", "");
+        data.load_config(&map(vec![
+            ("timeout", Value::from(10)),
+            ("max_memory_mb", Value::from(100)),
+            (
+                "limits",
+                map(vec![
+                    (
+                        "rust",
+                        map(vec![
+                            ("timeout", Value::from(60)),
+                            ("max_cpu_seconds", 5.into()),
+                        ]),
+                    ),
+                    (
+                        "Rust_original",
+                        map(vec![
+                            ("timeout", Value::from(90)),
+                            ("max_memory_mb", 0.into()),
+                        ]),
+                    ),
+                    ("Python3_original", map(vec![("timeout", Value::from(1.5))])),
+                ]),
+            ),
+        ]));
+
+        let rust = data.with_limits("Rust_original");
+        assert_eq!(rust.timeout, Some(Duration::from_secs(90)));
+        assert_eq!(rust.max_cpu_seconds, Some(5));
+        assert_eq!(rust.max_memory_mb, None);
+
+        // the filetype ones, for another interpreter of the filetype
+        let other = data.with_limits("Other_original");
+        assert_eq!(other.timeout, Some(Duration::from_secs(60)));
+        assert_eq!(other.max_memory_mb, Some(100));
+
+        // no entry: the global ones
+        data.filetype = String::from("python");
+        let python = data.with_limits("Python3_original");
+        assert_eq!(python.timeout, Some(Duration::from_millis(1500)));
+        let unset = data.with_limits("Jupyter_original");
+        assert_eq!(unset.timeout, Some(Duration::from_secs(10)));
+        assert_eq!(unset.max_memory_mb, Some(100));
+        assert_eq!(unset.max_cpu_seconds, None);
+    }
+
+    /// a neovim answering the handshake of a connected sniprun on the stream: the msgpack
+    /// requests are checked byte for byte
+    fn fake_neovim(mut stream: impl std::io::Read + std::io::Write) {
+        fn expect(stream: &mut impl std::io::Read, request: &[u8]) {
+            let mut received = vec![0; request.len()];
+            stream.read_exact(&mut received).unwrap();
+            assert_eq!(received, request);
+        }
+        // [0, 0, "nvim_get_api_info", []]
+        expect(
+            &mut stream,
+            &[&[0x94, 0, 0, 0xb1][..], b"nvim_get_api_info", &[0x90]].concat(),
+        );
+        // [1, 0, nil, [5, {}]]: the channel 5
+        stream
+            .write_all(&[0x94, 1, 0, 0xc0, 0x92, 5, 0x80])
+            .unwrap();
+        // [0, 1, "nvim_set_var", ["sniprun_channel", 5]]
+        expect(
+            &mut stream,
+            &[
+                &[0x94, 0, 1, 0xac][..],
+                b"nvim_set_var",
+                &[0x92, 0xaf],
+                b"sniprun_channel",
+                &[5],
+            ]
+            .concat(),
+        );
+        stream.write_all(&[0x94, 1, 1, 0xc0, 0xc0]).unwrap();
+    }
+
+    fn connect_to(args: &[&str]) -> Result<(Session, bool), String> {
+        connect(args.iter().map(|arg| arg.to_string()))
+    }
+
+    fn announce(session: Session) -> Result<(), CallError> {
+        let mut nvim = Neovim::new(session);
+        nvim.session.start_event_loop();
+        announce_channel(&mut nvim)
+    }
+
+    #[test]
+    fn sniprun_connects_over_tcp_and_announces_its_channel() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let neovim = thread::spawn(move || fake_neovim(listener.accept().unwrap().0));
+
+        let (session, connected) = connect_to(&["--tcp", &address]).unwrap();
+        assert!(connected);
+        announce(session).unwrap();
+        neovim.join().unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn sniprun_connects_over_a_socket_and_announces_its_channel() {
+        let temp = DataHolder::with_code_in_temp_dir("python", "");
+        let path = format!("{}/nvim.sock", temp.work_dir);
+        let listener = std::os::unix::net::UnixListener::bind(&path).unwrap();
+        let neovim = thread::spawn(move || fake_neovim(listener.accept().unwrap().0));
+
+        let (session, connected) = connect_to(&["--socket", &path]).unwrap();
+        assert!(connected);
+        announce(session).unwrap();
+        neovim.join().unwrap();
+    }
+
+    #[test]
+    fn bad_arguments_or_addresses_are_errors() {
+        let usage = |args: &[&str]| connect_to(args).err().unwrap_or_default();
+        assert!(usage(&["--verbose"]).contains("unexpected argument --verbose, usage:"));
+        assert!(usage(&["--tcp"]).contains("unexpected argument --tcp"));
+        assert!(usage(&["--socket"]).contains("unexpected argument --socket"));
+
+        // nothing listens there
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        drop(listener);
+        assert!(connect_to(&["--tcp", &address]).is_err());
+        assert!(connect_to(&["--socket", "/nonexistent/nvim.sock"]).is_err());
+    }
+
+    #[test]
+    fn a_disabled_interpreter_is_never_enabled() {
+        let list = |names: &[&str]| Value::Array(names.iter().map(|&n| Value::from(n)).collect());
+        let mut data = DataHolder::with_code_in_temp_dir("python", "");
+        assert!(data.is_enabled("Python3_original"));
+        assert!(data.is_enabled("Generic"));
+
+        data.load_config(&Value::Map(vec![
+            (
+                Value::from("enabled_interpreters"),
+                list(&["python3_original", "Jupyter_original", "Generic"]),
+            ),
+            (
+                Value::from("disabled_interpreters"),
+                list(&["Jupyter_original"]),
+            ),
+        ]));
+        // the names are compared ignoring the case
+        assert!(data.is_enabled("Python3_original"));
+        assert!(data.is_enabled("Generic"));
+        // disabled, even if it is also enabled
+        assert!(!data.is_enabled("Jupyter_original"));
+        // not in the allow-list
+        assert!(!data.is_enabled("Rust_original"));
+
+        // without an allow-list, everything but the disabled ones
+        data.enabled_interpreters = None;
+        assert!(data.is_enabled("Rust_original"));
+        assert!(!data.is_enabled("jupyter_original"));
+    }
 }